@@ -0,0 +1,299 @@
+//! Step benchmarks: time `Simulation::step` as a whole and per phase on fixed-seed scenarios
+//! (200², 500² and 1000² grids with 500, 2000 and 10000 hyphae).
+//!
+//! ```text
+//! cargo bench --bench step                                # every scenario
+//! cargo bench --bench step -- 500x500 /2000h              # scenarios matching any filter
+//! cargo bench --bench step -- --save baseline.json        # keep the results as a baseline
+//! cargo bench --bench step -- --baseline baseline.json    # compare against a saved baseline
+//! ```
+//!
+//! Options: `--steps N` measured steps per scenario (default 30), `--warmup N` untimed steps
+//! first (default 5), `--threshold PCT` slowdown reported as a regression (default 10).
+//! Exits with status 1 when a scenario regressed against the baseline.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use mycorust::profile::StepPhase;
+use mycorust::simulation::{create_rng, set_headless_mode, SimRng, Simulation};
+use mycorust::SimulationConfig;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+const SEED: u64 = 1;
+const GRID_SIZES: [usize; 3] = [200, 500, 1000];
+const HYPHAE_COUNTS: [usize; 3] = [500, 2000, 10000];
+
+struct Options {
+    filters: Vec<String>,
+    steps: usize,
+    warmup: usize,
+    save: Option<String>,
+    baseline: Option<String>,
+    threshold: f64,
+}
+
+impl Options {
+    fn parse() -> Result<Self, String> {
+        let mut options = Options {
+            filters: Vec::new(),
+            steps: 30,
+            warmup: 5,
+            save: None,
+            baseline: None,
+            threshold: 10.0,
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--bench" => {} // Passed by cargo bench
+                "--steps" => options.steps = parse_number(&value()?)?,
+                "--warmup" => options.warmup = parse_number(&value()?)?,
+                "--threshold" => options.threshold = parse_number(&value()?)?,
+                "--save" => options.save = Some(value()?),
+                "--baseline" => options.baseline = Some(value()?),
+                _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+                _ => options.filters.push(arg),
+            }
+        }
+        if options.steps == 0 {
+            return Err("--steps must be at least 1".into());
+        }
+        Ok(options)
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid number {}", value))
+}
+
+struct Scenario {
+    grid_size: usize,
+    hyphae: usize,
+}
+
+impl Scenario {
+    fn name(&self) -> String {
+        format!("{0}x{0}/{1}h", self.grid_size, self.hyphae)
+    }
+
+    fn build(&self) -> (Simulation, SimRng) {
+        let config = SimulationConfig {
+            seed: Some(SEED),
+            grid_size: self.grid_size,
+            initial_hyphae_count: self.hyphae,
+            max_hyphae: self.hyphae,
+            ..SimulationConfig::default()
+        };
+        let mut rng = create_rng(SEED);
+        let mut sim = Simulation::with_config(&mut rng, config);
+        // Spread the hyphae over the grid: the usual start packs them around the center,
+        // where thousands of them would fuse away within a few steps
+        let max = self.grid_size as f32 - 2.0;
+        for h in &mut sim.state.hyphae {
+            h.x = rng.gen_range(1.0..max);
+            h.y = rng.gen_range(1.0..max);
+            h.prev_x = h.x;
+            h.prev_y = h.y;
+        }
+        (sim, rng)
+    }
+}
+
+/// Mean milliseconds per step, overall and per phase
+#[derive(Serialize, Deserialize)]
+struct ScenarioResult {
+    step_ms: f64,
+    median_step_ms: f64,
+    phases_ms: BTreeMap<String, f64>,
+    live_hyphae: f64,
+}
+
+fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn run(scenario: &Scenario, options: &Options) -> ScenarioResult {
+    let (mut sim, mut rng) = scenario.build();
+    for _ in 0..options.warmup {
+        sim.step(&mut rng);
+    }
+
+    let mut step_times = Vec::with_capacity(options.steps);
+    let mut phases = [Duration::ZERO; StepPhase::ALL.len()];
+    let mut other = Duration::ZERO;
+    let mut live_hyphae = 0;
+    for _ in 0..options.steps {
+        sim.step(&mut rng);
+        let profile = &sim.profile;
+        step_times.push(profile.total);
+        for (total, &phase) in phases.iter_mut().zip(StepPhase::ALL.iter()) {
+            *total += profile.phase(phase);
+        }
+        other += profile.other();
+        live_hyphae += sim.state.hyphae.iter().filter(|h| h.alive).count();
+    }
+
+    let steps = options.steps as f64;
+    let mut phases_ms: BTreeMap<String, f64> = StepPhase::ALL
+        .iter()
+        .zip(phases)
+        .map(|(phase, total)| (phase.name().to_string(), ms(total) / steps))
+        .collect();
+    phases_ms.insert("other".to_string(), ms(other) / steps);
+    let step_ms = ms(step_times.iter().sum()) / steps;
+    step_times.sort();
+    ScenarioResult {
+        step_ms,
+        median_step_ms: ms(step_times[step_times.len() / 2]),
+        phases_ms,
+        live_hyphae: live_hyphae as f64 / steps,
+    }
+}
+
+fn phase_columns() -> Vec<&'static str> {
+    let mut columns: Vec<&str> = StepPhase::ALL.iter().map(|p| p.name()).collect();
+    columns.push("other");
+    columns
+}
+
+fn print_header() {
+    print!(
+        "{:<16} {:>9} {:>9} {:>7}",
+        "scenario", "ms/step", "median", "live"
+    );
+    for column in phase_columns() {
+        print!(" {:>11}", column);
+    }
+    println!();
+}
+
+fn print_row(name: &str, result: &ScenarioResult) {
+    print!(
+        "{:<16} {:>9.3} {:>9.3} {:>7.0}",
+        name, result.step_ms, result.median_step_ms, result.live_hyphae
+    );
+    for column in phase_columns() {
+        print!(
+            " {:>11.3}",
+            result.phases_ms.get(column).copied().unwrap_or(0.0)
+        );
+    }
+    println!();
+}
+
+/// Print the change against the baseline; returns true if any scenario got slower than allowed
+fn compare(
+    results: &BTreeMap<String, ScenarioResult>,
+    baseline: &BTreeMap<String, ScenarioResult>,
+    threshold: f64,
+) -> bool {
+    println!("\nChange against baseline (mean ms/step):");
+    let mut regressed = false;
+    for (name, result) in results {
+        let Some(base) = baseline.get(name) else {
+            println!("{:<16} not in baseline", name);
+            continue;
+        };
+        let change = (result.step_ms - base.step_ms) / base.step_ms * 100.0;
+        if change <= threshold {
+            println!("{:<16} {:>+7.1}%", name, change);
+            continue;
+        }
+        regressed = true;
+        // Name the phases that account for most of the slowdown
+        let mut slower: Vec<(&str, f64)> = result
+            .phases_ms
+            .iter()
+            .map(|(phase, ms)| {
+                let before = base.phases_ms.get(phase).copied().unwrap_or(0.0);
+                (phase.as_str(), ms - before)
+            })
+            .filter(|&(_, delta)| delta > 0.0)
+            .collect();
+        slower.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let phases: Vec<String> = slower
+            .iter()
+            .take(3)
+            .map(|(phase, delta)| format!("{} {:+.3} ms", phase, delta))
+            .collect();
+        println!(
+            "{:<16} {:>+7.1}%  REGRESSION ({})",
+            name,
+            change,
+            phases.join(", ")
+        );
+    }
+    regressed
+}
+
+fn main() {
+    let options = match Options::parse() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(2);
+        }
+    };
+    // Steps must never reach for the window
+    set_headless_mode(true);
+
+    let scenarios: Vec<Scenario> = GRID_SIZES
+        .iter()
+        .flat_map(|&grid_size| {
+            HYPHAE_COUNTS
+                .iter()
+                .map(move |&hyphae| Scenario { grid_size, hyphae })
+        })
+        .filter(|s| {
+            options.filters.is_empty() || options.filters.iter().any(|f| s.name().contains(f))
+        })
+        .collect();
+    if scenarios.is_empty() {
+        eprintln!("error: no scenario matches {}", options.filters.join(" "));
+        std::process::exit(2);
+    }
+
+    println!(
+        "{} warm-up + {} measured steps per scenario, seed {}, {} threads; times in ms per step",
+        options.warmup,
+        options.steps,
+        SEED,
+        rayon::current_num_threads()
+    );
+    print_header();
+    let mut results = BTreeMap::new();
+    for scenario in &scenarios {
+        let result = run(scenario, &options);
+        print_row(&scenario.name(), &result);
+        results.insert(scenario.name(), result);
+    }
+
+    if let Some(path) = &options.save {
+        let json = serde_json::to_string_pretty(&results).expect("results serialize");
+        if let Err(e) = std::fs::write(path, json) {
+            eprintln!("error: failed to write {}: {}", path, e);
+            std::process::exit(2);
+        }
+        println!("\nSaved results to {}", path);
+    }
+    if let Some(path) = &options.baseline {
+        let baseline: BTreeMap<String, ScenarioResult> = match std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+        {
+            Ok(baseline) => baseline,
+            Err(e) => {
+                eprintln!("error: failed to read baseline {}: {}", path, e);
+                std::process::exit(2);
+            }
+        };
+        if compare(&results, &baseline, options.threshold) {
+            std::process::exit(1);
+        }
+    }
+}
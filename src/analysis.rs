@@ -0,0 +1,390 @@
+// Network analytics - graph metrics over hyphae (nodes) and parent links + anastomosis
+// connections (edges), used to quantify how fusion and pruning parameters shape the network.
+// Path metrics count hops; lengths are Euclidean distances in grid units.
+
+use ::rand::seq::SliceRandom;
+use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+
+use crate::simulation::{create_rng, Simulation};
+
+// Above this many nodes, path metrics and betweenness are estimated from evenly spaced sources
+pub const EXACT_PATH_NODE_LIMIT: usize = 1500;
+
+// Fraction of edges removed at each fault tolerance step
+pub const FAULT_REMOVAL_FRACTIONS: [f32; 5] = [0.1, 0.2, 0.3, 0.4, 0.5];
+const FAULT_TRIALS: usize = 20;
+// Fixed seed so repeated analyses of the same state agree (and the run's rng is untouched)
+const FAULT_SEED: u64 = 0x6d79_636f;
+
+// Number of nodes listed in `BetweennessSummary::top`
+const TOP_BETWEENNESS: usize = 10;
+
+/// A node and its (normalized) betweenness centrality
+#[derive(Clone, Debug, Serialize)]
+pub struct NodeScore {
+    pub index: usize, // Slot in the hyphae list at this frame
+    pub id: u64,      // Hypha id
+    pub value: f32,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct BetweennessSummary {
+    pub mean: f32,
+    pub max: f32,
+    pub top: Vec<NodeScore>,
+}
+
+/// Network connectivity after removing a random fraction of edges (averaged over trials)
+#[derive(Clone, Debug, Serialize)]
+pub struct FaultTolerancePoint {
+    pub removed_fraction: f32,
+    // Size of the largest component relative to the intact network's largest component
+    pub largest_component_fraction: f32,
+}
+
+/// Topology metrics for the hyphal network
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct NetworkAnalysis {
+    pub frame_index: u64,
+    pub node_count: usize,
+    pub edge_count: usize,
+    pub parent_edges: usize,
+    pub anastomosis_edges: usize,
+    pub component_count: usize,
+    pub largest_component: usize,
+    pub loop_count: usize, // Cyclomatic number: edges - nodes + components
+    pub mean_degree: f32,
+    pub max_degree: usize,
+    pub degree_distribution: Vec<usize>, // Node count per degree (index = degree)
+    pub mean_shortest_path: f32,         // Mean hop count over connected node pairs
+    pub global_efficiency: f32,          // Mean of 1/hops over all node pairs (0 when unreachable)
+    pub betweenness: BetweennessSummary,
+    pub total_length: f32,
+    pub fault_tolerance: Vec<FaultTolerancePoint>,
+    pub sampled_sources: Option<usize>, // Set when path metrics were estimated from this many sources
+}
+
+// Disjoint-set forest for component counting
+struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+        }
+    }
+
+    fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+    }
+
+    // (component count, largest component size)
+    fn components(&mut self) -> (usize, usize) {
+        let mut count = 0;
+        let mut largest = 0;
+        for x in 0..self.parent.len() {
+            if self.find(x) == x {
+                count += 1;
+                largest = largest.max(self.size[x]);
+            }
+        }
+        (count, largest)
+    }
+}
+
+fn components(n: usize, edges: &[(usize, usize)]) -> (usize, usize) {
+    let mut uf = UnionFind::new(n);
+    for &(a, b) in edges {
+        uf.union(a, b);
+    }
+    uf.components()
+}
+
+// Hop-count path statistics and betweenness from one BFS per source (Brandes' algorithm)
+struct PathMetrics {
+    mean_shortest_path: f32,
+    global_efficiency: f32,
+    betweenness: Vec<f32>,
+    sampled_sources: Option<usize>,
+}
+
+fn path_metrics(adjacency: &[Vec<usize>]) -> PathMetrics {
+    let n = adjacency.len();
+    let sources: Vec<usize> = if n > EXACT_PATH_NODE_LIMIT {
+        (0..EXACT_PATH_NODE_LIMIT)
+            .map(|i| i * n / EXACT_PATH_NODE_LIMIT)
+            .collect()
+    } else {
+        (0..n).collect()
+    };
+
+    let mut betweenness = vec![0.0f64; n];
+    let mut distance_sum = 0.0f64;
+    let mut inverse_distance_sum = 0.0f64;
+    let mut reachable_pairs = 0u64;
+
+    let mut dist = vec![-1i64; n];
+    let mut sigma = vec![0.0f64; n];
+    let mut delta = vec![0.0f64; n];
+    let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut order: Vec<usize> = Vec::with_capacity(n);
+    let mut queue = VecDeque::new();
+
+    for &s in &sources {
+        for v in &order {
+            dist[*v] = -1;
+            sigma[*v] = 0.0;
+            delta[*v] = 0.0;
+            predecessors[*v].clear();
+        }
+        order.clear();
+
+        dist[s] = 0;
+        sigma[s] = 1.0;
+        queue.push_back(s);
+        while let Some(v) = queue.pop_front() {
+            order.push(v);
+            for &w in &adjacency[v] {
+                if dist[w] < 0 {
+                    dist[w] = dist[v] + 1;
+                    queue.push_back(w);
+                }
+                if dist[w] == dist[v] + 1 {
+                    sigma[w] += sigma[v];
+                    predecessors[w].push(v);
+                }
+            }
+        }
+
+        for &v in order.iter().skip(1) {
+            distance_sum += dist[v] as f64;
+            inverse_distance_sum += 1.0 / dist[v] as f64;
+            reachable_pairs += 1;
+        }
+
+        // Accumulate dependencies from the farthest nodes back towards the source
+        for &w in order.iter().rev() {
+            for &v in &predecessors[w] {
+                delta[v] += sigma[v] / sigma[w] * (1.0 + delta[w]);
+            }
+            if w != s {
+                betweenness[w] += delta[w];
+            }
+        }
+    }
+
+    // Every unordered pair is seen from both ends, and sampled sources are scaled up to all nodes
+    let scale = n as f64 / sources.len().max(1) as f64;
+    let normalization = if n > 2 {
+        ((n - 1) * (n - 2)) as f64
+    } else {
+        1.0
+    };
+    let betweenness = betweenness
+        .iter()
+        .map(|b| (b * scale / normalization) as f32)
+        .collect();
+
+    PathMetrics {
+        mean_shortest_path: if reachable_pairs > 0 {
+            (distance_sum / reachable_pairs as f64) as f32
+        } else {
+            0.0
+        },
+        global_efficiency: if n > 1 {
+            (inverse_distance_sum / (sources.len() * (n - 1)) as f64) as f32
+        } else {
+            0.0
+        },
+        betweenness,
+        sampled_sources: (sources.len() < n).then_some(sources.len()),
+    }
+}
+
+fn fault_tolerance(n: usize, edges: &[(usize, usize)], largest: usize) -> Vec<FaultTolerancePoint> {
+    let mut rng = create_rng(FAULT_SEED);
+    let mut shuffled = edges.to_vec();
+    FAULT_REMOVAL_FRACTIONS
+        .iter()
+        .map(|&fraction| {
+            let keep = edges.len() - (edges.len() as f32 * fraction).round() as usize;
+            let mut total = 0.0;
+            for _ in 0..FAULT_TRIALS {
+                shuffled.shuffle(&mut rng);
+                let (_, remaining) = components(n, &shuffled[..keep]);
+                total += remaining as f32 / largest.max(1) as f32;
+            }
+            FaultTolerancePoint {
+                removed_fraction: fraction,
+                largest_component_fraction: total / FAULT_TRIALS as f32,
+            }
+        })
+        .collect()
+}
+
+impl Simulation {
+    /// Compute topology metrics for the current hyphal network.
+    /// Nodes are all hyphae (living and dead); duplicate edges and self-loops are ignored.
+    pub fn analyze_network(&self) -> NetworkAnalysis {
+        let n = self.hyphae.len();
+        let mut seen = HashSet::new();
+        let mut edges = Vec::new();
+        let mut parent_edges = 0;
+        let mut anastomosis_edges = 0;
+        let mut total_length = 0.0;
+        for edge in self.graph_edges() {
+            let (a, b) = (edge.source.min(edge.target), edge.source.max(edge.target));
+            if a == b || b >= n || !seen.insert((a, b)) {
+                continue;
+            }
+            if edge.kind == "parent" {
+                parent_edges += 1;
+            } else {
+                anastomosis_edges += 1;
+            }
+            let (ha, hb) = (&self.hyphae[a], &self.hyphae[b]);
+            total_length += ((ha.x - hb.x).powi(2) + (ha.y - hb.y).powi(2)).sqrt();
+            edges.push((a, b));
+        }
+
+        let mut adjacency = vec![Vec::new(); n];
+        for &(a, b) in &edges {
+            adjacency[a].push(b);
+            adjacency[b].push(a);
+        }
+
+        let max_degree = adjacency.iter().map(Vec::len).max().unwrap_or(0);
+        let mut degree_distribution = vec![0; max_degree + 1];
+        for neighbors in &adjacency {
+            degree_distribution[neighbors.len()] += 1;
+        }
+
+        let (component_count, largest_component) = components(n, &edges);
+        let paths = path_metrics(&adjacency);
+
+        let mut ranked: Vec<NodeScore> = paths
+            .betweenness
+            .iter()
+            .enumerate()
+            .map(|(index, &value)| NodeScore {
+                index,
+                id: self.hyphae[index].id,
+                value,
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.value.total_cmp(&a.value).then(a.index.cmp(&b.index)));
+        let betweenness = BetweennessSummary {
+            mean: paths.betweenness.iter().sum::<f32>() / n.max(1) as f32,
+            max: ranked.first().map_or(0.0, |score| score.value),
+            top: ranked.into_iter().take(TOP_BETWEENNESS).collect(),
+        };
+
+        NetworkAnalysis {
+            frame_index: self.frame_index,
+            node_count: n,
+            edge_count: edges.len(),
+            parent_edges,
+            anastomosis_edges,
+            component_count,
+            largest_component,
+            loop_count: edges.len() + component_count - n,
+            mean_degree: if n > 0 {
+                2.0 * edges.len() as f32 / n as f32
+            } else {
+                0.0
+            },
+            max_degree,
+            degree_distribution,
+            mean_shortest_path: paths.mean_shortest_path,
+            global_efficiency: paths.global_efficiency,
+            betweenness,
+            total_length,
+            fault_tolerance: fault_tolerance(n, &edges, largest_component),
+            sampled_sources: paths.sampled_sources,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that path lengths, efficiency, betweenness and components match a hand-checked graph
+    #[test]
+    fn test_path_metrics() {
+        // Path 0-1-2 plus a triangle 3-4-5
+        let adjacency = vec![
+            vec![1],
+            vec![0, 2],
+            vec![1],
+            vec![4, 5],
+            vec![3, 5],
+            vec![3, 4],
+        ];
+        let paths = path_metrics(&adjacency);
+        // Connected ordered pairs: path has 4 at 1 hop and 2 at 2 hops, triangle has 6 at 1 hop
+        assert!((paths.mean_shortest_path - 14.0 / 12.0).abs() < 1e-6);
+        assert!((paths.global_efficiency - 11.0 / 30.0).abs() < 1e-6);
+        // Only the middle of the path lies between other nodes (1 pair, normalized by 5 * 4)
+        assert!((paths.betweenness[1] - 2.0 / 20.0).abs() < 1e-6);
+        assert_eq!(paths.betweenness[4], 0.0);
+        assert_eq!(paths.sampled_sources, None);
+
+        let edges = [(0, 1), (1, 2), (3, 4), (4, 5), (3, 5)];
+        assert_eq!(components(6, &edges), (2, 3));
+        let points = fault_tolerance(6, &edges, 3);
+        assert_eq!(points.len(), FAULT_REMOVAL_FRACTIONS.len());
+        assert!(points.iter().all(|p| p.largest_component_fraction <= 1.0));
+    }
+
+    /// Test that the network analysis counts match the simulation it was taken from
+    #[test]
+    fn test_analyze_network() {
+        let config = crate::config::SimulationConfig {
+            grid_size: 40,
+            ..Default::default()
+        };
+        let mut rng = create_rng(5);
+        let mut sim = Simulation::with_config(&mut rng, config);
+        for _ in 0..60 {
+            sim.step(&mut rng);
+        }
+
+        let analysis = sim.analyze_network();
+        assert_eq!(analysis.node_count, sim.hyphae.len());
+        assert_eq!(
+            analysis.degree_distribution.iter().sum::<usize>(),
+            analysis.node_count
+        );
+        assert_eq!(
+            analysis.edge_count,
+            analysis.parent_edges + analysis.anastomosis_edges
+        );
+        assert_eq!(
+            analysis.loop_count + analysis.node_count,
+            analysis.edge_count + analysis.component_count
+        );
+        assert!(analysis.largest_component <= analysis.node_count);
+    }
+}
@@ -1,24 +1,37 @@
 // API module for headless mode - HTTP endpoints to interact with the simulation
 
 use axum::{
-    extract::{Query, State},
-    http::StatusCode,
-    response::Json,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        DefaultBodyLimit, Path, Query, State,
+    },
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
     Router,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::error::RecvError;
 use tower_http::cors::CorsLayer;
 
-use crate::config::SimulationConfig;
-use crate::simulation::Simulation;
-use ::rand::rngs::StdRng;
-use ::rand::SeedableRng;
+use crate::analysis::NetworkAnalysis;
+use crate::config::{FieldEffect, SimulationConfig};
+use crate::graph_export::GraphFormat;
+use crate::grid::Grid2D;
+use crate::grid_export::GridLayer;
+use crate::profile::ProfileReport;
+use crate::render::RenderOptions;
+use crate::simulation::{create_rng, SimRng, Simulation};
+use crate::stream::{StateDelta, StreamChannel, StreamEvent, StreamHub, StreamRequest};
+use crate::timelapse::{Timelapse, TimelapseFrame};
+use crate::types::ZoneType;
 
 // Serializable versions of simulation data for API responses
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, Debug)]
 pub struct HyphaData {
+    pub id: u64, // Persistent across frames, unlike the position in the list
     pub x: f32,
     pub y: f32,
     pub prev_x: f32,
@@ -26,7 +39,7 @@ pub struct HyphaData {
     pub angle: f32,
     pub alive: bool,
     pub energy: f32,
-    pub parent: Option<usize>,
+    pub parent: Option<u64>, // Id of the hypha this one branched from
     pub age: f32,
     pub strength: f32,
     pub senescence_factor: f32,
@@ -46,8 +59,8 @@ pub struct SporeData {
 
 #[derive(Serialize, Clone)]
 pub struct ConnectionData {
-    pub hypha1: usize,
-    pub hypha2: usize,
+    pub hypha1: u64, // Hypha ids
+    pub hypha2: u64,
     pub strength: f32,
     pub signal: f32,
 }
@@ -80,7 +93,7 @@ pub struct WeatherData {
     pub growth_multiplier: f32,
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, Debug)]
 pub struct StatsData {
     pub hyphae_count: usize,
     pub spores_count: usize,
@@ -99,16 +112,16 @@ pub struct SimulationStateResponse {
     pub segments: Vec<SegmentData>,
     pub fruit_bodies: Vec<FruitBodyData>,
     pub nutrients: NutrientGridData,
-    pub nutrient_memory: Vec<Vec<f32>>,
-    pub obstacles: Vec<Vec<bool>>,
+    pub nutrient_memory: Grid2D<f32>,
+    pub obstacles: Grid2D<bool>,
     pub weather: WeatherData,
     pub stats: StatsData,
 }
 
 #[derive(Serialize, Clone)]
 pub struct NutrientGridData {
-    pub sugar: Vec<Vec<f32>>,
-    pub nitrogen: Vec<Vec<f32>>,
+    pub sugar: Grid2D<f32>,
+    pub nitrogen: Grid2D<f32>,
 }
 
 #[derive(Deserialize)]
@@ -116,34 +129,175 @@ pub struct StepQuery {
     pub steps: Option<usize>,
 }
 
+#[derive(Deserialize)]
+pub struct ConfigPatchQuery {
+    // Rebuild the simulation from the patched config (needed for grid size changes)
+    pub reinitialize: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct GraphQuery {
+    // Output format: graphml (default) or gexf
+    pub format: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct HistoryQuery {
+    // Output format: jsonl (default) or csv
+    pub format: Option<String>,
+    // Only samples taken after this frame
+    pub since: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct RenderQuery {
+    // Comma-separated layers to draw (default: what the window shows on startup)
+    pub layers: Option<String>,
+    // Output pixels per window pixel (default: 1)
+    pub scale: Option<f32>,
+}
+
+#[derive(Deserialize)]
+pub struct GridsQuery {
+    // Comma-separated grids to bundle (default: all)
+    pub names: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ConfigPatchResponse {
+    // Fields that took effect on the running simulation
+    pub applied: Vec<String>,
+    // Fields stored in the config that only take effect when the simulation is re-initialized
+    pub deferred: Vec<String>,
+    pub reinitialized: bool,
+    pub config: SimulationConfig,
+}
+
+// Environment manipulation requests (grid coordinates, radius in cells)
+#[derive(Deserialize)]
+pub struct SpawnHyphaRequest {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NutrientKind {
+    Sugar,
+    Nitrogen,
+}
+
+#[derive(Deserialize)]
+pub struct NutrientRequest {
+    pub x: usize,
+    pub y: usize,
+    pub nutrient: NutrientKind,
+    // Small patch (like the N/T keys) instead of a single cell (like mouse clicks)
+    #[serde(default = "default_true")]
+    pub patch: bool,
+}
+
+#[derive(Deserialize)]
+pub struct ObstacleRequest {
+    pub x: usize,
+    pub y: usize,
+    #[serde(default)]
+    pub radius: f32,
+    #[serde(default)]
+    pub remove: bool,
+}
+
+#[derive(Deserialize)]
+pub struct ZoneRequest {
+    pub x: usize,
+    pub y: usize,
+    pub radius: f32,
+    pub zone_type: ZoneType,
+    #[serde(default = "default_zone_intensity")]
+    pub intensity: f32,
+}
+
+#[derive(Deserialize)]
+pub struct RegionValueRequest {
+    pub x: usize,
+    pub y: usize,
+    #[serde(default)]
+    pub radius: f32,
+    pub value: f32,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_zone_intensity() -> f32 {
+    0.8
+}
+
+#[derive(Deserialize)]
+pub struct StreamQuery {
+    // Comma-separated channel names, e.g. `hyphae,spores`
+    pub channels: Option<String>,
+    pub interval_ms: Option<u64>,
+}
+
+// Messages sent to WebSocket clients
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamMessage<'a> {
+    // Full state, sent on connect and whenever the client has to resync
+    State(&'a SimulationStateResponse),
+    Delta(&'a StateDelta),
+    Error { message: String },
+}
+
+// Snapshots hold every grid, so allow uploads well beyond axum's 2 MB default
+const SNAPSHOT_BODY_LIMIT: usize = 512 * 1024 * 1024;
+
 // Shared state for the API server
 #[derive(Clone)]
 pub struct ApiState {
     pub simulation: Arc<Mutex<Simulation>>,
-    pub rng: Arc<Mutex<StdRng>>,
+    pub rng: Arc<Mutex<SimRng>>,
+    pub stream: Arc<StreamHub>,
 }
 
 impl ApiState {
-    pub fn new(sim: Simulation) -> Self {
-        let rng = StdRng::from_entropy();
+    /// Create the shared API state. `rng` should be the generator the simulation was
+    /// initialized with, so a seeded run stays reproducible while it is stepped.
+    pub fn new(sim: Simulation, rng: SimRng) -> Self {
         Self {
             simulation: Arc::new(Mutex::new(sim)),
             rng: Arc::new(Mutex::new(rng)),
+            stream: Arc::new(StreamHub::new()),
         }
     }
 }
 
-// Helper function to convert simulation state to API response
-fn simulation_to_response(sim: &Simulation) -> SimulationStateResponse {
+// Helper function to build the statistics response
+pub(crate) fn stats_to_response(sim: &Simulation) -> StatsData {
     let (hyphae_count, spores_count, connections_count, fruit_count, avg_energy, total_energy) =
         sim.stats();
+    StatsData {
+        hyphae_count,
+        spores_count,
+        connections_count,
+        fruit_count,
+        avg_energy,
+        total_energy,
+        frame_index: sim.state.frame_index,
+    }
+}
 
+// Helper function to convert simulation state to API response
+pub(crate) fn simulation_to_response(sim: &Simulation) -> SimulationStateResponse {
     SimulationStateResponse {
         hyphae: sim
             .state
             .hyphae
             .iter()
             .map(|h| HyphaData {
+                id: h.id,
                 x: h.x,
                 y: h.y,
                 prev_x: h.prev_x,
@@ -221,15 +375,7 @@ fn simulation_to_response(sim: &Simulation) -> SimulationStateResponse {
             temperature_celsius: sim.state.weather.temperature_celsius_approx(),
             growth_multiplier: sim.state.weather.growth_multiplier(),
         },
-        stats: StatsData {
-            hyphae_count,
-            spores_count,
-            connections_count,
-            fruit_count,
-            avg_energy,
-            total_energy,
-            frame_index: sim.state.frame_index,
-        },
+        stats: stats_to_response(sim),
     }
 }
 
@@ -250,17 +396,7 @@ async fn get_stats(State(api_state): State<ApiState>) -> Result<Json<StatsData>,
         .simulation
         .lock()
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    let (hyphae_count, spores_count, connections_count, fruit_count, avg_energy, total_energy) =
-        sim.stats();
-    Ok(Json(StatsData {
-        hyphae_count,
-        spores_count,
-        connections_count,
-        fruit_count,
-        avg_energy,
-        total_energy,
-        frame_index: sim.state.frame_index,
-    }))
+    Ok(Json(stats_to_response(&sim)))
 }
 
 // POST /step - Step the simulation forward
@@ -279,11 +415,51 @@ async fn step_simulation(
 
     let steps = params.steps.unwrap_or(1);
 
+    let mut frames = Vec::new();
     for _ in 0..steps {
         sim.step(&mut *rng);
+        api_state.stream.publish(&sim);
+        frames.extend(sim.timelapse_frame());
     }
+    let response = simulation_to_response(&sim);
+    let pending = take_timelapse(&mut sim, frames);
+    drop(rng);
+    drop(sim);
+    write_timelapse_frames(&api_state, pending);
 
-    Ok(Json(simulation_to_response(&sim)))
+    Ok(Json(response))
+}
+
+// Time-lapse frames rendered under the simulation lock, with the recording taken out of the
+// simulation so they can be encoded after the lock is released
+fn take_timelapse(
+    sim: &mut Simulation,
+    frames: Vec<TimelapseFrame>,
+) -> Option<(Timelapse, Vec<TimelapseFrame>)> {
+    if frames.is_empty() {
+        return None;
+    }
+    sim.stop_timelapse().map(|timelapse| (timelapse, frames))
+}
+
+// Encode frames from `take_timelapse` without holding the simulation lock (PNG and GIF
+// encoding would stall every other request), then hand the recording back unless another
+// one was started meanwhile
+fn write_timelapse_frames(api_state: &ApiState, pending: Option<(Timelapse, Vec<TimelapseFrame>)>) {
+    let Some((mut timelapse, frames)) = pending else {
+        return;
+    };
+    for frame in frames {
+        if let Err(e) = timelapse.write(frame) {
+            timelapse.report_stopped(&e);
+            return;
+        }
+    }
+    if let Ok(mut sim) = api_state.simulation.lock() {
+        if !sim.is_recording() {
+            sim.timelapse = Some(timelapse);
+        }
+    }
 }
 
 // POST /reset - Reset the simulation
@@ -300,6 +476,7 @@ async fn reset_simulation(
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     sim.reset(&mut *rng);
+    api_state.stream.resync(&sim);
 
     Ok(Json(simulation_to_response(&sim)))
 }
@@ -327,6 +504,581 @@ async fn get_config(
     Ok(Json(sim.config.clone()))
 }
 
+// PATCH /config - Change config fields on the running simulation
+async fn patch_config(
+    Query(params): Query<ConfigPatchQuery>,
+    State(api_state): State<ApiState>,
+    Json(patch): Json<serde_json::Value>,
+) -> Result<Json<ConfigPatchResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let error = |status: StatusCode, message: String, fields: Vec<String>| {
+        (
+            status,
+            Json(serde_json::json!({ "error": message, "fields": fields })),
+        )
+    };
+    let internal_error = || {
+        error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Simulation lock poisoned".to_string(),
+            vec![],
+        )
+    };
+
+    let mut sim = api_state.simulation.lock().map_err(|_| internal_error())?;
+    let (config, fields) = sim
+        .config
+        .with_patch(&patch)
+        .map_err(|e| error(StatusCode::BAD_REQUEST, e, vec![]))?;
+    if let Err(invalid) = config.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": "Invalid configuration",
+                "fields": invalid.issues.iter().map(|i| i.field.clone()).collect::<Vec<_>>(),
+                "issues": invalid.issues,
+            })),
+        ));
+    }
+
+    let reinitialize = params.reinitialize.unwrap_or(false);
+    let rebuild_fields: Vec<String> = fields
+        .iter()
+        .filter(|f| FieldEffect::of(f) == FieldEffect::Rebuild)
+        .cloned()
+        .collect();
+    if !rebuild_fields.is_empty() && !reinitialize {
+        return Err(error(
+            StatusCode::CONFLICT,
+            "These fields size the simulation grids; resend with ?reinitialize=true to rebuild the simulation".to_string(),
+            rebuild_fields,
+        ));
+    }
+
+    if reinitialize {
+        let mut config = config;
+        let mut rng = api_state.rng.lock().map_err(|_| internal_error())?;
+        // A new seed starts a fresh reproducible run; otherwise keep drawing from the current stream
+        if fields.iter().any(|f| f == "seed") {
+            *rng = create_rng(config.resolve_seed());
+        }
+        let new_sim = Simulation::try_with_config(&mut *rng, config)
+            .map_err(|e| error(StatusCode::BAD_REQUEST, e, vec![]))?;
+        // A running time-lapse carries on into the new simulation
+        let timelapse = sim.stop_timelapse();
+        *sim = new_sim;
+        sim.timelapse = timelapse;
+        api_state.stream.resync(&sim);
+        return Ok(Json(ConfigPatchResponse {
+            applied: fields,
+            deferred: Vec::new(),
+            reinitialized: true,
+            config: sim.config.clone(),
+        }));
+    }
+
+    let (applied, deferred): (Vec<String>, Vec<String>) = fields
+        .into_iter()
+        .partition(|f| FieldEffect::of(f) == FieldEffect::Live);
+    sim.update_config(config);
+
+    Ok(Json(ConfigPatchResponse {
+        applied,
+        deferred,
+        reinitialized: false,
+        config: sim.config.clone(),
+    }))
+}
+
+type EnvResult = Result<Json<serde_json::Value>, (StatusCode, String)>;
+
+fn lock_error<T>(_: T) -> (StatusCode, String) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Simulation lock poisoned".to_string(),
+    )
+}
+
+// Reject coordinates outside the grid and values outside 0.0-1.0
+fn check_cell(sim: &Simulation, x: f32, y: f32) -> Result<(), (StatusCode, String)> {
+    let size = sim.config.grid_size as f32;
+    if x >= 0.0 && y >= 0.0 && x < size && y < size {
+        Ok(())
+    } else {
+        Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Position ({}, {}) is outside the {}x{} grid",
+                x, y, size, size
+            ),
+        ))
+    }
+}
+
+fn check_unit(name: &str, value: f32) -> Result<(), (StatusCode, String)> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(())
+    } else {
+        Err((
+            StatusCode::BAD_REQUEST,
+            format!("{} must be between 0.0 and 1.0, got {}", name, value),
+        ))
+    }
+}
+
+// A disc wider than the grid covers nothing more, and huge radii would scan a huge square
+// while holding the simulation lock
+fn check_radius(sim: &Simulation, radius: f32) -> Result<(), (StatusCode, String)> {
+    let grid_size = sim.config.grid_size;
+    if radius.is_finite() && radius >= 0.0 && radius <= grid_size as f32 {
+        Ok(())
+    } else {
+        Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "radius must be a number from 0 to grid_size ({}), got {}",
+                grid_size, radius
+            ),
+        ))
+    }
+}
+
+// POST /env/hypha - Spawn a hypha (S key)
+async fn env_spawn_hypha(
+    State(api_state): State<ApiState>,
+    Json(req): Json<SpawnHyphaRequest>,
+) -> EnvResult {
+    let mut sim = api_state.simulation.lock().map_err(lock_error)?;
+    check_cell(&sim, req.x, req.y)?;
+    let mut rng = api_state.rng.lock().map_err(lock_error)?;
+    sim.spawn_hypha_at(&mut *rng, req.x, req.y);
+    let index = sim.state.hyphae.len() - 1;
+    Ok(Json(
+        serde_json::json!({ "index": index, "id": sim.state.hyphae[index].id }),
+    ))
+}
+
+// POST /env/nutrients - Add sugar or nitrogen (N/T keys, mouse clicks)
+async fn env_add_nutrients(
+    State(api_state): State<ApiState>,
+    Json(req): Json<NutrientRequest>,
+) -> EnvResult {
+    let mut sim = api_state.simulation.lock().map_err(lock_error)?;
+    check_cell(&sim, req.x as f32, req.y as f32)?;
+    match (req.nutrient, req.patch) {
+        (NutrientKind::Sugar, true) => sim.add_nutrient_patch(req.x, req.y),
+        (NutrientKind::Sugar, false) => sim.add_nutrient_cell(req.x, req.y),
+        (NutrientKind::Nitrogen, true) => sim.add_nitrogen_patch(req.x, req.y),
+        (NutrientKind::Nitrogen, false) => sim.add_nitrogen_cell(req.x, req.y),
+    }
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
+
+// POST /env/obstacles - Place or clear obstacles in a disc
+async fn env_set_obstacles(
+    State(api_state): State<ApiState>,
+    Json(req): Json<ObstacleRequest>,
+) -> EnvResult {
+    let mut sim = api_state.simulation.lock().map_err(lock_error)?;
+    check_cell(&sim, req.x as f32, req.y as f32)?;
+    check_radius(&sim, req.radius)?;
+    let cells = sim.set_obstacles(req.x, req.y, req.radius, !req.remove);
+    Ok(Json(serde_json::json!({ "cells": cells })))
+}
+
+// POST /env/zones - Paint a Toxic, Competitor or Deadwood zone (None erases)
+async fn env_paint_zone(
+    State(api_state): State<ApiState>,
+    Json(req): Json<ZoneRequest>,
+) -> EnvResult {
+    let mut sim = api_state.simulation.lock().map_err(lock_error)?;
+    check_cell(&sim, req.x as f32, req.y as f32)?;
+    check_radius(&sim, req.radius)?;
+    check_unit("intensity", req.intensity)?;
+    let cells = sim.paint_zone(req.x, req.y, req.radius, req.zone_type, req.intensity);
+    Ok(Json(serde_json::json!({ "cells": cells })))
+}
+
+// POST /env/moisture - Set soil moisture in a disc
+async fn env_set_moisture(
+    State(api_state): State<ApiState>,
+    Json(req): Json<RegionValueRequest>,
+) -> EnvResult {
+    let mut sim = api_state.simulation.lock().map_err(lock_error)?;
+    check_cell(&sim, req.x as f32, req.y as f32)?;
+    check_radius(&sim, req.radius)?;
+    check_unit("value", req.value)?;
+    let cells = sim.set_moisture_region(req.x, req.y, req.radius, req.value);
+    Ok(Json(serde_json::json!({ "cells": cells })))
+}
+
+// POST /env/light - Set light exposure in a disc
+async fn env_set_light(
+    State(api_state): State<ApiState>,
+    Json(req): Json<RegionValueRequest>,
+) -> EnvResult {
+    let mut sim = api_state.simulation.lock().map_err(lock_error)?;
+    check_cell(&sim, req.x as f32, req.y as f32)?;
+    check_radius(&sim, req.radius)?;
+    check_unit("value", req.value)?;
+    let cells = sim.set_light_region(req.x, req.y, req.radius, req.value);
+    Ok(Json(serde_json::json!({ "cells": cells })))
+}
+
+// GET /snapshot - Download a full simulation snapshot (JSON)
+async fn get_snapshot(State(api_state): State<ApiState>) -> Result<impl IntoResponse, StatusCode> {
+    let sim = api_state
+        .simulation
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let rng = api_state
+        .rng
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let mut body = Vec::new();
+    sim.write_snapshot(&rng, &mut body)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(([(header::CONTENT_TYPE, "application/json")], body))
+}
+
+impl RenderQuery {
+    fn options(&self) -> Result<RenderOptions, (StatusCode, String)> {
+        let scale = self.scale.unwrap_or(1.0);
+        match self.layers.as_deref() {
+            Some(layers) => RenderOptions::with_layers(layers, scale),
+            None => {
+                let options = RenderOptions {
+                    scale,
+                    ..RenderOptions::default()
+                };
+                options.validate().map(|_| options)
+            }
+        }
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))
+    }
+}
+
+// GET /render.png - Draw the colony offscreen and return it as a PNG
+async fn get_render(
+    Query(params): Query<RenderQuery>,
+    State(api_state): State<ApiState>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let options = params.options()?;
+    let internal_error = |message: String| (StatusCode::INTERNAL_SERVER_ERROR, message);
+    let sim = api_state
+        .simulation
+        .lock()
+        .map_err(|_| internal_error("Simulation lock poisoned".to_string()))?;
+    sim.check_render_size(&options)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let body = sim
+        .render_png(&options)
+        .map_err(|e| internal_error(e.to_string()))?;
+    Ok(([(header::CONTENT_TYPE, "image/png")], body))
+}
+
+// GET /render.svg - The colony as vector graphics (heatmaps embedded as raster layers)
+async fn get_render_svg(
+    Query(params): Query<RenderQuery>,
+    State(api_state): State<ApiState>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let options = params.options()?;
+    let internal_error = |message: String| (StatusCode::INTERNAL_SERVER_ERROR, message);
+    let sim = api_state
+        .simulation
+        .lock()
+        .map_err(|_| internal_error("Simulation lock poisoned".to_string()))?;
+    sim.check_render_size(&options)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let mut body = Vec::new();
+    sim.write_svg(&mut body, &options)
+        .map_err(|e| internal_error(e.to_string()))?;
+    Ok(([(header::CONTENT_TYPE, "image/svg+xml")], body))
+}
+
+// GET /grids/{name}.npy - One raster grid as a NumPy array
+async fn get_grid(
+    Path(file): Path<String>,
+    State(api_state): State<ApiState>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let grid = file
+        .strip_suffix(".npy")
+        .and_then(GridLayer::parse)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!(
+                    "Unknown grid file '{}' (expected <name>.npy with name one of: {})",
+                    file,
+                    GridLayer::ALL.map(|grid| grid.name()).join(", ")
+                ),
+            )
+        })?;
+    let internal_error = |message: String| (StatusCode::INTERNAL_SERVER_ERROR, message);
+    let sim = api_state
+        .simulation
+        .lock()
+        .map_err(|_| internal_error("Simulation lock poisoned".to_string()))?;
+    let mut body = Vec::new();
+    sim.write_npy(&mut body, grid)
+        .map_err(|e| internal_error(e.to_string()))?;
+    Ok(([(header::CONTENT_TYPE, "application/octet-stream")], body))
+}
+
+// GET /grids.npz - Several raster grids in one NumPy archive
+async fn get_grids_npz(
+    Query(params): Query<GridsQuery>,
+    State(api_state): State<ApiState>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let grids = GridLayer::parse_list(params.names.as_deref().unwrap_or(""))
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let internal_error = |message: String| (StatusCode::INTERNAL_SERVER_ERROR, message);
+    let sim = api_state
+        .simulation
+        .lock()
+        .map_err(|_| internal_error("Simulation lock poisoned".to_string()))?;
+    let mut body = Vec::new();
+    sim.write_npz(&mut body, &grids)
+        .map_err(|e| internal_error(e.to_string()))?;
+    Ok(([(header::CONTENT_TYPE, "application/zip")], body))
+}
+
+// GET /history - Recorded metric samples as JSON Lines or CSV
+async fn get_history(
+    Query(params): Query<HistoryQuery>,
+    State(api_state): State<ApiState>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let csv = match params.format.as_deref() {
+        None | Some("jsonl") => false,
+        Some("csv") => true,
+        Some(name) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Unknown history format '{}' (use jsonl or csv)", name),
+            ))
+        }
+    };
+    let internal_error = |message: String| (StatusCode::INTERNAL_SERVER_ERROR, message);
+    let sim = api_state
+        .simulation
+        .lock()
+        .map_err(|_| internal_error("Simulation lock poisoned".to_string()))?;
+    let mut body = Vec::new();
+    let (result, content_type) = if csv {
+        (sim.history.write_csv(&mut body, params.since), "text/csv")
+    } else {
+        (
+            sim.history.write_jsonl(&mut body, params.since),
+            "application/x-ndjson",
+        )
+    };
+    result.map_err(|e| internal_error(e.to_string()))?;
+    Ok(([(header::CONTENT_TYPE, content_type)], body))
+}
+
+// GET /analysis - Network topology metrics
+async fn get_analysis(
+    State(api_state): State<ApiState>,
+) -> Result<Json<NetworkAnalysis>, StatusCode> {
+    let sim = api_state
+        .simulation
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(sim.analyze_network()))
+}
+
+// GET /profile - Per-phase step timings and work counters
+async fn get_profile(State(api_state): State<ApiState>) -> Result<Json<ProfileReport>, StatusCode> {
+    let sim = api_state
+        .simulation
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(sim.profile_report()))
+}
+
+// GET /graph - Download the hyphal network as GraphML or GEXF
+async fn get_graph(
+    Query(params): Query<GraphQuery>,
+    State(api_state): State<ApiState>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let format = match params.format.as_deref() {
+        None => GraphFormat::GraphMl,
+        Some(name) => GraphFormat::parse(name).ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("Unknown graph format '{}' (use graphml or gexf)", name),
+            )
+        })?,
+    };
+    let internal_error = |message: String| (StatusCode::INTERNAL_SERVER_ERROR, message);
+    let sim = api_state
+        .simulation
+        .lock()
+        .map_err(|_| internal_error("Simulation lock poisoned".to_string()))?;
+    let mut body = Vec::new();
+    sim.write_graph(&mut body, format)
+        .map_err(|e| internal_error(e.to_string()))?;
+    Ok(([(header::CONTENT_TYPE, format.content_type())], body))
+}
+
+// POST /snapshot - Replace the running simulation with an uploaded snapshot
+async fn post_snapshot(
+    State(api_state): State<ApiState>,
+    body: String,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let (restored, restored_rng) = Simulation::from_snapshot_json(&body)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid snapshot: {}", e)))?;
+    let internal_error = || {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Simulation lock poisoned".to_string(),
+        )
+    };
+    let mut sim = api_state.simulation.lock().map_err(|_| internal_error())?;
+    let mut rng = api_state.rng.lock().map_err(|_| internal_error())?;
+
+    *rng = restored_rng;
+    let timelapse = sim.stop_timelapse();
+    *sim = restored;
+    sim.timelapse = timelapse;
+    api_state.stream.resync(&sim);
+
+    Ok(Json(serde_json::json!({
+        "loaded": true,
+        "frame_index": sim.state.frame_index,
+    })))
+}
+
+// GET /ws - Stream per-tick state deltas over a WebSocket
+async fn stream_ws(
+    ws: WebSocketUpgrade,
+    Query(params): Query<StreamQuery>,
+    State(api_state): State<ApiState>,
+) -> Result<Response, (StatusCode, String)> {
+    let mut request = StreamRequest {
+        channels: None,
+        interval_ms: params.interval_ms,
+    };
+    if let Some(names) = params.channels {
+        let mut channels = Vec::new();
+        for name in names.split(',').filter(|n| !n.trim().is_empty()) {
+            let channel = StreamChannel::parse(name).ok_or_else(|| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    format!("Unknown stream channel: {}", name),
+                )
+            })?;
+            channels.push(channel);
+        }
+        request.channels = Some(channels);
+    }
+    Ok(ws.on_upgrade(move |socket| stream_client(socket, api_state, request)))
+}
+
+// Full simulation state message, the baseline for the deltas that follow
+fn full_state_message(sim: &Simulation) -> Option<(String, u64)> {
+    let response = simulation_to_response(sim);
+    let text = serde_json::to_string(&StreamMessage::State(&response)).ok()?;
+    Some((text, sim.state.frame_index))
+}
+
+// Send the full simulation state as a new baseline
+async fn send_full_state(socket: &mut WebSocket, api_state: &ApiState) -> Option<u64> {
+    let (text, frame_index) = {
+        let sim = api_state.simulation.lock().ok()?;
+        full_state_message(&sim)?
+    };
+    socket.send(Message::Text(text)).await.ok()?;
+    Some(frame_index)
+}
+
+// Per-client loop: forwards deltas for subscribed channels, merging them while throttled
+async fn stream_client(mut socket: WebSocket, api_state: ApiState, initial: StreamRequest) {
+    // Subscribe and take the baseline under the same lock, so the first delta starts right after it
+    let (mut events, baseline) = {
+        let Ok(sim) = api_state.simulation.lock() else {
+            return;
+        };
+        (api_state.stream.subscribe(&sim), full_state_message(&sim))
+    };
+    let Some((text, mut baseline_frame)) = baseline else {
+        return;
+    };
+    if socket.send(Message::Text(text)).await.is_err() {
+        return;
+    }
+
+    let mut channels: HashSet<StreamChannel> = StreamChannel::ALL.into_iter().collect();
+    let mut interval_ms = 0u64;
+    initial.apply(&mut channels, &mut interval_ms);
+
+    let mut pending: Option<StateDelta> = None;
+    let mut last_sent = tokio::time::Instant::now();
+
+    loop {
+        let flush_at = last_sent + std::time::Duration::from_millis(interval_ms);
+        tokio::select! {
+            incoming = socket.recv() => {
+                match incoming {
+                    Some(Ok(Message::Text(text))) => {
+                        match serde_json::from_str::<StreamRequest>(&text) {
+                            Ok(request) => request.apply(&mut channels, &mut interval_ms),
+                            Err(e) => {
+                                let message = StreamMessage::Error {
+                                    message: format!("Invalid stream request: {}", e),
+                                };
+                                if let Ok(text) = serde_json::to_string(&message) {
+                                    if socket.send(Message::Text(text)).await.is_err() {
+                                        break;
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                    Some(Ok(_)) => {}
+                }
+            }
+            event = events.recv() => {
+                match event {
+                    Ok(StreamEvent::Delta(delta)) => {
+                        // Deltas already covered by the full state baseline
+                        if delta.frame_index <= baseline_frame {
+                            continue;
+                        }
+                        let delta = delta.filtered(&channels);
+                        match pending.as_mut() {
+                            Some(batch) => batch.merge(delta),
+                            None => pending = Some(delta),
+                        }
+                    }
+                    // Simulation replaced, or this client fell too far behind
+                    Ok(StreamEvent::Resync) | Err(RecvError::Lagged(_)) => {
+                        pending = None;
+                        match send_full_state(&mut socket, &api_state).await {
+                            Some(frame) => baseline_frame = frame,
+                            None => break,
+                        }
+                        last_sent = tokio::time::Instant::now();
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+            _ = tokio::time::sleep_until(flush_at), if pending.is_some() => {}
+        }
+
+        if pending.is_some() && tokio::time::Instant::now() >= flush_at {
+            let delta = pending.take().unwrap();
+            let Ok(text) = serde_json::to_string(&StreamMessage::Delta(&delta)) else {
+                continue;
+            };
+            if socket.send(Message::Text(text)).await.is_err() {
+                break;
+            }
+            last_sent = tokio::time::Instant::now();
+        }
+    }
+}
+
 // Create the API router
 pub fn create_router(api_state: ApiState) -> Router {
     Router::new()
@@ -335,7 +1087,28 @@ pub fn create_router(api_state: ApiState) -> Router {
         .route("/step", post(step_simulation))
         .route("/reset", post(reset_simulation))
         .route("/pause", post(pause_simulation))
-        .route("/config", get(get_config))
+        .route("/config", get(get_config).patch(patch_config))
+        .route("/ws", get(stream_ws))
+        .route("/env/hypha", post(env_spawn_hypha))
+        .route("/env/nutrients", post(env_add_nutrients))
+        .route("/env/obstacles", post(env_set_obstacles))
+        .route("/env/zones", post(env_paint_zone))
+        .route("/env/moisture", post(env_set_moisture))
+        .route("/env/light", post(env_set_light))
+        .route("/graph", get(get_graph))
+        .route("/analysis", get(get_analysis))
+        .route("/profile", get(get_profile))
+        .route("/history", get(get_history))
+        .route("/render.png", get(get_render))
+        .route("/render.svg", get(get_render_svg))
+        .route("/grids/:file", get(get_grid))
+        .route("/grids.npz", get(get_grids_npz))
+        .route(
+            "/snapshot",
+            get(get_snapshot)
+                .post(post_snapshot)
+                .layer(DefaultBodyLimit::max(SNAPSHOT_BODY_LIMIT)),
+        )
         .layer(CorsLayer::permissive())
         .with_state(api_state)
 }
@@ -356,6 +1129,19 @@ pub async fn run_server(api_state: ApiState, port: u16) -> Result<(), Box<dyn st
     println!("  POST /reset - Reset simulation");
     println!("  POST /pause - Toggle pause");
     println!("  GET  /config - Get simulation configuration");
+    println!("  PATCH /config - Change config fields live (partial JSON)");
+    println!("  GET  /snapshot - Download a full simulation snapshot");
+    println!("  POST /snapshot - Replace the simulation with an uploaded snapshot");
+    println!("  GET  /graph?format=graphml|gexf - Download the hyphal network as a graph file");
+    println!("  GET  /analysis - Network topology metrics (components, loops, paths, centrality)");
+    println!("  GET  /history?format=jsonl|csv&since=FRAME - Recorded metric time series");
+    println!("  GET  /profile - Step time and work counters per phase (last step, recent window, run mean)");
+    println!("  GET  /render.png?layers=...&scale=S - Render the colony as a PNG image");
+    println!("  GET  /render.svg?layers=...&scale=S - Export the colony as SVG vector graphics");
+    println!("  GET  /grids/{{name}}.npy - Download one raster grid (sugar, nitrogen, ...) as a NumPy array");
+    println!("  GET  /grids.npz?names=a,b - Download several raster grids as a NumPy .npz archive");
+    println!("  GET  /ws - WebSocket stream of per-tick state deltas");
+    println!("  POST /env/hypha, /env/nutrients, /env/obstacles, /env/zones, /env/moisture, /env/light - Edit the environment");
     println!();
     println!("Simulation is running automatically at ~60 FPS (respects pause state)");
 
@@ -388,12 +1174,13 @@ async fn simulation_loop(api_state: ApiState) {
         let start = std::time::Instant::now();
 
         // Step simulation if not paused
-        {
+        let pending = {
             let mut sim = match api_state.simulation.lock() {
                 Ok(sim) => sim,
                 Err(_) => break,
             };
 
+            let mut frames = Vec::new();
             if !sim.paused {
                 let mut rng = match api_state.rng.lock() {
                     Ok(rng) => rng,
@@ -407,9 +1194,13 @@ async fn simulation_loop(api_state: ApiState) {
 
                 for _ in 0..steps {
                     sim.step(&mut *rng);
+                    api_state.stream.publish(&sim);
+                    frames.extend(sim.timelapse_frame());
                 }
             }
-        }
+            take_timelapse(&mut sim, frames)
+        };
+        write_timelapse_frames(&api_state, pending);
 
         // Sleep to maintain target FPS
         let elapsed = start.elapsed();
@@ -0,0 +1,202 @@
+// Batch runs - step a simulation as fast as possible (no HTTP server, no frame pacing)
+// and write the final snapshot, metrics and exports into an output directory
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::analysis::NetworkAnalysis;
+use crate::api::{stats_to_response, StatsData};
+use crate::grid_export::GridLayer;
+use crate::render::RenderOptions;
+use crate::simulation::{SimRng, Simulation};
+
+// Files written into the output directory
+pub const CONFIG_FILE: &str = "config.yaml";
+pub const SNAPSHOT_FILE: &str = "snapshot.json";
+pub const HISTORY_FILE: &str = "history.csv";
+pub const NETWORK_FILE: &str = "network.graphml";
+pub const SUMMARY_FILE: &str = "summary.json";
+pub const RENDER_FILE: &str = "final.png";
+pub const SVG_FILE: &str = "final.svg";
+pub const GRIDS_FILE: &str = "grids.npz";
+
+/// What to run and where to put the results
+#[derive(Clone, Debug)]
+pub struct BatchOptions {
+    pub steps: u64,
+    pub out_dir: PathBuf,
+    // Print progress to stderr every 10% of the run
+    pub progress: bool,
+}
+
+/// Final numbers of a batch run (also written as `summary.json`)
+#[derive(Clone, Debug, Serialize)]
+pub struct BatchSummary {
+    pub seed: Option<u64>,
+    pub steps: u64,
+    pub frame_index: u64,
+    pub elapsed_secs: f64,
+    pub steps_per_sec: f64,
+    pub stats: StatsData,
+    pub analysis: NetworkAnalysis,
+}
+
+/// Step the simulation `options.steps` times, then write into `options.out_dir`:
+/// the effective config, a final snapshot, the metric history (CSV), the network (GraphML),
+/// images of the final state (PNG and SVG) and a summary with final stats and network analysis.
+pub fn run_batch(
+    sim: &mut Simulation,
+    rng: &mut SimRng,
+    options: &BatchOptions,
+) -> Result<BatchSummary, Box<dyn std::error::Error>> {
+    std::fs::create_dir_all(&options.out_dir).map_err(|e| {
+        format!(
+            "Failed to create output directory {}: {}",
+            options.out_dir.display(),
+            e
+        )
+    })?;
+    let summary = step_and_summarize(sim, rng, options.steps, options.progress);
+    write_batch_outputs(sim, rng, &summary, &options.out_dir)?;
+    Ok(summary)
+}
+
+/// Step the simulation `steps` times as fast as possible and summarize the final state.
+pub fn step_and_summarize(
+    sim: &mut Simulation,
+    rng: &mut SimRng,
+    steps: u64,
+    progress: bool,
+) -> BatchSummary {
+    let report_every = (steps / 10).max(1);
+    let started = Instant::now();
+    for step in 1..=steps {
+        sim.step(rng);
+        sim.record_timelapse();
+        if progress && step % report_every == 0 {
+            eprintln!(
+                "  step {}/{} ({:.0}%), {:.1}s elapsed",
+                step,
+                steps,
+                step as f64 * 100.0 / steps as f64,
+                started.elapsed().as_secs_f64()
+            );
+        }
+    }
+    let elapsed_secs = started.elapsed().as_secs_f64();
+
+    BatchSummary {
+        seed: sim.config.seed,
+        steps,
+        frame_index: sim.frame_index,
+        elapsed_secs,
+        steps_per_sec: if elapsed_secs > 0.0 {
+            steps as f64 / elapsed_secs
+        } else {
+            0.0
+        },
+        stats: stats_to_response(sim),
+        analysis: sim.analyze_network(),
+    }
+}
+
+/// Write the config, snapshot, history, network, image, grid and summary files into an existing directory.
+/// `rng` is the generator that stepped the run, saved with the snapshot.
+pub fn write_batch_outputs(
+    sim: &Simulation,
+    rng: &SimRng,
+    summary: &BatchSummary,
+    out_dir: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let with_path = |path: &Path, e: Box<dyn std::error::Error>| {
+        format!("Failed to write {}: {}", path.display(), e)
+    };
+    let path = out_dir.join(CONFIG_FILE);
+    sim.config
+        .save_to_file(&path)
+        .map_err(|e| with_path(&path, e))?;
+    let path = out_dir.join(SNAPSHOT_FILE);
+    sim.save_snapshot(rng, &path)
+        .map_err(|e| with_path(&path, e))?;
+    let path = out_dir.join(HISTORY_FILE);
+    sim.history.save(&path).map_err(|e| with_path(&path, e))?;
+    let path = out_dir.join(NETWORK_FILE);
+    sim.save_graph(&path).map_err(|e| with_path(&path, e))?;
+    let path = out_dir.join(RENDER_FILE);
+    let options = RenderOptions {
+        scale: sim.fit_render_scale(1.0),
+        ..RenderOptions::default()
+    };
+    sim.save_png(&path, &options)
+        .map_err(|e| with_path(&path, e))?;
+    let path = out_dir.join(SVG_FILE);
+    sim.save_svg(&path, &RenderOptions::default())
+        .map_err(|e| with_path(&path, e))?;
+    let path = out_dir.join(GRIDS_FILE);
+    sim.save_grids(&path, &GridLayer::ALL)
+        .map_err(|e| with_path(&path, e))?;
+    let path = out_dir.join(SUMMARY_FILE);
+    std::fs::write(&path, serde_json::to_string_pretty(summary)?)
+        .map_err(|e| with_path(&path, e.into()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimulationConfig;
+    use crate::simulation::create_rng;
+
+    // A 50-step seeded batch run into its own temporary directory
+    fn batch_run(name: &str) -> (BatchSummary, SimRng, PathBuf) {
+        let config = SimulationConfig {
+            grid_size: 40,
+            seed: Some(11),
+            ..SimulationConfig::default()
+        };
+        let mut rng = create_rng(11);
+        let mut sim = Simulation::with_config(&mut rng, config);
+        let out_dir =
+            std::env::temp_dir().join(format!("mycorust_batch_{}_{}", name, std::process::id()));
+        let options = BatchOptions {
+            steps: 50,
+            out_dir: out_dir.clone(),
+            progress: false,
+        };
+        let summary = run_batch(&mut sim, &mut rng, &options).unwrap();
+        (summary, rng, out_dir)
+    }
+
+    /// Test that a batch run steps the requested number of times and writes every output file
+    #[test]
+    fn test_run_batch() {
+        let (summary, _, out_dir) = batch_run("outputs");
+        assert_eq!(summary.frame_index, 50);
+        assert_eq!(summary.seed, Some(11));
+        for name in [
+            CONFIG_FILE,
+            SNAPSHOT_FILE,
+            HISTORY_FILE,
+            NETWORK_FILE,
+            RENDER_FILE,
+            SVG_FILE,
+            GRIDS_FILE,
+            SUMMARY_FILE,
+        ] {
+            assert!(out_dir.join(name).exists(), "missing {}", name);
+        }
+        std::fs::remove_dir_all(&out_dir).unwrap();
+    }
+
+    /// Test that the batch snapshot resumes exactly where the run stopped
+    #[test]
+    fn test_batch_snapshot_resumes() {
+        let (_, rng, out_dir) = batch_run("snapshot");
+        let (resumed, resumed_rng) =
+            Simulation::load_snapshot(out_dir.join(SNAPSHOT_FILE)).unwrap();
+        assert_eq!(resumed.frame_index, 50);
+        assert!(resumed_rng == rng);
+        std::fs::remove_dir_all(&out_dir).unwrap();
+    }
+}
@@ -139,8 +139,8 @@ impl Camera {
         }
     }
 
-    /// Convert world coordinates to screen coordinates
-    /// Uses macroquad's camera system to get the screen position
+    // Convert world coordinates to screen coordinates
+    // Uses macroquad's camera system to get the screen position
     // pub fn world_to_screen(&self, world_pos: Vec2) -> Vec2 {
     //     // Use macroquad's camera to convert world to screen coordinates
     //     let camera = self.get_camera();
@@ -1,8 +1,13 @@
 // Global configuration and constants
 use serde::{Deserialize, Serialize};
 
+use crate::history::HistoryMetric;
+use crate::render::MAX_RENDER_SCALE;
+
 // Configuration struct for simulation parameters
+// Fields missing from a config file are filled from `Default`, so configs can be sparse
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SimulationConfig {
     // Grid/display
     pub grid_size: usize,
@@ -100,6 +105,13 @@ pub struct SimulationConfig {
 
     // Initialization
     pub initial_hyphae_count: usize,
+    // Environment maps: image files resampled to grid_size that replace the generated grids
+    pub sugar_map: Option<String>,    // Brightness = sugar (0.0-1.0)
+    pub nitrogen_map: Option<String>, // Brightness = nitrogen (0.0-1.0)
+    pub obstacle_map: Option<String>, // Dark pixels = obstacles (replaces obstacle_count)
+    pub moisture_map: Option<String>, // Brightness = soil moisture (0.0-1.0)
+    pub light_map: Option<String>,    // Brightness = light exposure (0.0-1.0)
+    pub zone_map: Option<String>, // Zone colors: red toxic, yellow competitor, brown deadwood (replaces generated zones)
 
     // Network Intelligence: Signal Propagation
     pub signal_propagation_enabled: bool,
@@ -152,6 +164,24 @@ pub struct SimulationConfig {
 
     // Camera
     pub camera_enabled: bool, // Enable camera pan/zoom functionality
+
+    // Time stepping (independent of render FPS)
+    pub time_step: f32, // Fixed model time advanced by each simulation step
+    pub lod_skip_diffusion: bool, // Quality setting: diffuse nutrients only every other step (opt-in)
+
+    // Reproducibility
+    pub seed: Option<u64>, // RNG seed for the whole run (None = pick a random seed at startup)
+
+    // Time-series history recorder
+    pub history_interval: u32, // Sample metrics every N frames (0 = disabled)
+    pub history_capacity: usize, // Samples kept in the ring buffer (oldest dropped first)
+    pub history_metrics: Vec<String>, // Metrics to record (see `history::HistoryMetric`)
+
+    // Time-lapse recording (started with --timelapse or the O key)
+    pub timelapse_interval: u32,     // Render a frame every N steps
+    pub timelapse_scale: f32, // Frame size relative to the window (see `render::RenderOptions`)
+    pub timelapse_gif: bool,  // Also assemble the frames into an animated GIF
+    pub timelapse_gif_delay_ms: u32, // Display time of each GIF frame
 }
 
 impl Default for SimulationConfig {
@@ -233,6 +263,13 @@ impl Default for SimulationConfig {
             nutrient_regen_floor: 0.12,
             nutrient_regen_samples: 120,
             initial_hyphae_count: 5,
+            // Environment maps (None = generated)
+            sugar_map: None,
+            nitrogen_map: None,
+            obstacle_map: None,
+            moisture_map: None,
+            light_map: None,
+            zone_map: None,
 
             // Network Intelligence: Signal Propagation
             signal_propagation_enabled: true,
@@ -286,6 +323,27 @@ impl Default for SimulationConfig {
             // Camera
             // Disabled by default for now until we have a proper camera system
             camera_enabled: false,
+
+            // Time stepping
+            time_step: 1.0 / 60.0, // One step = 1/60 time unit (matches the original 60 FPS pacing)
+            lod_skip_diffusion: false, // Full-quality diffusion every step
+
+            // Reproducibility
+            seed: None, // Random seed chosen at startup (printed so the run can be reproduced)
+
+            // History recorder
+            history_interval: 10, // Six samples per model time unit at the default time step
+            history_capacity: 10000, // Enough for 100k frames at the default interval
+            history_metrics: HistoryMetric::ALL
+                .iter()
+                .map(|metric| metric.name().to_string())
+                .collect(),
+
+            // Time-lapse
+            timelapse_interval: 10,
+            timelapse_scale: 1.0,
+            timelapse_gif: false,
+            timelapse_gif_delay_ms: 100, // 10 frames per second
         }
     }
 }
@@ -299,12 +357,26 @@ impl SimulationConfig {
         self.hyphae_avoidance_distance * self.hyphae_avoidance_distance
     }
 
+    /// Return the seed for this run, picking a random one if none was configured.
+    /// The chosen seed is stored back into the config so it shows up in `GET /config`
+    /// and can be reused to reproduce the run.
+    pub fn resolve_seed(&mut self) -> u64 {
+        *self.seed.get_or_insert_with(rand::random)
+    }
+
     /// Load configuration from a file (YAML or JSON).
     /// If the file doesn't exist, returns the default configuration.
     /// If the file exists but parsing fails, returns an error.
     pub fn from_file<P: AsRef<std::path::Path>>(
         path: P,
     ) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_value(Self::read_value(path)?)
+    }
+
+    /// Read a config file into a generic value without filling in defaults
+    fn read_value<P: AsRef<std::path::Path>>(
+        path: P,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
         let path = path.as_ref();
 
         if !path.exists() {
@@ -319,19 +391,55 @@ impl SimulationConfig {
             .and_then(|ext| ext.to_str())
             .map(|ext| ext.to_lowercase());
 
-        let config = match ext.as_deref() {
+        // Parse into a generic value first so unknown keys can be reported
+        let value: serde_json::Value = match ext.as_deref() {
             Some("yaml") | Some("yml") => serde_yaml::from_str(&contents)?,
             Some("json") => serde_json::from_str(&contents)?,
             _ => {
                 // Try YAML first, then JSON
                 match serde_yaml::from_str(&contents) {
-                    Ok(config) => config,
+                    Ok(value) => value,
                     Err(_) => serde_json::from_str(&contents)?,
                 }
             }
         };
 
-        Ok(config)
+        Ok(match value {
+            // An empty file means "all defaults"
+            serde_json::Value::Null => serde_json::Value::Object(Default::default()),
+            value => value,
+        })
+    }
+
+    /// Build a config from a (possibly partial) JSON/YAML value.
+    /// Missing fields take their default; unknown fields are an error so typos don't go unnoticed.
+    pub fn from_value(value: serde_json::Value) -> Result<Self, Box<dyn std::error::Error>> {
+        let value = match value {
+            // An empty file means "all defaults"
+            serde_json::Value::Null => serde_json::Value::Object(Default::default()),
+            value => value,
+        };
+        let fields = value
+            .as_object()
+            .ok_or("Config must be a mapping of field names to values")?;
+        let unknown = Self::unknown_fields(fields.keys());
+        if !unknown.is_empty() {
+            return Err(format!("Unknown config fields: {}", unknown.join(", ")).into());
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Names of every config field
+    pub fn field_names() -> Vec<String> {
+        match serde_json::to_value(Self::default()) {
+            Ok(serde_json::Value::Object(fields)) => fields.keys().cloned().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn unknown_fields<'a>(keys: impl Iterator<Item = &'a String>) -> Vec<String> {
+        let known = Self::field_names();
+        keys.filter(|key| !known.contains(key)).cloned().collect()
     }
 
     /// Save configuration to a file (YAML format).
@@ -358,27 +466,705 @@ impl SimulationConfig {
         }
     }
 
-    /// Try to load config from common default locations, or return default.
-    pub fn from_default_paths() -> Self {
-        let default_paths = vec!["config.yaml", "config.yml", "config.json"];
-
-        for path in &default_paths {
-            if std::path::Path::new(path).exists() {
-                match Self::from_file(path) {
-                    Ok(config) => {
-                        println!("Loaded configuration from: {}", path);
-                        return config;
-                    }
-                    Err(e) => {
-                        eprintln!("Warning: Could not parse config file {}: {}", path, e);
-                        eprintln!("Using default configuration.");
-                        return Self::default();
-                    }
-                }
+    /// Load the config from the first of `config.yaml`, `config.yml` and `config.json` in the
+    /// current directory. Defaults when none exists; a file that fails to load is an error.
+    pub fn from_default_paths() -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_first_existing(&["config.yaml", "config.yml", "config.json"])
+    }
+
+    fn from_first_existing(paths: &[&str]) -> Result<Self, Box<dyn std::error::Error>> {
+        let Some(path) = paths
+            .iter()
+            .find(|path| std::path::Path::new(path).exists())
+        else {
+            // No config file found, use default
+            return Ok(Self::default());
+        };
+        let config = Self::from_file(path)
+            .map_err(|e| format!("Failed to load config from {}: {}", path, e))?;
+        eprintln!("Loaded configuration from: {}", path);
+        Ok(config)
+    }
+}
+
+/// A single problem found by `SimulationConfig::validate`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ConfigIssue {
+    pub field: String,
+    pub reason: String,
+}
+
+/// Every problem found in a config, reported together
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ConfigValidationError {
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl std::fmt::Display for ConfigValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid configuration ({} problem", self.issues.len())?;
+        if self.issues.len() != 1 {
+            write!(f, "s")?;
+        }
+        write!(f, "):")?;
+        for issue in &self.issues {
+            write!(f, "\n  - {}: {}", issue.field, issue.reason)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigValidationError {}
+
+// Collects issues while checking ranges and relationships between fields
+#[derive(Default)]
+struct Validator {
+    issues: Vec<ConfigIssue>,
+}
+
+impl Validator {
+    fn fail(&mut self, field: &str, reason: String) {
+        self.issues.push(ConfigIssue {
+            field: field.to_string(),
+            reason,
+        });
+    }
+
+    fn range(&mut self, field: &str, value: f32, min: f32, max: f32) {
+        if !value.is_finite() || value < min || value > max {
+            self.fail(
+                field,
+                format!("must be between {} and {}, got {}", min, max, value),
+            );
+        }
+    }
+
+    // Rates and probabilities in 0.0-1.0
+    fn unit(&mut self, field: &str, value: f32) {
+        self.range(field, value, 0.0, 1.0);
+    }
+
+    fn positive(&mut self, field: &str, value: f32) {
+        if !value.is_finite() || value <= 0.0 {
+            self.fail(field, format!("must be greater than 0, got {}", value));
+        }
+    }
+
+    fn non_negative(&mut self, field: &str, value: f32) {
+        if !value.is_finite() || value < 0.0 {
+            self.fail(field, format!("must not be negative, got {}", value));
+        }
+    }
+
+    fn finite(&mut self, field: &str, value: f32) {
+        if !value.is_finite() {
+            self.fail(field, format!("must be a finite number, got {}", value));
+        }
+    }
+}
+
+impl SimulationConfig {
+    /// Check value ranges and the relationships between fields.
+    /// Every problem is reported with its field name and the reason.
+    pub fn validate(&self) -> Result<(), ConfigValidationError> {
+        let mut v = Validator::default();
+
+        // Grid/display
+        if self.grid_size < 10 {
+            v.fail(
+                "grid_size",
+                format!("must be at least 10, got {}", self.grid_size),
+            );
+        }
+        v.positive("cell_size", self.cell_size);
+        if self.density_map_resolution == 0 {
+            v.fail("density_map_resolution", "must be at least 1".to_string());
+        }
+
+        // Growth & branching
+        v.unit("branch_prob", self.branch_prob);
+        v.positive("step_size", self.step_size);
+        v.unit(
+            "gradient_steering_strength",
+            self.gradient_steering_strength,
+        );
+        v.non_negative("angle_wander_range", self.angle_wander_range);
+
+        // Nutrients
+        v.unit("nutrient_decay", self.nutrient_decay);
+        v.unit("diffusion_rate", self.diffusion_rate);
+        v.non_negative(
+            "spore_germination_threshold",
+            self.spore_germination_threshold,
+        );
+        v.positive("spore_max_age", self.spore_max_age);
+        v.positive("cn_ratio_required", self.cn_ratio_required);
+        v.non_negative("cn_ratio_tolerance", self.cn_ratio_tolerance);
+        v.unit("nutrient_flow_rate", self.nutrient_flow_rate);
+        v.unit("flow_strength", self.flow_strength);
+        v.finite("flow_direction", self.flow_direction);
+        v.non_negative("flow_variation", self.flow_variation);
+        v.finite("tropism_angle", self.tropism_angle);
+        v.non_negative("tropism_strength", self.tropism_strength);
+
+        // Zones
+        v.non_negative("toxic_zone_radius", self.toxic_zone_radius);
+        v.non_negative("competitor_zone_radius", self.competitor_zone_radius);
+        v.unit("toxic_zone_damage_rate", self.toxic_zone_damage_rate);
+        v.unit(
+            "competitor_nutrient_consumption",
+            self.competitor_nutrient_consumption,
+        );
+        v.unit("zone_growth_rate", self.zone_growth_rate);
+
+        // Energy
+        v.unit("energy_decay_rate", self.energy_decay_rate);
+        v.unit("min_energy_to_live", self.min_energy_to_live);
+
+        // Senescence
+        v.unit(
+            "senescence_base_probability",
+            self.senescence_base_probability,
+        );
+        v.positive(
+            "senescence_nutrient_flow_threshold",
+            self.senescence_nutrient_flow_threshold,
+        );
+        v.non_negative(
+            "senescence_distance_threshold",
+            self.senescence_distance_threshold,
+        );
+        v.positive(
+            "senescence_weather_extreme_threshold",
+            self.senescence_weather_extreme_threshold,
+        );
+        v.positive(
+            "senescence_unsupported_collapse_distance",
+            self.senescence_unsupported_collapse_distance,
+        );
+        v.non_negative("senescence_min_age", self.senescence_min_age);
+
+        // Anastomosis, fusion and density
+        v.non_negative("anastomosis_distance", self.anastomosis_distance);
+        v.unit("connection_flow_rate", self.connection_flow_rate);
+        v.non_negative("hyphae_avoidance_distance", self.hyphae_avoidance_distance);
+        v.positive(
+            "density_inhibition_threshold",
+            self.density_inhibition_threshold,
+        );
+        v.unit(
+            "density_inhibition_strength",
+            self.density_inhibition_strength,
+        );
+        v.unit("density_decay_rate", self.density_decay_rate);
+        v.non_negative("fusion_distance", self.fusion_distance);
+        v.unit("fusion_energy_transfer", self.fusion_energy_transfer);
+        v.non_negative("fusion_min_age", self.fusion_min_age);
+        if self.fusion_enabled && self.fusion_distance > self.anastomosis_distance {
+            v.fail(
+                "fusion_distance",
+                format!(
+                    "must not exceed anastomosis_distance ({}), got {}",
+                    self.anastomosis_distance, self.fusion_distance
+                ),
+            );
+        }
+
+        // Segments
+        v.positive("max_segment_age", self.max_segment_age);
+        v.non_negative("segment_age_increment", self.segment_age_increment);
+
+        // Fruiting
+        v.non_negative("fruiting_cooldown", self.fruiting_cooldown);
+        v.positive("fruiting_lifespan_min", self.fruiting_lifespan_min);
+        v.finite("fruiting_lifespan_max", self.fruiting_lifespan_max);
+        if self.fruiting_lifespan_max < self.fruiting_lifespan_min {
+            v.fail(
+                "fruiting_lifespan_max",
+                format!(
+                    "must not be below fruiting_lifespan_min ({}), got {}",
+                    self.fruiting_lifespan_min, self.fruiting_lifespan_max
+                ),
+            );
+        }
+        v.unit(
+            "fruiting_spore_release_fraction",
+            self.fruiting_spore_release_fraction,
+        );
+        v.non_negative("fruiting_spore_drift", self.fruiting_spore_drift);
+        v.non_negative("fruiting_spore_radius", self.fruiting_spore_radius);
+        v.unit(
+            "fruiting_nutrient_return_fraction",
+            self.fruiting_nutrient_return_fraction,
+        );
+        v.unit(
+            "fruiting_spore_release_interval",
+            self.fruiting_spore_release_interval,
+        );
+
+        // Nutrient regeneration
+        v.unit("nutrient_regen_rate", self.nutrient_regen_rate);
+        v.unit("nutrient_regen_floor", self.nutrient_regen_floor);
+
+        // Network intelligence
+        v.unit("signal_decay_rate", self.signal_decay_rate);
+        v.non_negative("signal_strength_threshold", self.signal_strength_threshold);
+        v.non_negative(
+            "signal_trigger_nutrient_threshold",
+            self.signal_trigger_nutrient_threshold,
+        );
+        v.non_negative("flow_strengthening_rate", self.flow_strengthening_rate);
+        v.unit("flow_decay_rate", self.flow_decay_rate);
+        v.unit("min_connection_strength", self.min_connection_strength);
+        v.unit("pruning_threshold", self.pruning_threshold);
+        v.unit("memory_decay_rate", self.memory_decay_rate);
+        v.unit("memory_update_strength", self.memory_update_strength);
+        v.unit("memory_influence", self.memory_influence);
+
+        // Growth limits
+        if self.max_hyphae > 0 && self.max_hyphae_branching_threshold > self.max_hyphae {
+            v.fail(
+                "max_hyphae_branching_threshold",
+                format!(
+                    "must not exceed max_hyphae ({}), got {}",
+                    self.max_hyphae, self.max_hyphae_branching_threshold
+                ),
+            );
+        }
+        if self.max_hyphae > 0 && self.initial_hyphae_count > self.max_hyphae {
+            v.fail(
+                "initial_hyphae_count",
+                format!(
+                    "must not exceed max_hyphae ({}), got {}",
+                    self.max_hyphae, self.initial_hyphae_count
+                ),
+            );
+        }
+
+        // Moisture and light
+        v.unit("moisture_diffusion_rate", self.moisture_diffusion_rate);
+        v.unit("moisture_decay_rate", self.moisture_decay_rate);
+        v.unit("moisture_rain_gain", self.moisture_rain_gain);
+        v.unit("light_growth_penalty", self.light_growth_penalty);
+
+        // Time stepping
+        v.positive("time_step", self.time_step);
+
+        // History recorder
+        if self.history_interval > 0 && self.history_capacity == 0 {
+            v.fail(
+                "history_capacity",
+                "must be at least 1 while history_interval is set".to_string(),
+            );
+        }
+        for name in &self.history_metrics {
+            if HistoryMetric::parse(name).is_none() {
+                v.fail("history_metrics", format!("unknown metric '{}'", name));
+            }
+        }
+
+        // Time-lapse
+        if self.timelapse_interval == 0 {
+            v.fail("timelapse_interval", "must be at least 1".to_string());
+        }
+        if !(self.timelapse_scale > 0.0 && self.timelapse_scale <= MAX_RENDER_SCALE) {
+            v.fail(
+                "timelapse_scale",
+                format!(
+                    "must be greater than 0 and at most {}, got {}",
+                    MAX_RENDER_SCALE, self.timelapse_scale
+                ),
+            );
+        }
+        if self.timelapse_gif_delay_ms == 0 {
+            v.fail("timelapse_gif_delay_ms", "must be at least 1".to_string());
+        }
+
+        if v.issues.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigValidationError { issues: v.issues })
+        }
+    }
+}
+
+/// How a change to a config field reaches a running simulation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldEffect {
+    /// Read every step, so a new value applies immediately
+    Live,
+    /// Only read when the simulation is initialized (obstacles, zones, maps, initial hyphae, seed)
+    Initialization,
+    /// Sizes allocated grids, so changing it requires re-initializing the simulation
+    Rebuild,
+}
+
+impl FieldEffect {
+    pub fn of(field: &str) -> Self {
+        match field {
+            "grid_size" | "cell_size" | "density_map_resolution" => FieldEffect::Rebuild,
+            "obstacle_count"
+            | "toxic_zone_count"
+            | "competitor_zone_count"
+            | "deadwood_patch_count"
+            | "toxic_zone_radius"
+            | "competitor_zone_radius"
+            | "shaded_zone_count"
+            | "sunlit_zone_count"
+            | "initial_hyphae_count"
+            | "sugar_map"
+            | "nitrogen_map"
+            | "obstacle_map"
+            | "moisture_map"
+            | "light_map"
+            | "zone_map"
+            | "seed" => FieldEffect::Initialization,
+            _ => FieldEffect::Live,
+        }
+    }
+}
+
+impl SimulationConfig {
+    /// Overlay a partial JSON object onto this config.
+    /// Returns the merged config and the names of the fields present in the patch.
+    /// Unknown fields and values of the wrong type are rejected.
+    pub fn with_patch(&self, patch: &serde_json::Value) -> Result<(Self, Vec<String>), String> {
+        let patch = patch
+            .as_object()
+            .ok_or_else(|| "Config patch must be a JSON object".to_string())?;
+        let mut merged = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        let fields = merged
+            .as_object_mut()
+            .ok_or_else(|| "Config did not serialize to an object".to_string())?;
+
+        let unknown = Self::unknown_fields(patch.keys());
+        if !unknown.is_empty() {
+            return Err(format!("Unknown config fields: {}", unknown.join(", ")));
+        }
+
+        for (key, value) in patch {
+            fields.insert(key.clone(), value.clone());
+        }
+        let config: Self = serde_json::from_value(merged).map_err(|e| e.to_string())?;
+        Ok((config, patch.keys().cloned().collect()))
+    }
+}
+
+/// A built-in set of config overrides, selectable with `--preset`
+pub struct ConfigPreset {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Sparse YAML layered on top of the base config
+    overrides: &'static str,
+}
+
+impl ConfigPreset {
+    /// The preset's fields as a config patch
+    pub fn overrides(&self) -> serde_json::Value {
+        serde_yaml::from_str(self.overrides).expect("built-in preset is valid YAML")
+    }
+}
+
+/// Built-in presets
+pub const PRESETS: &[ConfigPreset] = &[
+    ConfigPreset {
+        name: "drought-summer",
+        description:
+            "Hot, dry season: fast evaporation, little rain, sparse regrowth, bright open ground",
+        overrides: "
+soil_moisture_enabled: true
+moisture_decay_rate: 0.99     # Moisture evaporates ten times faster
+moisture_rain_gain: 0.01      # Rare, light rain
+moisture_growth_multiplier: 0.8
+nutrient_regen_rate: 0.001
+light_exposure_enabled: true
+light_growth_penalty: 0.6
+shaded_zone_count: 2
+sunlit_zone_count: 12
+",
+    },
+    ConfigPreset {
+        name: "pure-diffusion",
+        description:
+            "No zones, obstacles, flow or weather: growth driven only by nutrient diffusion",
+        overrides: "
+obstacle_count: 0
+zones_enabled: false
+flow_enabled: false
+pressure_flow_enabled: false
+tropism_strength: 0.0
+weather_enabled: false
+seasonal_cycles_enabled: false
+soil_moisture_enabled: false
+light_exposure_enabled: false
+",
+    },
+    ConfigPreset {
+        name: "forest-floor",
+        description:
+            "High-competition forest floor: many competitors and deadwood patches under heavy shade",
+        overrides: "
+zones_enabled: true
+competitor_zone_count: 12
+competitor_zone_radius: 14.0
+competitor_nutrient_consumption: 0.012
+deadwood_patch_count: 20
+toxic_zone_count: 3
+initial_hyphae_count: 10
+shaded_zone_count: 16
+sunlit_zone_count: 2
+moisture_decay_rate: 0.9995
+",
+    },
+];
+
+/// Look up a built-in preset by name
+pub fn find_preset(name: &str) -> Option<&'static ConfigPreset> {
+    PRESETS.iter().find(|preset| preset.name == name)
+}
+
+/// Where a run's config comes from. Layers are applied in order: the base
+/// (`SimulationConfig::from_default_paths`), the preset, each file, then the
+/// `key=value` overrides.
+#[derive(Clone, Debug, Default)]
+pub struct ConfigSources {
+    pub preset: Option<String>,
+    pub files: Vec<String>,
+    pub overrides: Vec<String>,
+}
+
+impl ConfigSources {
+    /// Merge every layer and validate the result
+    pub fn load(&self) -> Result<SimulationConfig, Box<dyn std::error::Error>> {
+        let (config, _) = self.apply_to(&SimulationConfig::from_default_paths()?)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Layer the preset, files and overrides onto `base` without validating.
+    /// Returns the merged config and the names of the fields the layers set.
+    pub fn apply_to(
+        &self,
+        base: &SimulationConfig,
+    ) -> Result<(SimulationConfig, Vec<String>), Box<dyn std::error::Error>> {
+        let mut config = base.clone();
+        let mut fields = Vec::new();
+
+        if let Some(name) = &self.preset {
+            let preset = find_preset(name).ok_or_else(|| {
+                let names: Vec<&str> = PRESETS.iter().map(|preset| preset.name).collect();
+                format!(
+                    "Unknown preset '{}' (available: {})",
+                    name,
+                    names.join(", ")
+                )
+            })?;
+            let (patched, set) = config.with_patch(&preset.overrides())?;
+            config = patched;
+            fields.extend(set);
+        }
+
+        for path in &self.files {
+            let (patched, set) = SimulationConfig::read_value(path)
+                .and_then(|value| Ok(config.with_patch(&value)?))
+                .map_err(|e| format!("Failed to load config from {}: {}", path, e))?;
+            config = patched;
+            fields.extend(set);
+        }
+
+        if !self.overrides.is_empty() {
+            let mut patch = serde_json::Map::new();
+            for item in &self.overrides {
+                let (key, value) = parse_override(item)?;
+                patch.insert(key, value);
             }
+            let (patched, set) = config
+                .with_patch(&serde_json::Value::Object(patch))
+                .map_err(|e| format!("Invalid --set override: {}", e))?;
+            config = patched;
+            fields.extend(set);
+        }
+
+        fields.sort();
+        fields.dedup();
+        Ok((config, fields))
+    }
+}
+
+/// Parse a `key=value` override; the value is read as YAML so numbers,
+/// booleans and `null` get their natural types
+fn parse_override(item: &str) -> Result<(String, serde_json::Value), String> {
+    let (key, value) = item
+        .split_once('=')
+        .ok_or_else(|| format!("Invalid --set override '{}': expected key=value", item))?;
+    let key = key.trim();
+    if key.is_empty() {
+        return Err(format!("Invalid --set override '{}': missing key", item));
+    }
+    let value = serde_yaml::from_str(value)
+        .map_err(|e| format!("Invalid --set override '{}': {}", item, e))?;
+    Ok((key.to_string(), value))
+}
+
+/// Load configuration from the default paths, with the given file (if any) layered on top
+pub fn load_config(
+    config_path: Option<&str>,
+) -> Result<SimulationConfig, Box<dyn std::error::Error>> {
+    ConfigSources {
+        files: config_path.into_iter().map(String::from).collect(),
+        ..Default::default()
+    }
+    .load()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that a patch changes only the fields it names and reports their effect
+    #[test]
+    fn test_config_patch() {
+        let config = SimulationConfig::default();
+        let (patched, fields) = config
+            .with_patch(&serde_json::json!({ "branch_prob": 0.02, "weather_enabled": false }))
+            .unwrap();
+        assert_eq!(patched.branch_prob, 0.02);
+        assert!(!patched.weather_enabled);
+        assert_eq!(patched.grid_size, config.grid_size);
+        assert_eq!(fields.len(), 2);
+
+        assert!(config
+            .with_patch(&serde_json::json!({ "branch_probability": 0.02 }))
+            .is_err());
+        assert!(config
+            .with_patch(&serde_json::json!({ "grid_size": "large" }))
+            .is_err());
+        assert_eq!(FieldEffect::of("grid_size"), FieldEffect::Rebuild);
+        assert_eq!(FieldEffect::of("memory_decay_rate"), FieldEffect::Live);
+    }
+
+    /// Test that partial config files fill in defaults and reject unknown fields
+    #[test]
+    fn test_sparse_config() {
+        let config = SimulationConfig::from_value(serde_json::json!({ "grid_size": 120 })).unwrap();
+        assert_eq!(config.grid_size, 120);
+        assert_eq!(config.step_size, SimulationConfig::default().step_size);
+
+        let empty = SimulationConfig::from_value(serde_json::Value::Null).unwrap();
+        assert_eq!(empty.grid_size, SimulationConfig::default().grid_size);
+
+        let err = SimulationConfig::from_value(serde_json::json!({ "grid_siz": 120 })).unwrap_err();
+        assert!(err.to_string().contains("grid_siz"));
+
+        // A default-path config with a typo is an error, not silently replaced by defaults
+        let dir = std::env::temp_dir().join(format!("mycorust_config_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let missing = dir.join("missing.yaml").to_string_lossy().into_owned();
+        let typo = dir.join("typo.yaml");
+        std::fs::write(&typo, "grid_siz: 120\n").unwrap();
+        let typo = typo.to_string_lossy().into_owned();
+        let config = SimulationConfig::from_first_existing(&[&missing]).unwrap();
+        assert_eq!(config.grid_size, SimulationConfig::default().grid_size);
+        let err = SimulationConfig::from_first_existing(&[&missing, &typo]).unwrap_err();
+        assert!(err.to_string().contains("grid_siz"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Test that presets validate and that files and overrides apply on top of a preset
+    #[test]
+    fn test_config_layers() {
+        for preset in PRESETS {
+            let (config, _) = SimulationConfig::default()
+                .with_patch(&preset.overrides())
+                .unwrap();
+            assert!(config.validate().is_ok(), "preset {}", preset.name);
         }
 
-        // No config file found, use default
-        Self::default()
+        let path = std::env::temp_dir().join(format!("mycorust_layer_{}.yaml", std::process::id()));
+        std::fs::write(&path, "branch_prob: 0.02\nobstacle_count: 7\n").unwrap();
+        let sources = ConfigSources {
+            preset: Some("pure-diffusion".to_string()),
+            files: vec![path.display().to_string()],
+            overrides: vec!["obstacle_count=11".to_string(), "seed=5".to_string()],
+        };
+        let config = sources.load().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(!config.zones_enabled); // preset
+        assert_eq!(config.branch_prob, 0.02); // file
+        assert_eq!(config.obstacle_count, 11); // override beats file
+        assert_eq!(config.seed, Some(5));
+
+        assert!(parse_override("branch_prob").is_err());
+        let unknown = ConfigSources {
+            preset: Some("rainforest".to_string()),
+            ..Default::default()
+        };
+        assert!(unknown.load().is_err());
+    }
+
+    /// Test that the preset, files and overrides layer in order onto the base config
+    #[test]
+    fn test_config_layer_precedence() {
+        let base = SimulationConfig {
+            grid_size: 150,
+            branch_prob: 0.05,
+            obstacle_count: 3,
+            ..SimulationConfig::default()
+        };
+        let path = std::env::temp_dir().join(format!("mycorust_order_{}.yaml", std::process::id()));
+        std::fs::write(&path, "flow_enabled: true\nbranch_prob: 0.02\n").unwrap();
+        let sources = ConfigSources {
+            preset: Some("pure-diffusion".to_string()),
+            files: vec![path.display().to_string()],
+            overrides: vec!["branch_prob=0.03".to_string()],
+        };
+        let (config, fields) = sources.apply_to(&base).unwrap();
+        assert_eq!(config.grid_size, 150); // base, untouched by any layer
+        assert_eq!(config.obstacle_count, 0); // preset beats base
+        assert!(config.flow_enabled); // file beats preset
+        assert_eq!(config.branch_prob, 0.03); // override beats file and base
+        assert!(fields.contains(&"flow_enabled".to_string()));
+        assert!(!fields.contains(&"grid_size".to_string()));
+
+        // Giving a file still starts from the default-path config, not the built-in defaults
+        let (expected, _) = sources
+            .apply_to(&SimulationConfig::from_default_paths().unwrap())
+            .unwrap();
+        let loaded = sources.load().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            serde_json::to_value(&loaded).unwrap(),
+            serde_json::to_value(&expected).unwrap()
+        );
+    }
+
+    /// Test that the shipped config is valid and out-of-range values are reported per field
+    #[test]
+    fn test_config_validation() {
+        assert!(SimulationConfig::default().validate().is_ok());
+        let shipped = SimulationConfig::from_file("config.yaml").unwrap();
+        assert!(shipped.validate().is_ok(), "config.yaml should be valid");
+
+        let config = SimulationConfig {
+            fusion_distance: 3.0,
+            max_hyphae_branching_threshold: 5000,
+            step_size: -0.5,
+            memory_decay_rate: 1.2,
+            cell_size: 0.0,
+            ..SimulationConfig::default()
+        };
+        let error = config.validate().unwrap_err();
+        let fields: Vec<&str> = error.issues.iter().map(|i| i.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "cell_size",
+                "step_size",
+                "fusion_distance",
+                "memory_decay_rate",
+                "max_hyphae_branching_threshold"
+            ]
+        );
+        assert!(error.to_string().contains("anastomosis_distance"));
     }
 }
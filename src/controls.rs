@@ -1,8 +1,8 @@
-use ::rand as external_rand;
-use external_rand::Rng;
 use macroquad::prelude::*;
 
-use crate::simulation::Simulation;
+use crate::graph_export::GraphFormat;
+use crate::render::RenderOptions;
+use crate::simulation::{SimRng, Simulation};
 
 pub struct ControlText {
     pub text: &'static str,
@@ -10,7 +10,7 @@ pub struct ControlText {
     pub color: Color,
 }
 
-pub fn handle_controls<R: Rng>(sim: &mut Simulation, rng: &mut R) {
+pub fn handle_controls(sim: &mut Simulation, rng: &mut SimRng) {
     // Keyboard controls
     // Only toggle pause if space is pressed without left mouse (to avoid conflict with pan)
     // Camera handles space+left for panning, so we only toggle pause if left mouse is not down
@@ -99,6 +99,77 @@ pub fn handle_controls<R: Rng>(sim: &mut Simulation, rng: &mut R) {
         sim.take_screenshot = true;
     }
 
+    // Time-lapse recording toggle (O key)
+    if is_key_pressed(KeyCode::O) {
+        if let Some(timelapse) = sim.stop_timelapse() {
+            println!(
+                "Time-lapse saved: {} frames in {}",
+                timelapse.frame_count(),
+                timelapse.dir().display()
+            );
+        } else {
+            let timestamp = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            let dir = format!("mycorust_timelapse_{}", timestamp);
+            match sim.start_timelapse(&dir) {
+                Ok(_) => println!("Recording time-lapse into {}", dir),
+                Err(e) => eprintln!("Failed to start time-lapse {}: {}", dir, e),
+            }
+        }
+    }
+
+    // Save snapshot (F5 key)
+    if is_key_pressed(KeyCode::F5) {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let filename = format!("mycorust_snapshot_{}.json", timestamp);
+        match sim.save_snapshot(rng, &filename) {
+            Ok(_) => println!("Snapshot saved: {}", filename),
+            Err(e) => eprintln!("Failed to save snapshot {}: {}", filename, e),
+        }
+    }
+
+    // Export the hyphal network (F6 = GraphML, F7 = GEXF)
+    for (key, format) in [
+        (KeyCode::F6, GraphFormat::GraphMl),
+        (KeyCode::F7, GraphFormat::Gexf),
+    ] {
+        if is_key_pressed(key) {
+            let timestamp = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            let filename = format!("mycorust_network_{}.{}", timestamp, format.extension());
+            match sim.save_graph(&filename) {
+                Ok(_) => println!("Network exported: {}", filename),
+                Err(e) => eprintln!("Failed to export network {}: {}", filename, e),
+            }
+        }
+    }
+
+    // Export the current view as SVG (F8 key)
+    if is_key_pressed(KeyCode::F8) {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let filename = format!("mycorust_{}.svg", timestamp);
+        let options = RenderOptions::from_view(sim, 1.0);
+        match sim.save_svg(&filename, &options) {
+            Ok(_) => println!("SVG exported: {}", filename),
+            Err(e) => eprintln!("Failed to export SVG {}: {}", filename, e),
+        }
+    }
+
+    // Step profile overlay (F3 key)
+    if is_key_pressed(KeyCode::F3) {
+        sim.toggle_profile_overlay();
+    }
+
     // Help popup (F1 key, or Escape to close when visible)
     if is_key_pressed(KeyCode::F1) {
         sim.toggle_help_popup();
@@ -225,7 +296,7 @@ pub fn get_controls_text(camera_enabled: bool) -> Vec<ControlText> {
         },
         if camera_enabled {
             ControlText {
-                text: "Camera: Arrow Keys/WASD=Pan | Mouse Wheel=Zoom | Middle Mouse/Space+LMB=Drag | Home=Reset | C=Toggle | P=Screenshot",
+                text: "Camera: Arrow Keys/WASD=Pan | Mouse Wheel=Zoom | Middle Mouse/Space+LMB=Drag | Home=Reset | C=Toggle | P=Screenshot | O=Time-lapse",
                 font_size: 16.0,
                 color: Color::new(1.0, 1.0, 1.0, 0.7),
             }
@@ -237,7 +308,7 @@ pub fn get_controls_text(camera_enabled: bool) -> Vec<ControlText> {
             }
         },
         ControlText {
-            text: "Visualization: V=Enhanced | F=Flow | 1=Stress",
+            text: "Visualization: V=Enhanced | F=Flow | 1=Stress | F3=Step profile",
             font_size: 16.0,
             color: Color::new(1.0, 1.0, 1.0, 0.7),
         },
@@ -0,0 +1,216 @@
+// Environment maps - initialize sugar, nitrogen, obstacles, moisture, light and zones from image
+// files instead of the random generators, e.g. a maze PNG for obstacles or a food layout for sugar.
+// Each image is stretched to grid_size x grid_size; pixel (x, y) becomes grid cell (x, y), top-left first.
+
+use image::imageops::FilterType;
+use image::DynamicImage;
+
+use crate::config::SimulationConfig;
+use crate::grid::Grid2D;
+use crate::nutrients::NutrientGrid;
+use crate::render::zone_rgb;
+use crate::simulation::SimulationState;
+use crate::types::{Zone, ZoneType};
+
+// Pixels darker than this (and mostly opaque) are obstacles
+const OBSTACLE_THRESHOLD: f32 = 0.5;
+
+// Zone types a zone map can paint, with the intensity generated zones start at
+const ZONE_TYPES: [(ZoneType, f32); 3] = [
+    (ZoneType::Toxic, 0.8),
+    (ZoneType::Competitor, 0.7),
+    (ZoneType::Deadwood, 0.5),
+];
+
+/// Grids loaded from the `*_map` config fields (None where no map is set)
+#[derive(Default)]
+pub struct EnvironmentMaps {
+    pub sugar: Option<Grid2D<f32>>,
+    pub nitrogen: Option<Grid2D<f32>>,
+    pub obstacles: Option<Grid2D<bool>>,
+    pub moisture: Option<Grid2D<f32>>,
+    pub light: Option<Grid2D<f32>>,
+    pub zones: Option<Grid2D<Zone>>,
+}
+
+impl EnvironmentMaps {
+    /// Load and resample every map set in the config.
+    pub fn load(config: &SimulationConfig) -> Result<Self, String> {
+        let size = config.grid_size as u32;
+        Ok(Self {
+            sugar: load_map(&config.sugar_map, |img| luminance_grid(img, size))?,
+            nitrogen: load_map(&config.nitrogen_map, |img| luminance_grid(img, size))?,
+            obstacles: load_map(&config.obstacle_map, |img| obstacle_grid(img, size))?,
+            moisture: load_map(&config.moisture_map, |img| luminance_grid(img, size))?,
+            light: load_map(&config.light_map, |img| luminance_grid(img, size))?,
+            zones: load_map(&config.zone_map, |img| zone_grid(img, size))?,
+        })
+    }
+
+    /// Overwrite sugar and nitrogen where maps are set.
+    pub(crate) fn apply_nutrients(&self, nutrients: &mut NutrientGrid) {
+        if let Some(sugar) = &self.sugar {
+            nutrients.sugar.clone_from(sugar);
+        }
+        if let Some(nitrogen) = &self.nitrogen {
+            nutrients.nitrogen.clone_from(nitrogen);
+        }
+    }
+
+    /// Overwrite every grid that has a map.
+    pub(crate) fn apply(&self, state: &mut SimulationState) {
+        self.apply_nutrients(&mut state.nutrients);
+        if let Some(obstacles) = &self.obstacles {
+            state.obstacles.clone_from(obstacles);
+        }
+        if let Some(moisture) = &self.moisture {
+            state.soil_moisture.clone_from(moisture);
+        }
+        if let Some(light) = &self.light {
+            state.light_exposure.clone_from(light);
+        }
+        if let Some(zones) = &self.zones {
+            state.zones.clone_from(zones);
+        }
+    }
+}
+
+fn load_map<T>(
+    path: &Option<String>,
+    convert: impl Fn(&DynamicImage) -> T,
+) -> Result<Option<T>, String> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    let image = image::open(path).map_err(|e| format!("Failed to load map {}: {}", path, e))?;
+    Ok(Some(convert(&image)))
+}
+
+// Build a grid from a resampled image
+fn grid<T>(width: u32, mut cell: impl FnMut(u32, u32) -> T) -> Grid2D<T> {
+    let width = width as usize;
+    Grid2D::from_fn(width, width, |x, y| cell(x as u32, y as u32))
+}
+
+// Brightness 0.0 (black) to 1.0 (white), smoothly resampled
+fn luminance_grid(image: &DynamicImage, size: u32) -> Grid2D<f32> {
+    let luma = image
+        .resize_exact(size, size, FilterType::Triangle)
+        .to_luma32f();
+    grid(size, |x, y| luma.get_pixel(x, y)[0].clamp(0.0, 1.0))
+}
+
+// Dark opaque pixels are obstacles; nearest-neighbour resampling keeps walls crisp
+fn obstacle_grid(image: &DynamicImage, size: u32) -> Grid2D<bool> {
+    let luma = image
+        .resize_exact(size, size, FilterType::Nearest)
+        .to_luma_alpha32f();
+    grid(size, |x, y| {
+        let [value, alpha] = luma.get_pixel(x, y).0;
+        value < OBSTACLE_THRESHOLD && alpha >= 0.5
+    })
+}
+
+// Each pixel takes the nearest zone color of the window (red toxic, yellow competitor,
+// brown deadwood), or no zone when black or white is nearer; alpha scales the intensity
+fn zone_grid(image: &DynamicImage, size: u32) -> Grid2D<Zone> {
+    let rgba = image
+        .resize_exact(size, size, FilterType::Nearest)
+        .to_rgba32f();
+    let distance = |a: &[f32], b: &[f32; 3]| (0..3).map(|i| (a[i] - b[i]).powi(2)).sum::<f32>();
+    grid(size, |x, y| {
+        let pixel = rgba.get_pixel(x, y).0;
+        let none = distance(&pixel, &[0.0; 3]).min(distance(&pixel, &[1.0; 3]));
+        let nearest = ZONE_TYPES
+            .iter()
+            .filter_map(|&(zone_type, intensity)| {
+                let color = zone_rgb(zone_type)?;
+                Some((distance(&pixel, &color), zone_type, intensity))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .filter(|&(d, _, _)| d < none && pixel[3] > 0.0);
+        match nearest {
+            Some((_, zone_type, intensity)) => Zone {
+                zone_type,
+                intensity: intensity * pixel[3],
+                age: 0.0,
+            },
+            None => Zone {
+                zone_type: ZoneType::None,
+                intensity: 0.0,
+                age: 0.0,
+            },
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{create_rng, Simulation};
+    use image::{Rgba, RgbaImage};
+
+    /// Test that image maps set nutrients, light, obstacles and zones, and are reapplied on reset
+    #[test]
+    fn test_environment_maps() {
+        let dir = std::env::temp_dir().join(format!("mycorust_env_maps_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // Left half bright, right half black: sugar on the left, a wall on the right
+        let halves = RgbaImage::from_fn(40, 40, |x, _| {
+            if x < 20 {
+                Rgba([255, 255, 255, 255])
+            } else {
+                Rgba([0, 0, 0, 255])
+            }
+        });
+        let halves_path = dir.join("halves.png");
+        halves.save(&halves_path).unwrap();
+        // Top half toxic red, bottom half competitor yellow
+        let zones = RgbaImage::from_fn(40, 40, |_, y| {
+            if y < 20 {
+                Rgba([250, 40, 40, 255])
+            } else {
+                Rgba([200, 200, 60, 255])
+            }
+        });
+        let zones_path = dir.join("zones.png");
+        zones.save(&zones_path).unwrap();
+
+        let halves_path = halves_path.to_string_lossy().into_owned();
+        let config = SimulationConfig {
+            grid_size: 20,
+            sugar_map: Some(halves_path.clone()),
+            obstacle_map: Some(halves_path.clone()),
+            light_map: Some(halves_path),
+            zone_map: Some(zones_path.to_string_lossy().into_owned()),
+            ..SimulationConfig::default()
+        };
+        let mut rng = create_rng(2);
+        let mut sim = Simulation::try_with_config(&mut rng, config.clone()).unwrap();
+        assert!(sim.nutrients.sugar[2][5] > 0.99);
+        assert!(sim.nutrients.sugar[17][5] < 0.01);
+        assert!(sim.light_exposure[2][5] > 0.99);
+        assert!(!sim.obstacles[2][5] && sim.obstacles[17][5]);
+        // Random obstacles are replaced, not added to
+        assert_eq!(sim.obstacles.iter().filter(|&&o| o).count(), 200);
+        assert_eq!(sim.zones[5][2].zone_type, ZoneType::Toxic);
+        assert_eq!(sim.zones[5][17].zone_type, ZoneType::Competitor);
+        assert_eq!(sim.zones[5][17].intensity, 0.7);
+
+        let missing = SimulationConfig {
+            moisture_map: Some(dir.join("missing.png").to_string_lossy().into_owned()),
+            ..config
+        };
+        assert!(Simulation::try_with_config(&mut rng, missing.clone()).is_err());
+        // Without the error, the maps are left out and the generated grids kept
+        let fallback = Simulation::with_config(&mut rng, missing);
+        assert!(fallback.env_maps.moisture.is_none() && fallback.env_maps.sugar.is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // Reset reapplies the nutrient maps loaded at creation, without the files
+        sim.reset(&mut rng);
+        assert!(sim.nutrients.sugar[2][5] > 0.99);
+        assert!(sim.nutrients.sugar[17][5] < 0.01);
+    }
+}
@@ -0,0 +1,361 @@
+// Network export - write the hyphal network as a graph file (GraphML or GEXF)
+// Nodes are hyphae (id = hypha id, stable across frames); edges are parent links and anastomosis connections
+// so colonies can be opened in Gephi, NetworkX, Cytoscape and similar tools
+
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::simulation::Simulation;
+
+/// Supported graph file formats
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphFormat {
+    GraphMl,
+    Gexf,
+}
+
+impl GraphFormat {
+    /// Parse a format name (`graphml` or `gexf`, case-insensitive)
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "graphml" => Some(Self::GraphMl),
+            "gexf" => Some(Self::Gexf),
+            _ => None,
+        }
+    }
+
+    /// Pick the format from a file extension (`.graphml` or `.gexf`)
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        path.as_ref()
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Self::parse)
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::GraphMl => "graphml",
+            Self::Gexf => "gexf",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::GraphMl => "application/graphml+xml",
+            Self::Gexf => "application/gexf+xml",
+        }
+    }
+}
+
+// One exported edge between hypha slots; parent edges run from parent to child
+pub(crate) struct GraphEdge {
+    pub(crate) source: usize,
+    pub(crate) target: usize,
+    pub(crate) kind: &'static str,
+    pub(crate) strength: f32,
+    pub(crate) flow: f32,
+    pub(crate) signal: f32,
+}
+
+// Node attributes as (name, GraphML/GEXF type)
+const NODE_ATTRIBUTES: [(&str, &str); 9] = [
+    ("x", "float"),
+    ("y", "float"),
+    ("energy", "float"),
+    ("age", "float"),
+    ("carbon", "float"),
+    ("nitrogen", "float"),
+    ("senescence", "float"),
+    ("strength", "float"),
+    ("alive", "boolean"),
+];
+
+const EDGE_ATTRIBUTES: [(&str, &str); 4] = [
+    ("type", "string"),
+    ("strength", "float"),
+    ("flow", "float"),
+    ("signal", "float"),
+];
+
+impl Simulation {
+    // Parent links first, then anastomosis connections
+    pub(crate) fn graph_edges(&self) -> Vec<GraphEdge> {
+        let mut edges = Vec::with_capacity(self.hyphae.len() + self.connections.len());
+        for (i, h) in self.hyphae.iter().enumerate() {
+            // The parent may have fused or been culled since
+            if let Some(parent) = h.parent.and_then(|id| self.hypha_slot(id)) {
+                // Hyphal segments have no flow of their own; use the child's branch strength
+                edges.push(GraphEdge {
+                    source: parent,
+                    target: i,
+                    kind: "parent",
+                    strength: h.strength,
+                    flow: 0.0,
+                    signal: h.signal_received,
+                });
+            }
+        }
+        for c in &self.connections {
+            let (Some(source), Some(target)) =
+                (self.hypha_slot(c.hypha1), self.hypha_slot(c.hypha2))
+            else {
+                continue;
+            };
+            edges.push(GraphEdge {
+                source,
+                target,
+                kind: "anastomosis",
+                strength: c.strength,
+                flow: c.flow_accumulator,
+                signal: c.signal,
+            });
+        }
+        edges
+    }
+
+    fn node_values(&self, i: usize) -> [String; 9] {
+        let h = &self.hyphae[i];
+        [
+            h.x.to_string(),
+            h.y.to_string(),
+            h.energy.to_string(),
+            h.age.to_string(),
+            h.carbon.to_string(),
+            h.nitrogen.to_string(),
+            h.senescence_factor.to_string(),
+            h.strength.to_string(),
+            h.alive.to_string(),
+        ]
+    }
+
+    /// Write the hyphal network in the given format.
+    pub fn write_graph<W: Write>(
+        &self,
+        writer: W,
+        format: GraphFormat,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match format {
+            GraphFormat::GraphMl => self.write_graphml(writer),
+            GraphFormat::Gexf => self.write_gexf(writer),
+        }
+    }
+
+    /// Write the hyphal network as GraphML.
+    pub fn write_graphml<W: Write>(&self, mut w: W) -> Result<(), Box<dyn std::error::Error>> {
+        writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            w,
+            r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://graphml.graphdrawing.org/xmlns http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd">"#
+        )?;
+        for (name, ty) in NODE_ATTRIBUTES {
+            writeln!(
+                w,
+                r#"  <key id="n_{0}" for="node" attr.name="{0}" attr.type="{1}"/>"#,
+                name, ty
+            )?;
+        }
+        for (name, ty) in EDGE_ATTRIBUTES {
+            writeln!(
+                w,
+                r#"  <key id="e_{0}" for="edge" attr.name="{0}" attr.type="{1}"/>"#,
+                name, ty
+            )?;
+        }
+        writeln!(
+            w,
+            r#"  <graph id="frame_{}" edgedefault="undirected">"#,
+            self.frame_index
+        )?;
+
+        for (i, h) in self.hyphae.iter().enumerate() {
+            writeln!(w, r#"    <node id="{}">"#, h.id)?;
+            for ((name, _), value) in NODE_ATTRIBUTES.iter().zip(self.node_values(i)) {
+                writeln!(w, r#"      <data key="n_{}">{}</data>"#, name, value)?;
+            }
+            writeln!(w, "    </node>")?;
+        }
+
+        for (id, edge) in self.graph_edges().iter().enumerate() {
+            writeln!(
+                w,
+                r#"    <edge id="e{}" source="{}" target="{}">"#,
+                id, self.hyphae[edge.source].id, self.hyphae[edge.target].id
+            )?;
+            writeln!(w, r#"      <data key="e_type">{}</data>"#, edge.kind)?;
+            writeln!(
+                w,
+                r#"      <data key="e_strength">{}</data>"#,
+                edge.strength
+            )?;
+            writeln!(w, r#"      <data key="e_flow">{}</data>"#, edge.flow)?;
+            writeln!(w, r#"      <data key="e_signal">{}</data>"#, edge.signal)?;
+            writeln!(w, "    </edge>")?;
+        }
+
+        writeln!(w, "  </graph>")?;
+        writeln!(w, "</graphml>")?;
+        Ok(())
+    }
+
+    /// Write the hyphal network as GEXF 1.2 (positions are also stored as `viz:position`).
+    pub fn write_gexf<W: Write>(&self, mut w: W) -> Result<(), Box<dyn std::error::Error>> {
+        writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            w,
+            r#"<gexf xmlns="http://www.gexf.net/1.2draft" xmlns:viz="http://www.gexf.net/1.2draft/viz" version="1.2">"#
+        )?;
+        writeln!(w, "  <meta>")?;
+        writeln!(w, "    <creator>mycorust</creator>")?;
+        writeln!(
+            w,
+            "    <description>Hyphal network at frame {}</description>",
+            self.frame_index
+        )?;
+        writeln!(w, "  </meta>")?;
+        writeln!(w, r#"  <graph mode="static" defaultedgetype="undirected">"#)?;
+
+        writeln!(w, r#"    <attributes class="node">"#)?;
+        for (name, ty) in NODE_ATTRIBUTES {
+            writeln!(
+                w,
+                r#"      <attribute id="{0}" title="{0}" type="{1}"/>"#,
+                name, ty
+            )?;
+        }
+        writeln!(w, "    </attributes>")?;
+        writeln!(w, r#"    <attributes class="edge">"#)?;
+        for (name, ty) in EDGE_ATTRIBUTES {
+            writeln!(
+                w,
+                r#"      <attribute id="{0}" title="{0}" type="{1}"/>"#,
+                name, ty
+            )?;
+        }
+        writeln!(w, "    </attributes>")?;
+
+        writeln!(w, "    <nodes>")?;
+        for (i, h) in self.hyphae.iter().enumerate() {
+            writeln!(w, r#"      <node id="{0}" label="{0}">"#, h.id)?;
+            writeln!(w, "        <attvalues>")?;
+            for ((name, _), value) in NODE_ATTRIBUTES.iter().zip(self.node_values(i)) {
+                writeln!(
+                    w,
+                    r#"          <attvalue for="{}" value="{}"/>"#,
+                    name, value
+                )?;
+            }
+            writeln!(w, "        </attvalues>")?;
+            writeln!(
+                w,
+                r#"        <viz:position x="{}" y="{}" z="0.0"/>"#,
+                h.x, h.y
+            )?;
+            writeln!(w, "      </node>")?;
+        }
+        writeln!(w, "    </nodes>")?;
+
+        writeln!(w, "    <edges>")?;
+        for (id, edge) in self.graph_edges().iter().enumerate() {
+            writeln!(
+                w,
+                r#"      <edge id="{}" source="{}" target="{}">"#,
+                id, self.hyphae[edge.source].id, self.hyphae[edge.target].id
+            )?;
+            writeln!(w, "        <attvalues>")?;
+            writeln!(
+                w,
+                r#"          <attvalue for="type" value="{}"/>"#,
+                edge.kind
+            )?;
+            writeln!(
+                w,
+                r#"          <attvalue for="strength" value="{}"/>"#,
+                edge.strength
+            )?;
+            writeln!(
+                w,
+                r#"          <attvalue for="flow" value="{}"/>"#,
+                edge.flow
+            )?;
+            writeln!(
+                w,
+                r#"          <attvalue for="signal" value="{}"/>"#,
+                edge.signal
+            )?;
+            writeln!(w, "        </attvalues>")?;
+            writeln!(w, "      </edge>")?;
+        }
+        writeln!(w, "    </edges>")?;
+
+        writeln!(w, "  </graph>")?;
+        writeln!(w, "</gexf>")?;
+        Ok(())
+    }
+
+    /// Save the hyphal network to a file; the format comes from the extension (`.graphml` or `.gexf`).
+    pub fn save_graph<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let format = GraphFormat::from_path(path).ok_or_else(|| {
+            format!(
+                "Unknown graph format for {} (use a .graphml or .gexf extension)",
+                path.display()
+            )
+        })?;
+        let mut writer = BufWriter::new(std::fs::File::create(path)?);
+        self.write_graph(&mut writer, format)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimulationConfig;
+    use crate::simulation::create_rng;
+
+    /// Test that GraphML and GEXF exports have one node per hypha and one edge per link
+    #[test]
+    fn test_graph_export() {
+        let config = SimulationConfig {
+            grid_size: 40,
+            ..SimulationConfig::default()
+        };
+        let mut rng = create_rng(3);
+        let mut sim = Simulation::with_config(&mut rng, config);
+        for _ in 0..40 {
+            sim.step(&mut rng);
+        }
+
+        let parent_edges = sim
+            .hyphae
+            .iter()
+            .filter(|h| h.parent.and_then(|id| sim.hypha_slot(id)).is_some())
+            .count();
+        let edge_count = parent_edges + sim.connections.len();
+
+        let mut graphml = Vec::new();
+        sim.write_graph(&mut graphml, GraphFormat::GraphMl).unwrap();
+        let graphml = String::from_utf8(graphml).unwrap();
+        assert_eq!(graphml.matches("<node id=").count(), sim.hyphae.len());
+        assert_eq!(graphml.matches("<edge id=").count(), edge_count);
+        assert_eq!(
+            graphml
+                .matches(r#"<data key="e_type">parent</data>"#)
+                .count(),
+            parent_edges
+        );
+
+        let mut gexf = Vec::new();
+        sim.write_graph(&mut gexf, GraphFormat::Gexf).unwrap();
+        let gexf = String::from_utf8(gexf).unwrap();
+        assert_eq!(gexf.matches("<viz:position").count(), sim.hyphae.len());
+        assert_eq!(gexf.matches("<edge id=").count(), edge_count);
+
+        assert_eq!(
+            GraphFormat::from_path("colony.GEXF"),
+            Some(GraphFormat::Gexf)
+        );
+        assert!(sim.save_graph("colony.txt").is_err());
+    }
+}
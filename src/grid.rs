@@ -0,0 +1,271 @@
+// Grid2D - a 2D field stored in one contiguous buffer
+// Cells are laid out x-major (all of column x = 0, then x = 1, ...), so `grid[x]` is a slice
+// over y and `grid[x][y]` works like the nested vectors it replaces, without the pointer chasing.
+// Serialized as nested arrays ([x][y]) so snapshots and the API keep their JSON layout.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::ops::{Index, IndexMut};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Grid2D<T> {
+    width: usize,  // Extent in x (number of row slices)
+    height: usize, // Extent in y (length of each row slice)
+    data: Vec<T>,
+}
+
+impl<T: Clone> Grid2D<T> {
+    /// A `width` x `height` grid with every cell set to `value`
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self {
+            width,
+            height,
+            data: vec![value; width * height],
+        }
+    }
+
+    pub fn fill(&mut self, value: T) {
+        self.data.fill(value);
+    }
+}
+
+impl<T> Grid2D<T> {
+    /// Build a grid from a function of (x, y)
+    pub fn from_fn(width: usize, height: usize, mut cell: impl FnMut(usize, usize) -> T) -> Self {
+        let mut data = Vec::with_capacity(width * height);
+        for x in 0..width {
+            for y in 0..height {
+                data.push(cell(x, y));
+            }
+        }
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    /// Build a grid from nested `[x][y]` vectors; every row must have the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self, String> {
+        let width = rows.len();
+        let height = rows.first().map_or(0, Vec::len);
+        let mut data = Vec::with_capacity(width * height);
+        for (x, row) in rows.into_iter().enumerate() {
+            if row.len() != height {
+                return Err(format!(
+                    "grid row {} has {} cells, expected {}",
+                    x,
+                    row.len(),
+                    height
+                ));
+            }
+            data.extend(row);
+        }
+        Ok(Self {
+            width,
+            height,
+            data,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Number of cells
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn in_bounds(&self, x: isize, y: isize) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if x < self.width && y < self.height {
+            self.data.get(x * self.height + y)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if x < self.width && y < self.height {
+            self.data.get_mut(x * self.height + y)
+        } else {
+            None
+        }
+    }
+
+    /// All cells with the given x, indexed by y
+    pub fn row(&self, x: usize) -> &[T] {
+        &self.data[x * self.height..(x + 1) * self.height]
+    }
+
+    pub fn row_mut(&mut self, x: usize) -> &mut [T] {
+        &mut self.data[x * self.height..(x + 1) * self.height]
+    }
+
+    /// Row slices in x order
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.width).map(move |x| self.row(x))
+    }
+
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [T]> {
+        self.data.chunks_mut(self.height.max(1)).take(self.width)
+    }
+
+    /// Every cell in storage order (x-major)
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.data.iter_mut()
+    }
+
+    /// The whole buffer in storage order, e.g. for a C-order `[x, y]` array
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    /// Cells within `radius` of (x, y) in both x and y, including (x, y), clipped to the grid.
+    /// The center itself may lie outside the grid.
+    pub fn neighborhood(
+        &self,
+        x: usize,
+        y: usize,
+        radius: usize,
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let x_range =
+            x.saturating_sub(radius)..x.saturating_add(radius).saturating_add(1).min(self.width);
+        let y_range =
+            y.saturating_sub(radius)..y.saturating_add(radius).saturating_add(1).min(self.height);
+        x_range.flat_map(move |nx| y_range.clone().map(move |ny| (nx, ny)))
+    }
+
+    /// The (up to) 8 cells touching (x, y)
+    pub fn neighbors(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        self.neighborhood(x, y, 1)
+            .filter(move |&(nx, ny)| nx != x || ny != y)
+    }
+
+    /// Apply `f` to every cell, keeping the shape
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid2D<U> {
+        Grid2D {
+            width: self.width,
+            height: self.height,
+            data: self.data.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T> Index<usize> for Grid2D<T> {
+    type Output = [T];
+
+    fn index(&self, x: usize) -> &[T] {
+        self.row(x)
+    }
+}
+
+impl<T> IndexMut<usize> for Grid2D<T> {
+    fn index_mut(&mut self, x: usize) -> &mut [T] {
+        self.row_mut(x)
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid2D<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        assert!(
+            x < self.width && y < self.height,
+            "cell ({}, {}) outside {}x{} grid",
+            x,
+            y,
+            self.width,
+            self.height
+        );
+        &self.data[x * self.height + y]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid2D<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        assert!(
+            x < self.width && y < self.height,
+            "cell ({}, {}) outside {}x{} grid",
+            x,
+            y,
+            self.width,
+            self.height
+        );
+        &mut self.data[x * self.height + y]
+    }
+}
+
+impl<T: Serialize> Serialize for Grid2D<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.rows())
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Grid2D<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let rows = Vec::<Vec<T>>::deserialize(deserializer)?;
+        Self::from_rows(rows).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that Grid2D indexing, rows and neighborhoods follow the column-major layout
+    #[test]
+    fn test_grid2d() {
+        let mut grid = Grid2D::from_fn(3, 2, |x, y| x * 10 + y);
+        assert_eq!((grid.width(), grid.height(), grid.len()), (3, 2, 6));
+        assert_eq!(grid[2][1], 21);
+        assert_eq!(grid[(1, 0)], 10);
+        assert_eq!(grid.row(1), &[10, 11]);
+        assert_eq!(grid.as_slice(), &[0, 1, 10, 11, 20, 21]);
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.get(0, 2), None);
+        grid[0][1] = 5;
+        *grid.get_mut(2, 0).unwrap() = 7;
+        assert_eq!(
+            grid.rows().map(<[usize]>::to_vec).collect::<Vec<_>>(),
+            [[0, 5], [10, 11], [7, 21]]
+        );
+
+        // Neighborhoods are clipped to the grid
+        assert_eq!(
+            grid.neighbors(0, 0).collect::<Vec<_>>(),
+            [(0, 1), (1, 0), (1, 1)]
+        );
+        assert_eq!(grid.neighbors(1, 1).count(), 5);
+        assert_eq!(grid.neighborhood(1, 0, 5).count(), 6);
+        assert_eq!(grid.neighborhood(4, 1, 1).collect::<Vec<_>>(), []);
+        assert_eq!(grid.neighborhood(1, 0, usize::MAX).count(), 6);
+        assert!(grid.in_bounds(2, 1) && !grid.in_bounds(-1, 0) && !grid.in_bounds(0, 2));
+
+        // JSON keeps the nested [x][y] layout
+        let json = serde_json::to_string(&grid).unwrap();
+        assert_eq!(json, "[[0,5],[10,11],[7,21]]");
+        let parsed: Grid2D<usize> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, grid);
+        assert!(serde_json::from_str::<Grid2D<usize>>("[[1,2],[3]]").is_err());
+    }
+}
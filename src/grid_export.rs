@@ -0,0 +1,385 @@
+// Raster grid export - write simulation grids as NumPy .npy arrays, or several at once as an
+// uncompressed .npz archive, so analysis pipelines can skip the nested JSON arrays of /state.
+// Arrays keep the simulation's [x][y] indexing: element [x, y] is grid cell (x, y).
+
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::grid::Grid2D;
+use crate::simulation::Simulation;
+use crate::types::ZoneType;
+
+/// Exportable grids
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GridLayer {
+    Sugar,
+    Nitrogen,
+    NutrientMemory,
+    SoilMoisture,
+    LightExposure,
+    DensityMap, // grid_size * density_map_resolution cells per side
+    FlowVelocityX,
+    FlowVelocityY,
+    ZoneType, // 0 = none, 1 = toxic, 2 = competitor, 3 = deadwood
+    ZoneIntensity,
+    Obstacles,
+}
+
+impl GridLayer {
+    pub const ALL: [GridLayer; 11] = [
+        GridLayer::Sugar,
+        GridLayer::Nitrogen,
+        GridLayer::NutrientMemory,
+        GridLayer::SoilMoisture,
+        GridLayer::LightExposure,
+        GridLayer::DensityMap,
+        GridLayer::FlowVelocityX,
+        GridLayer::FlowVelocityY,
+        GridLayer::ZoneType,
+        GridLayer::ZoneIntensity,
+        GridLayer::Obstacles,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GridLayer::Sugar => "sugar",
+            GridLayer::Nitrogen => "nitrogen",
+            GridLayer::NutrientMemory => "nutrient_memory",
+            GridLayer::SoilMoisture => "soil_moisture",
+            GridLayer::LightExposure => "light_exposure",
+            GridLayer::DensityMap => "density_map",
+            GridLayer::FlowVelocityX => "flow_velocity_x",
+            GridLayer::FlowVelocityY => "flow_velocity_y",
+            GridLayer::ZoneType => "zone_type",
+            GridLayer::ZoneIntensity => "zone_intensity",
+            GridLayer::Obstacles => "obstacles",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|grid| grid.name() == name)
+    }
+
+    /// Parse a comma-separated list of grid names; an empty list means every grid
+    pub fn parse_list(names: &str) -> Result<Vec<Self>, String> {
+        let grids = names
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                Self::parse(name).ok_or_else(|| {
+                    format!(
+                        "Unknown grid '{}' (known grids: {})",
+                        name,
+                        Self::ALL.map(|grid| grid.name()).join(", ")
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(if grids.is_empty() {
+            Self::ALL.to_vec()
+        } else {
+            grids
+        })
+    }
+}
+
+// Element type and raw little-endian data of one array
+enum NpyData {
+    F32(Vec<f32>),
+    U8(Vec<u8>),
+    Bool(Vec<bool>),
+}
+
+impl NpyData {
+    fn descr(&self) -> &'static str {
+        match self {
+            NpyData::F32(_) => "<f4",
+            NpyData::U8(_) => "|u1",
+            NpyData::Bool(_) => "|b1",
+        }
+    }
+
+    fn write_to(&self, out: &mut Vec<u8>) {
+        match self {
+            NpyData::F32(values) => {
+                for value in values {
+                    out.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+            NpyData::U8(values) => out.extend_from_slice(values),
+            NpyData::Bool(values) => out.extend(values.iter().map(|&v| v as u8)),
+        }
+    }
+}
+
+// Grids are stored x-major, which is already row-major order for an [x, y] array
+fn flatten<T, U>(grid: &Grid2D<T>, map: impl Fn(&T) -> U) -> (Vec<U>, usize, usize) {
+    (grid.iter().map(map).collect(), grid.width(), grid.height())
+}
+
+impl Simulation {
+    fn grid_data(&self, grid: GridLayer) -> (NpyData, usize, usize) {
+        let f32_grid = |values: &Grid2D<f32>| {
+            (
+                NpyData::F32(values.as_slice().to_vec()),
+                values.width(),
+                values.height(),
+            )
+        };
+        match grid {
+            GridLayer::Sugar => f32_grid(&self.nutrients.sugar),
+            GridLayer::Nitrogen => f32_grid(&self.nutrients.nitrogen),
+            GridLayer::NutrientMemory => f32_grid(&self.nutrient_memory),
+            GridLayer::SoilMoisture => f32_grid(&self.soil_moisture),
+            GridLayer::LightExposure => f32_grid(&self.light_exposure),
+            GridLayer::DensityMap => f32_grid(&self.density_map),
+            GridLayer::FlowVelocityX => f32_grid(&self.flow_velocity_x),
+            GridLayer::FlowVelocityY => f32_grid(&self.flow_velocity_y),
+            GridLayer::ZoneType => {
+                let (values, rows, cols) = flatten(&self.zones, |z| zone_code(z.zone_type));
+                (NpyData::U8(values), rows, cols)
+            }
+            GridLayer::ZoneIntensity => {
+                let (values, rows, cols) = flatten(&self.zones, |z| z.intensity);
+                (NpyData::F32(values), rows, cols)
+            }
+            GridLayer::Obstacles => {
+                let (values, rows, cols) = flatten(&self.obstacles, |&v| v);
+                (NpyData::Bool(values), rows, cols)
+            }
+        }
+    }
+
+    /// Write one grid as a NumPy `.npy` array (format version 1.0).
+    pub fn write_npy<W: Write>(
+        &self,
+        mut writer: W,
+        grid: GridLayer,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        writer.write_all(&self.npy_bytes(grid))?;
+        Ok(())
+    }
+
+    /// Write several grids as a NumPy `.npz` archive (one `<name>.npy` entry per grid).
+    pub fn write_npz<W: Write>(
+        &self,
+        mut writer: W,
+        grids: &[GridLayer],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut archive = ZipWriter::default();
+        for &grid in grids {
+            archive.add(&format!("{}.npy", grid.name()), &self.npy_bytes(grid));
+        }
+        writer.write_all(&archive.finish())?;
+        Ok(())
+    }
+
+    /// Save grids to a file: `.npy` takes exactly one grid, `.npz` any number.
+    pub fn save_grids<P: AsRef<Path>>(
+        &self,
+        path: P,
+        grids: &[GridLayer],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase());
+        let mut writer = match extension.as_deref() {
+            Some("npy") if grids.len() != 1 => {
+                return Err(format!(
+                    "{} holds a single grid; pick one or use a .npz file for {} grids",
+                    path.display(),
+                    grids.len()
+                )
+                .into())
+            }
+            Some("npy") | Some("npz") => BufWriter::new(std::fs::File::create(path)?),
+            _ => {
+                return Err(format!(
+                    "Unknown grid format for {} (use a .npy or .npz extension)",
+                    path.display()
+                )
+                .into())
+            }
+        };
+        if extension.as_deref() == Some("npy") {
+            self.write_npy(&mut writer, grids[0])?;
+        } else {
+            self.write_npz(&mut writer, grids)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn npy_bytes(&self, grid: GridLayer) -> Vec<u8> {
+        let (data, rows, cols) = self.grid_data(grid);
+        let mut header = format!(
+            "{{'descr': '{}', 'fortran_order': False, 'shape': ({}, {}), }}",
+            data.descr(),
+            rows,
+            cols
+        );
+        // Magic (6) + version (2) + header length (2) + header, padded with spaces to a
+        // multiple of 64 bytes and terminated by a newline
+        let unpadded = 10 + header.len() + 1;
+        header.push_str(&" ".repeat(unpadded.next_multiple_of(64) - unpadded));
+        header.push('\n');
+
+        let mut out = Vec::with_capacity(10 + header.len() + rows * cols * 4);
+        out.extend_from_slice(b"\x93NUMPY\x01\x00");
+        out.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        out.extend_from_slice(header.as_bytes());
+        data.write_to(&mut out);
+        out
+    }
+}
+
+fn zone_code(zone_type: ZoneType) -> u8 {
+    match zone_type {
+        ZoneType::None => 0,
+        ZoneType::Toxic => 1,
+        ZoneType::Competitor => 2,
+        ZoneType::Deadwood => 3,
+    }
+}
+
+// Minimal ZIP writer for .npz: stored (uncompressed) entries, no ZIP64
+#[derive(Default)]
+struct ZipWriter {
+    data: Vec<u8>,
+    central_directory: Vec<u8>,
+    entries: u16,
+}
+
+impl ZipWriter {
+    // MS-DOS date of 1980-01-01, the earliest ZIP timestamp
+    const DOS_DATE: u16 = (1 << 5) | 1;
+
+    fn add(&mut self, name: &str, contents: &[u8]) {
+        let crc = crc32(contents);
+        let offset = self.data.len() as u32;
+        let size = contents.len() as u32;
+
+        let d = &mut self.data;
+        d.extend_from_slice(&0x0403_4b50u32.to_le_bytes()); // Local file header
+        d.extend_from_slice(&20u16.to_le_bytes()); // Version needed (2.0)
+        d.extend_from_slice(&0u16.to_le_bytes()); // Flags
+        d.extend_from_slice(&0u16.to_le_bytes()); // Stored
+        d.extend_from_slice(&0u16.to_le_bytes()); // Time
+        d.extend_from_slice(&Self::DOS_DATE.to_le_bytes());
+        d.extend_from_slice(&crc.to_le_bytes());
+        d.extend_from_slice(&size.to_le_bytes()); // Compressed size
+        d.extend_from_slice(&size.to_le_bytes()); // Uncompressed size
+        d.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        d.extend_from_slice(&0u16.to_le_bytes()); // Extra field length
+        d.extend_from_slice(name.as_bytes());
+        d.extend_from_slice(contents);
+
+        let c = &mut self.central_directory;
+        c.extend_from_slice(&0x0201_4b50u32.to_le_bytes()); // Central directory header
+        c.extend_from_slice(&20u16.to_le_bytes()); // Version made by
+        c.extend_from_slice(&20u16.to_le_bytes()); // Version needed
+        c.extend_from_slice(&0u16.to_le_bytes()); // Flags
+        c.extend_from_slice(&0u16.to_le_bytes()); // Stored
+        c.extend_from_slice(&0u16.to_le_bytes()); // Time
+        c.extend_from_slice(&Self::DOS_DATE.to_le_bytes());
+        c.extend_from_slice(&crc.to_le_bytes());
+        c.extend_from_slice(&size.to_le_bytes());
+        c.extend_from_slice(&size.to_le_bytes());
+        c.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        c.extend_from_slice(&[0; 12]); // Extra, comment, disk, internal and external attributes
+        c.extend_from_slice(&offset.to_le_bytes());
+        c.extend_from_slice(name.as_bytes());
+        self.entries += 1;
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let directory_offset = self.data.len() as u32;
+        let directory_size = self.central_directory.len() as u32;
+        self.data.append(&mut self.central_directory);
+        let d = &mut self.data;
+        d.extend_from_slice(&0x0605_4b50u32.to_le_bytes()); // End of central directory
+        d.extend_from_slice(&[0; 4]); // Disk numbers
+        d.extend_from_slice(&self.entries.to_le_bytes());
+        d.extend_from_slice(&self.entries.to_le_bytes());
+        d.extend_from_slice(&directory_size.to_le_bytes());
+        d.extend_from_slice(&directory_offset.to_le_bytes());
+        d.extend_from_slice(&0u16.to_le_bytes()); // Comment length
+        self.data
+    }
+}
+
+// CRC-32 (IEEE) as used by ZIP
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut table = [0u32; 256];
+    for (i, entry) in table.iter_mut().enumerate() {
+        let mut c = i as u32;
+        for _ in 0..8 {
+            c = if c & 1 != 0 {
+                0xEDB8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+        }
+        *entry = c;
+    }
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in bytes {
+        crc = table[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    crc ^ 0xFFFF_FFFF
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimulationConfig;
+    use crate::simulation::create_rng;
+
+    /// Test that .npy and .npz exports have valid headers, layout and entries
+    #[test]
+    fn test_grid_export() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+
+        let config = SimulationConfig {
+            grid_size: 30,
+            ..SimulationConfig::default()
+        };
+        let mut rng = create_rng(8);
+        let sim = Simulation::with_config(&mut rng, config);
+
+        let mut npy = Vec::new();
+        sim.write_npy(&mut npy, GridLayer::Sugar).unwrap();
+        assert!(npy.starts_with(b"\x93NUMPY\x01\x00"));
+        let header_len = u16::from_le_bytes([npy[8], npy[9]]) as usize;
+        assert_eq!((10 + header_len) % 64, 0);
+        let header = std::str::from_utf8(&npy[10..10 + header_len]).unwrap();
+        assert!(header.contains("'descr': '<f4'"));
+        assert!(header.contains("'shape': (30, 30)"));
+        assert_eq!(npy.len(), 10 + header_len + 30 * 30 * 4);
+        // Row-major [x, y]: element 1 is sugar[0][1]
+        let offset = 10 + header_len + 4;
+        let value = f32::from_le_bytes(npy[offset..offset + 4].try_into().unwrap());
+        assert_eq!(value, sim.nutrients.sugar[0][1]);
+
+        let mut npz = Vec::new();
+        sim.write_npz(&mut npz, &GridLayer::ALL).unwrap();
+        let end = npz.len() - 22;
+        assert_eq!(&npz[end..end + 4], &0x0605_4b50u32.to_le_bytes());
+        let entries = u16::from_le_bytes([npz[end + 10], npz[end + 11]]);
+        assert_eq!(entries as usize, GridLayer::ALL.len());
+
+        assert_eq!(
+            GridLayer::parse_list("sugar, obstacles").unwrap(),
+            vec![GridLayer::Sugar, GridLayer::Obstacles]
+        );
+        assert_eq!(
+            GridLayer::parse_list("").unwrap().len(),
+            GridLayer::ALL.len()
+        );
+        assert!(GridLayer::parse_list("sugar,clouds").is_err());
+        assert!(sim.save_grids("grids.npy", &GridLayer::ALL).is_err());
+    }
+}
@@ -0,0 +1,384 @@
+// Time-series recorder - samples run metrics every N frames into a ring buffer
+// so clients can fetch a complete history instead of polling /stats and missing samples
+
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::config::SimulationConfig;
+use crate::grid::Grid2D;
+use crate::simulation::Simulation;
+use crate::weather::Season;
+
+/// Metrics the recorder can sample
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistoryMetric {
+    HyphaeCount,
+    ConnectionCount,
+    SporeCount,
+    FruitBodyCount,
+    TotalEnergy,
+    TotalSugar,
+    TotalNitrogen,
+    MeanMoisture,
+    Temperature,
+    Humidity,
+    Rain,
+    Season, // 0 = spring, 1 = summer, 2 = autumn, 3 = winter
+    // Event counts since the previous sample
+    FruitSpawned,
+    FruitDied,
+    SporesReleased,
+    SporesGerminated,
+}
+
+impl HistoryMetric {
+    pub const ALL: [HistoryMetric; 16] = [
+        HistoryMetric::HyphaeCount,
+        HistoryMetric::ConnectionCount,
+        HistoryMetric::SporeCount,
+        HistoryMetric::FruitBodyCount,
+        HistoryMetric::TotalEnergy,
+        HistoryMetric::TotalSugar,
+        HistoryMetric::TotalNitrogen,
+        HistoryMetric::MeanMoisture,
+        HistoryMetric::Temperature,
+        HistoryMetric::Humidity,
+        HistoryMetric::Rain,
+        HistoryMetric::Season,
+        HistoryMetric::FruitSpawned,
+        HistoryMetric::FruitDied,
+        HistoryMetric::SporesReleased,
+        HistoryMetric::SporesGerminated,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            HistoryMetric::HyphaeCount => "hyphae_count",
+            HistoryMetric::ConnectionCount => "connection_count",
+            HistoryMetric::SporeCount => "spore_count",
+            HistoryMetric::FruitBodyCount => "fruit_body_count",
+            HistoryMetric::TotalEnergy => "total_energy",
+            HistoryMetric::TotalSugar => "total_sugar",
+            HistoryMetric::TotalNitrogen => "total_nitrogen",
+            HistoryMetric::MeanMoisture => "mean_moisture",
+            HistoryMetric::Temperature => "temperature",
+            HistoryMetric::Humidity => "humidity",
+            HistoryMetric::Rain => "rain",
+            HistoryMetric::Season => "season",
+            HistoryMetric::FruitSpawned => "fruit_spawned",
+            HistoryMetric::FruitDied => "fruit_died",
+            HistoryMetric::SporesReleased => "spores_released",
+            HistoryMetric::SporesGerminated => "spores_germinated",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|metric| metric.name() == name)
+    }
+}
+
+/// One row of the history: the frame it was taken at and one value per recorded metric
+#[derive(Clone, Debug, Serialize)]
+pub struct HistorySample {
+    pub frame_index: u64,
+    pub values: Vec<f64>,
+}
+
+// Step events accumulated between samples
+#[derive(Clone, Debug, Default)]
+struct EventCounts {
+    fruit_spawned: usize,
+    fruit_died: usize,
+    spores_released: usize,
+    spores_germinated: usize,
+}
+
+/// Ring buffer of metric samples, configured by the `history_*` config fields
+#[derive(Clone, Debug)]
+pub struct HistoryRecorder {
+    metrics: Vec<HistoryMetric>,
+    interval: u32,
+    capacity: usize,
+    samples: VecDeque<HistorySample>,
+    pending: EventCounts,
+}
+
+impl HistoryRecorder {
+    pub fn from_config(config: &SimulationConfig) -> Self {
+        Self {
+            metrics: Self::metrics_from_config(config),
+            interval: config.history_interval,
+            capacity: config.history_capacity,
+            samples: VecDeque::new(),
+            pending: EventCounts::default(),
+        }
+    }
+
+    // Unknown names are rejected by config validation, so they are simply skipped here
+    fn metrics_from_config(config: &SimulationConfig) -> Vec<HistoryMetric> {
+        config
+            .history_metrics
+            .iter()
+            .filter_map(|name| HistoryMetric::parse(name))
+            .collect()
+    }
+
+    /// Apply changed `history_*` settings. Changing the metric list starts a new history
+    /// (old rows would not match the new columns); a smaller capacity drops the oldest samples.
+    pub fn configure(&mut self, config: &SimulationConfig) {
+        let metrics = Self::metrics_from_config(config);
+        if metrics != self.metrics {
+            self.metrics = metrics;
+            self.samples.clear();
+        }
+        self.interval = config.history_interval;
+        self.capacity = config.history_capacity;
+        while self.samples.len() > self.capacity {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn metrics(&self) -> &[HistoryMetric] {
+        &self.metrics
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Samples taken after `since` (a frame index), oldest first
+    pub fn samples_since(&self, since: Option<u64>) -> impl Iterator<Item = &HistorySample> {
+        self.samples
+            .iter()
+            .filter(move |sample| since.is_none_or(|frame| sample.frame_index > frame))
+    }
+
+    /// Write samples as CSV with a `frame_index` column followed by one column per metric.
+    pub fn write_csv<W: Write>(
+        &self,
+        mut writer: W,
+        since: Option<u64>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        write!(writer, "frame_index")?;
+        for metric in &self.metrics {
+            write!(writer, ",{}", csv_field(metric.name()))?;
+        }
+        writeln!(writer)?;
+        for sample in self.samples_since(since) {
+            write!(writer, "{}", sample.frame_index)?;
+            for value in &sample.values {
+                write!(writer, ",{}", value)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Write samples as JSON Lines: one object per sample keyed by metric name.
+    pub fn write_jsonl<W: Write>(
+        &self,
+        mut writer: W,
+        since: Option<u64>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // Written by hand to keep the configured column order
+        for sample in self.samples_since(since) {
+            write!(writer, "{{\"frame_index\":{}", sample.frame_index)?;
+            for (metric, value) in self.metrics.iter().zip(&sample.values) {
+                write!(
+                    writer,
+                    ",\"{}\":{}",
+                    metric.name(),
+                    serde_json::Value::from(*value)
+                )?;
+            }
+            writeln!(writer, "}}")?;
+        }
+        Ok(())
+    }
+
+    /// Save the whole history to a file; `.csv` writes CSV, anything else JSON Lines.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let mut writer = BufWriter::new(std::fs::File::create(path)?);
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => self.write_csv(&mut writer, None)?,
+            _ => self.write_jsonl(&mut writer, None)?,
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl Simulation {
+    /// Count this step's events and take a history sample when the interval is reached.
+    /// Called at the end of every `step`.
+    pub(crate) fn record_history(&mut self) {
+        let pending = &mut self.history.pending;
+        pending.fruit_spawned += self.events.fruit_spawned.len();
+        pending.fruit_died += self.events.fruit_died.len();
+        pending.spores_released += self.events.spores_released.len();
+        pending.spores_germinated += self.events.spores_germinated.len();
+
+        let interval = self.history.interval;
+        if interval == 0 || self.history.capacity == 0 {
+            return;
+        }
+        if !self.state.frame_index.is_multiple_of(u64::from(interval)) {
+            return;
+        }
+
+        let values = self
+            .history
+            .metrics
+            .iter()
+            .map(|&metric| self.metric_value(metric))
+            .collect();
+        let history = &mut self.history;
+        history.pending = EventCounts::default();
+        if history.samples.len() >= history.capacity {
+            history.samples.pop_front();
+        }
+        history.samples.push_back(HistorySample {
+            frame_index: self.state.frame_index,
+            values,
+        });
+    }
+
+    fn metric_value(&self, metric: HistoryMetric) -> f64 {
+        let grid_sum = |grid: &Grid2D<f32>| -> f64 { grid.iter().map(|&v| v as f64).sum() };
+        let pending = &self.history.pending;
+        match metric {
+            HistoryMetric::HyphaeCount => self.hyphae.iter().filter(|h| h.alive).count() as f64,
+            HistoryMetric::ConnectionCount => self.connections.len() as f64,
+            HistoryMetric::SporeCount => self.spores.iter().filter(|s| s.alive).count() as f64,
+            HistoryMetric::FruitBodyCount => self.fruit_bodies.len() as f64,
+            HistoryMetric::TotalEnergy => self
+                .hyphae
+                .iter()
+                .filter(|h| h.alive)
+                .map(|h| h.energy as f64)
+                .sum(),
+            HistoryMetric::TotalSugar => grid_sum(&self.nutrients.sugar),
+            HistoryMetric::TotalNitrogen => grid_sum(&self.nutrients.nitrogen),
+            HistoryMetric::MeanMoisture => {
+                let cells = (self.config.grid_size * self.config.grid_size).max(1);
+                grid_sum(&self.soil_moisture) / cells as f64
+            }
+            HistoryMetric::Temperature => self.weather.temperature as f64,
+            HistoryMetric::Humidity => self.weather.humidity as f64,
+            HistoryMetric::Rain => self.weather.rain as f64,
+            HistoryMetric::Season => match self.weather.get_season() {
+                Season::Spring => 0.0,
+                Season::Summer => 1.0,
+                Season::Autumn => 2.0,
+                Season::Winter => 3.0,
+            },
+            HistoryMetric::FruitSpawned => pending.fruit_spawned as f64,
+            HistoryMetric::FruitDied => pending.fruit_died as f64,
+            HistoryMetric::SporesReleased => pending.spores_released as f64,
+            HistoryMetric::SporesGerminated => pending.spores_germinated as f64,
+        }
+    }
+}
+
+/// Quote a CSV field per RFC 4180 when it contains a comma, quote or line break
+pub(crate) fn csv_field(text: &str) -> Cow<'_, str> {
+    if text.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", text.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::create_rng;
+
+    // 30 steps sampled every 5 frames into a buffer of 4
+    fn sampled_simulation() -> Simulation {
+        let config = SimulationConfig {
+            grid_size: 40,
+            history_interval: 5,
+            history_capacity: 4,
+            history_metrics: vec!["hyphae_count".to_string(), "season".to_string()],
+            ..SimulationConfig::default()
+        };
+        let mut rng = create_rng(9);
+        let mut sim = Simulation::with_config(&mut rng, config);
+        for _ in 0..30 {
+            sim.step(&mut rng);
+        }
+        sim
+    }
+
+    /// Test that samples are taken every interval and only the newest ones are kept
+    #[test]
+    fn test_history_sampling() {
+        let sim = sampled_simulation();
+        // 6 samples taken, only the newest 4 kept
+        assert_eq!(sim.history.len(), 4);
+        let frames: Vec<u64> = sim
+            .history
+            .samples_since(None)
+            .map(|s| s.frame_index)
+            .collect();
+        assert_eq!(frames, vec![15, 20, 25, 30]);
+        assert_eq!(sim.history.samples_since(Some(20)).count(), 2);
+    }
+
+    /// Test that CSV and JSON Lines exports have one row per sample with the chosen metrics
+    #[test]
+    fn test_history_export() {
+        let sim = sampled_simulation();
+        let mut csv = Vec::new();
+        sim.history.write_csv(&mut csv, Some(25)).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "frame_index,hyphae_count,season");
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("30,"));
+
+        let mut jsonl = Vec::new();
+        sim.history.write_jsonl(&mut jsonl, None).unwrap();
+        let rows: Vec<serde_json::Value> = String::from_utf8(jsonl)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0]["frame_index"], 15);
+        assert!(rows[0]["hyphae_count"].is_number());
+    }
+
+    /// Test that changing the metric list starts over with the new columns
+    #[test]
+    fn test_history_metrics_change() {
+        let mut sim = sampled_simulation();
+        let mut config = sim.config.clone();
+        config.history_metrics = vec!["rain".to_string()];
+        sim.update_config(config);
+        assert!(sim.history.is_empty());
+        assert_eq!(sim.history.metrics(), &[HistoryMetric::Rain]);
+    }
+
+    /// Test that CSV fields are quoted only when they contain separators, quotes or line breaks
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+}
@@ -1,5 +1,11 @@
-#[derive(Clone)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Hypha {
+    // Persistent unique id, assigned by `SimulationState::add_hypha` and never reused
+    // (snapshots from before ids existed number hyphae by slot, see `snapshot.rs`)
+    #[serde(default)]
+    pub id: u64,
     pub x: f32,
     pub y: f32,
     pub prev_x: f32,
@@ -7,7 +13,7 @@ pub struct Hypha {
     pub angle: f32,
     pub alive: bool,
     pub energy: f32,
-    pub parent: Option<usize>,
+    pub parent: Option<u64>, // Id of the hypha this one branched from
     pub age: f32,
     // Network intelligence: adaptive growth
     pub strength: f32,        // Branch strength (affects growth rate)
@@ -0,0 +1,45 @@
+//! MycoRust — mycelium growth simulation.
+//!
+//! The library exposes the simulation core (state, config, hyphae, weather),
+//! snapshots, and the HTTP API router so other tools can drive or embed a run.
+//! The macroquad-based modules (`camera`, `controls`, `visualization`) are only
+//! available with the `ui` feature.
+
+pub mod analysis;
+pub mod api;
+pub mod batch;
+pub mod config;
+pub mod env_maps;
+pub mod graph_export;
+pub mod grid;
+pub mod grid_export;
+pub mod history;
+pub mod hypha;
+pub mod nutrients;
+pub mod profile;
+pub mod render;
+pub mod simulation;
+pub mod snapshot;
+pub mod spore;
+pub mod stream;
+pub mod svg_export;
+pub mod sweep;
+pub mod timelapse;
+pub mod types;
+pub mod weather;
+
+#[cfg(feature = "ui")]
+pub mod camera;
+#[cfg(feature = "ui")]
+pub mod controls;
+#[cfg(feature = "ui")]
+pub mod visualization;
+
+pub use api::{create_router, run_server, ApiState};
+pub use config::{load_config, ConfigSources, SimulationConfig};
+pub use grid::Grid2D;
+pub use hypha::Hypha;
+pub use nutrients::NutrientGrid;
+pub use simulation::{create_rng, SimRng, Simulation, SimulationState};
+pub use types::Connection;
+pub use weather::Weather;
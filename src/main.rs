@@ -1,26 +1,13 @@
-use ::rand as external_rand;
-use clap::Parser;
-use external_rand::thread_rng;
+use clap::{Parser, Subcommand};
+use std::io::Write;
+use std::path::PathBuf;
 
-mod config;
-mod hypha;
-mod nutrients;
-mod simulation;
-mod spore;
-mod types;
-mod weather;
-
-use config::*;
-use simulation::Simulation;
-
-#[cfg(feature = "ui")]
-mod camera;
-#[cfg(feature = "ui")]
-mod controls;
-#[cfg(feature = "ui")]
-mod visualization;
-
-mod api;
+use mycorust::batch::{run_batch, BatchOptions};
+use mycorust::config::{ConfigSources, FieldEffect, SimulationConfig, PRESETS};
+use mycorust::grid_export::GridLayer;
+use mycorust::render::RenderOptions;
+use mycorust::simulation::{create_rng, SimRng, Simulation};
+use mycorust::sweep::{run_sweep, write_summary_csv, SweepSpec};
 
 #[cfg(feature = "ui")]
 use macroquad::prelude::*;
@@ -36,9 +23,89 @@ struct Args {
     #[arg(long, default_value_t = 8080)]
     port: u16,
 
-    /// Configuration file path (YAML or JSON). If not specified, searches for config.yaml, config.yml, or config.json in current directory.
+    /// Configuration file path (YAML or JSON). Repeat to layer several files, later ones winning. If not specified, searches for config.yaml, config.yml, or config.json in current directory.
+    #[arg(short, long, global = true)]
+    config: Vec<String>,
+
+    /// Built-in preset applied on top of the base config (see --list-presets)
+    #[arg(long, global = true)]
+    preset: Option<String>,
+
+    /// Override a single config field, applied after all files (repeatable), e.g. --set branch_prob=0.02
+    #[arg(long = "set", value_name = "KEY=VALUE", global = true)]
+    overrides: Vec<String>,
+
+    /// List the built-in presets and exit
+    #[arg(long)]
+    list_presets: bool,
+
+    /// RNG seed for the run (overrides `seed` from the config file). The same seed and config reproduce the same run.
+    #[arg(long, global = true)]
+    seed: Option<u64>,
+
+    /// Resume from a snapshot file saved earlier. --preset, --config and --set layer onto its embedded config; fields that size the grids can't change
+    #[arg(long, global = true)]
+    load_snapshot: Option<String>,
+
+    /// Record a time-lapse into this directory: a numbered PNG every `timelapse_interval` steps (plus timelapse.gif with `timelapse_gif`)
+    #[arg(long, value_name = "DIR", global = true)]
+    timelapse: Option<PathBuf>,
+
+    /// Export the hyphal network of the initial (or --load-snapshot) state to a .graphml or .gexf file and exit
+    #[arg(long, value_name = "PATH")]
+    export_graph: Option<String>,
+
+    /// Export the initial (or --load-snapshot) state as an SVG figure and exit
+    #[arg(long, value_name = "PATH")]
+    export_svg: Option<String>,
+
+    /// Export raster grids of the initial (or --load-snapshot) state as a NumPy .npy (one grid) or .npz (several) file and exit
+    #[arg(long, value_name = "PATH")]
+    export_grids: Option<String>,
+
+    /// Comma-separated grids for --export-grids (default: all), e.g. sugar,nitrogen,obstacles
+    #[arg(long, value_name = "LIST", requires = "export_grids")]
+    grids: Option<String>,
+
+    /// Print the merged config (file values plus defaults and CLI overrides) as YAML and exit
+    #[arg(long)]
+    print_effective_config: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Batch run: step as fast as possible (no server, no frame pacing) and write the results to a directory
+    Run(RunArgs),
+    /// Parameter sweep: run every combination in a sweep spec file in parallel and write a summary table
+    Sweep(SweepArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct RunArgs {
+    /// Number of simulation steps to run
+    #[arg(long)]
+    steps: u64,
+
+    /// Output directory for the final snapshot, metric history, network export and summary
     #[arg(short, long)]
-    config: Option<String>,
+    out: PathBuf,
+}
+
+#[derive(clap::Args, Debug)]
+struct SweepArgs {
+    /// Sweep specification file (YAML or JSON)
+    spec: PathBuf,
+
+    /// Output directory for the summary table (and per-run results with `save_runs`)
+    #[arg(short, long)]
+    out: PathBuf,
+
+    /// Number of runs executed in parallel (default: one per CPU core)
+    #[arg(short, long)]
+    jobs: Option<usize>,
 }
 
 #[cfg(not(feature = "ui"))]
@@ -46,8 +113,15 @@ struct Args {
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Headless mode only
     let args = Args::parse();
-    let config = load_config(args.config.as_deref())?;
-    headless_main(args.port, config).await
+    handle_info_flags(&args);
+    let (sim, rng) = match init_simulation(&args) {
+        Ok(parts) => parts,
+        Err(e) => {
+            eprintln!("Error initializing simulation: {}", e);
+            std::process::exit(1);
+        }
+    };
+    headless_main(args.port, sim, rng).await
 }
 
 #[cfg(feature = "ui")]
@@ -56,10 +130,10 @@ async fn main() {
     let args = Args::parse();
 
     // Load configuration
-    let config = match load_config(args.config.as_deref()) {
-        Ok(cfg) => cfg,
+    let (sim, rng) = match init_simulation(&args) {
+        Ok(parts) => parts,
         Err(e) => {
-            eprintln!("Error loading config: {}", e);
+            eprintln!("Error initializing simulation: {}", e);
             std::process::exit(1);
         }
     };
@@ -68,43 +142,309 @@ async fn main() {
         // Run headless mode even with UI feature enabled
         let rt = tokio::runtime::Runtime::new().unwrap();
         rt.block_on(async {
-            if let Err(e) = headless_main(args.port, config).await {
+            if let Err(e) = headless_main(args.port, sim, rng).await {
                 eprintln!("Error running headless mode: {}", e);
                 std::process::exit(1);
             }
         });
     } else {
         // Run UI mode
-        ui_main(config).await;
+        ui_main(sim, rng).await;
+    }
+}
+
+/// Layer the base config, preset, config files and `--set` overrides, then apply `--seed`
+fn build_config(args: &Args) -> Result<SimulationConfig, Box<dyn std::error::Error>> {
+    let mut config = config_sources(args).load()?;
+    if args.seed.is_some() {
+        config.seed = args.seed;
+    }
+    Ok(config)
+}
+
+fn config_sources(args: &Args) -> ConfigSources {
+    ConfigSources {
+        preset: args.preset.clone(),
+        files: args.config.clone(),
+        overrides: args.overrides.clone(),
+    }
+}
+
+/// The config of a loaded snapshot with --preset, --config, --set and --seed layered on top.
+/// Also returns whether a seed was given, which starts a fresh generator.
+fn snapshot_config(
+    args: &Args,
+    saved: &SimulationConfig,
+) -> Result<(SimulationConfig, bool), Box<dyn std::error::Error>> {
+    let (mut config, fields) = config_sources(args).apply_to(saved)?;
+    let rebuild: Vec<&str> = fields
+        .iter()
+        .filter(|f| FieldEffect::of(f) == FieldEffect::Rebuild)
+        .map(String::as_str)
+        .collect();
+    if !rebuild.is_empty() {
+        return Err(format!(
+            "Can't change {} when loading a snapshot (these fields size its saved grids)",
+            rebuild.join(", ")
+        )
+        .into());
+    }
+    let deferred: Vec<&str> = fields
+        .iter()
+        .filter(|f| FieldEffect::of(f) == FieldEffect::Initialization && *f != "seed")
+        .map(String::as_str)
+        .collect();
+    if !deferred.is_empty() {
+        eprintln!(
+            "Note: {} only apply when a simulation is initialized, not to the loaded snapshot",
+            deferred.join(", ")
+        );
+    }
+    if args.seed.is_some() {
+        config.seed = args.seed;
+    }
+    config.validate()?;
+    let reseed = args.seed.is_some() || fields.iter().any(|f| f == "seed");
+    Ok((config, reseed))
+}
+
+/// Handle subcommands and flags that do their work and exit instead of starting the UI or server
+fn handle_info_flags(args: &Args) {
+    match &args.command {
+        Some(Command::Run(run)) => std::process::exit(batch_main(args, run)),
+        Some(Command::Sweep(sweep)) => std::process::exit(match sweep_main(args, sweep) {
+            Ok(()) => 0,
+            Err(e) => {
+                eprintln!("Error running sweep: {}", e);
+                1
+            }
+        }),
+        None => {}
+    }
+    if args.list_presets {
+        for preset in PRESETS {
+            println!("{:<16} {}", preset.name, preset.description);
+        }
+        std::process::exit(0);
+    }
+    if args.print_effective_config {
+        print_effective_config(args);
+    }
+    if let Some(path) = &args.export_graph {
+        let result = init_simulation(args).and_then(|(sim, _)| sim.save_graph(path));
+        match result {
+            Ok(()) => {
+                println!("Network exported: {}", path);
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Error exporting network: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if let Some(path) = &args.export_svg {
+        let result = init_simulation(args)
+            .and_then(|(sim, _)| sim.save_svg(path, &RenderOptions::default()));
+        match result {
+            Ok(()) => {
+                println!("SVG exported: {}", path);
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Error exporting SVG: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if let Some(path) = &args.export_grids {
+        let result = GridLayer::parse_list(args.grids.as_deref().unwrap_or(""))
+            .map_err(|e| e.into())
+            .and_then(|grids| {
+                init_simulation(args).and_then(|(sim, _)| sim.save_grids(path, &grids))
+            });
+        match result {
+            Ok(()) => {
+                println!("Grids exported: {}", path);
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Error exporting grids: {}", e);
+                std::process::exit(1);
+            }
+        }
     }
 }
 
-/// Load configuration from file or use default
-fn load_config(config_path: Option<&str>) -> Result<SimulationConfig, Box<dyn std::error::Error>> {
-    if let Some(path) = config_path {
-        // User specified a config file
-        SimulationConfig::from_file(path)
-            .map_err(|e| format!("Failed to load config from {}: {}", path, e).into())
+/// Handle `--print-effective-config`: print the merged config and exit
+fn print_effective_config(args: &Args) -> ! {
+    let config = if let Some(path) = &args.load_snapshot {
+        Simulation::load_snapshot(path)
+            .and_then(|(sim, _)| snapshot_config(args, &sim.config))
+            .map(|(config, _)| config)
     } else {
-        // Try default paths
-        Ok(SimulationConfig::from_default_paths())
+        build_config(args)
+    };
+    match config.and_then(|config| Ok(serde_yaml::to_string(&config)?)) {
+        Ok(yaml) => {
+            print!("{}", yaml);
+            std::process::exit(0);
+        }
+        Err(e) => {
+            eprintln!("Error loading config: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// `mycorust run`: a single batch run; returns the process exit code
+fn batch_main(args: &Args, run: &RunArgs) -> i32 {
+    let (mut sim, mut rng) = match init_simulation(args) {
+        Ok(parts) => parts,
+        Err(e) => {
+            eprintln!("Error initializing simulation: {}", e);
+            return 1;
+        }
+    };
+    let options = BatchOptions {
+        steps: run.steps,
+        out_dir: run.out.clone(),
+        progress: true,
+    };
+    println!("Running {} steps...", run.steps);
+    let result = run_batch(&mut sim, &mut rng, &options);
+    if let Some(timelapse) = sim.stop_timelapse() {
+        println!(
+            "Time-lapse: {} frames in {}",
+            timelapse.frame_count(),
+            timelapse.dir().display()
+        );
+    }
+    match result {
+        Ok(summary) => {
+            println!(
+                "Finished {} steps in {:.1}s ({:.0} steps/s): {} hyphae, {} connections",
+                summary.steps,
+                summary.elapsed_secs,
+                summary.steps_per_sec,
+                summary.stats.hyphae_count,
+                summary.stats.connections_count
+            );
+            println!("Results written to {}", run.out.display());
+            0
+        }
+        Err(e) => {
+            eprintln!("Error running batch: {}", e);
+            1
+        }
     }
 }
 
+/// `mycorust sweep`: every run of a sweep spec, in parallel
+fn sweep_main(args: &Args, sweep: &SweepArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if args.load_snapshot.is_some() {
+        return Err("--load-snapshot is not supported for sweeps".into());
+    }
+    if args.timelapse.is_some() {
+        return Err("--timelapse is not supported for sweeps".into());
+    }
+    let spec = SweepSpec::from_file(&sweep.spec)?;
+    let mut base = build_config(args)?;
+    let base_seed = base.resolve_seed();
+    let runs = spec.expand(&base, base_seed)?;
+
+    std::fs::create_dir_all(&sweep.out).map_err(|e| {
+        format!(
+            "Failed to create output directory {}: {}",
+            sweep.out.display(),
+            e
+        )
+    })?;
+    base.save_to_file(sweep.out.join("base_config.yaml"))?;
+
+    let mut pool = rayon::ThreadPoolBuilder::new();
+    if let Some(jobs) = sweep.jobs {
+        pool = pool.num_threads(jobs);
+    }
+    let pool = pool.build()?;
+    println!(
+        "Running {} runs of {} steps on {} threads...",
+        runs.len(),
+        spec.steps,
+        pool.current_num_threads()
+    );
+    let started = std::time::Instant::now();
+    let results =
+        pool.install(|| run_sweep(&runs, spec.steps, spec.save_runs, &sweep.out, true))?;
+
+    let path = sweep.out.join("summary.csv");
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(&path)?);
+    write_summary_csv(&mut writer, &results)?;
+    writer.flush()?;
+    println!(
+        "Finished {} runs in {:.1}s; summary written to {}",
+        results.len(),
+        started.elapsed().as_secs_f64(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// Create the simulation (fresh from config, or restored from a snapshot)
+/// together with the seeded generator that keeps stepping it
+fn init_simulation(args: &Args) -> Result<(Simulation, SimRng), Box<dyn std::error::Error>> {
+    if let Some(path) = &args.load_snapshot {
+        let (mut sim, mut rng) = Simulation::load_snapshot(path)
+            .map_err(|e| format!("Failed to load snapshot from {}: {}", path, e))?;
+        let (mut config, reseed) = snapshot_config(args, &sim.config)?;
+        // A seed branches a new experiment from the same saved state;
+        // otherwise the saved generator continues the run where it stopped
+        if reseed {
+            rng = create_rng(config.resolve_seed());
+        }
+        sim.update_config(config);
+        println!(
+            "Loaded snapshot from: {} (frame {})",
+            path, sim.state.frame_index
+        );
+        start_timelapse(args, &mut sim)?;
+        return Ok((sim, rng));
+    }
+
+    let mut config = build_config(args)?;
+    let seed = config.resolve_seed();
+    println!("Simulation seed: {}", seed);
+    let mut rng = create_rng(seed);
+    let mut sim = Simulation::try_with_config(&mut rng, config)?;
+    start_timelapse(args, &mut sim)?;
+    Ok((sim, rng))
+}
+
+/// Start recording when `--timelapse` is given
+fn start_timelapse(args: &Args, sim: &mut Simulation) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(dir) = &args.timelapse {
+        sim.start_timelapse(dir)?;
+        println!(
+            "Recording a time-lapse every {} steps into {}",
+            sim.config.timelapse_interval,
+            dir.display()
+        );
+    }
+    Ok(())
+}
+
 #[cfg(feature = "ui")]
-async fn ui_main(config: SimulationConfig) {
-    use controls::handle_controls;
-    use visualization::{
+async fn ui_main(mut sim: Simulation, mut rng: SimRng) {
+    use mycorust::controls::handle_controls;
+    use mycorust::visualization::{
         draw_connections, draw_fruit_bodies, draw_heatmap_age, draw_heatmap_flow,
         draw_heatmap_growth, draw_heatmap_moisture, draw_help_popup, draw_hyphae_enhanced,
-        draw_memory_overlay, draw_minimap, draw_nutrients, draw_obstacles, draw_segments,
-        draw_stats_and_help, draw_zones,
+        draw_memory_overlay, draw_minimap, draw_nutrients, draw_obstacles, draw_profile_overlay,
+        draw_segments, draw_stats_and_help, draw_zones,
     };
 
-    let mut rng = thread_rng();
-    // Initialize simulation with loaded config
-    let mut sim = Simulation::with_config(&mut rng, config);
-
     loop {
         // Update camera (pan/zoom)
         if sim.config.camera_enabled {
@@ -167,12 +507,7 @@ async fn ui_main(config: SimulationConfig) {
         );
 
         // Draw anastomosis connections
-        draw_connections(
-            &sim.state.connections,
-            &sim.state.hyphae,
-            sim.connections_visible,
-            &sim.config,
-        );
+        draw_connections(&sim.state, sim.connections_visible, &sim.config);
 
         // Network Intelligence: Draw memory overlay
         draw_memory_overlay(&sim.state.nutrient_memory, sim.memory_visible, &sim.config);
@@ -215,6 +550,7 @@ async fn ui_main(config: SimulationConfig) {
 
             for _ in 0..steps {
                 sim.step(&mut rng);
+                sim.record_timelapse();
             }
         }
 
@@ -234,6 +570,22 @@ async fn ui_main(config: SimulationConfig) {
             Some(&sim.state.weather),
         );
 
+        // Recording indicator while a time-lapse is running
+        if let Some(timelapse) = &sim.timelapse {
+            draw_text(
+                &format!("REC time-lapse ({} frames)", timelapse.frame_count()),
+                10.0,
+                120.0,
+                18.0,
+                RED,
+            );
+        }
+
+        // Step profile overlay
+        if sim.profile_overlay_visible {
+            draw_profile_overlay(&sim.profile_report());
+        }
+
         // Draw help popup if visible
         if sim.help_popup_visible {
             draw_help_popup(sim.config.camera_enabled);
@@ -277,8 +629,13 @@ async fn ui_main(config: SimulationConfig) {
 
 #[cfg(feature = "ui")]
 fn window_conf() -> Conf {
+    // Runs before main in UI builds, so handle print-and-exit flags here (before a window opens)
+    let args = Args::parse();
+    handle_info_flags(&args);
+
     // Try to load config to set window size, fall back to defaults if not available
-    let config = SimulationConfig::from_default_paths();
+    // (main reports any config error once the window is up)
+    let config = build_config(&args).unwrap_or_default();
     let width = (config.grid_size as f32 * config.cell_size) as i32;
     let height = (config.grid_size as f32 * config.cell_size) as i32;
 
@@ -333,21 +690,17 @@ fn capture_screenshot(filename: &str) -> Result<(), Box<dyn std::error::Error>>
 /// Headless mode - runs HTTP API server
 async fn headless_main(
     port: u16,
-    config: SimulationConfig,
+    sim: Simulation,
+    rng: SimRng,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    use api::run_server;
-    use api::ApiState;
-    use simulation::set_headless_mode;
+    use mycorust::api::{run_server, ApiState};
+    use mycorust::simulation::set_headless_mode;
 
     // Set headless mode flag to avoid calling macroquad functions
     set_headless_mode(true);
 
-    // Initialize simulation with loaded config
-    let mut rng = thread_rng();
-    let sim = Simulation::with_config(&mut rng, config);
-
-    // Create API state
-    let api_state = ApiState::new(sim);
+    // Create API state (keeps stepping with the same seeded generator)
+    let api_state = ApiState::new(sim, rng);
 
     // Run the server
     run_server(api_state, port).await?;
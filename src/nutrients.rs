@@ -1,18 +1,21 @@
 #[cfg(feature = "ui")]
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::grid::Grid2D;
 
 // Multi-nutrient grid
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct NutrientGrid {
-    pub sugar: Vec<Vec<f32>>,
-    pub nitrogen: Vec<Vec<f32>>,
+    pub sugar: Grid2D<f32>,
+    pub nitrogen: Grid2D<f32>,
 }
 
 impl NutrientGrid {
     pub fn new(grid_size: usize) -> Self {
         Self {
-            sugar: vec![vec![0.0f32; grid_size]; grid_size],
-            nitrogen: vec![vec![0.0f32; grid_size]; grid_size],
+            sugar: Grid2D::new(grid_size, grid_size, 0.0),
+            nitrogen: Grid2D::new(grid_size, grid_size, 0.0),
         }
     }
 
@@ -29,8 +32,8 @@ impl NutrientGrid {
     }
 }
 
-#[cfg(feature = "ui")]
-pub fn nutrient_color(sugar: f32, nitrogen: f32) -> Color {
+// Shared by the window renderer and the offscreen renderer
+pub fn nutrient_rgb(sugar: f32, nitrogen: f32) -> [f32; 3] {
     let s = sugar.clamp(0.0, 1.0);
     let n = nitrogen.clamp(0.0, 1.0);
     // Sugar = brown/green, Nitrogen = blue/purple
@@ -38,6 +41,12 @@ pub fn nutrient_color(sugar: f32, nitrogen: f32) -> Color {
     let r = 0.2 + 0.3 * s + 0.2 * n;
     let g = 0.3 + 0.5 * s + 0.1 * n;
     let b = 0.2 + 0.3 * n;
+    [r, g, b]
+}
+
+#[cfg(feature = "ui")]
+pub fn nutrient_color(sugar: f32, nitrogen: f32) -> Color {
+    let [r, g, b] = nutrient_rgb(sugar, nitrogen);
     Color::new(r, g, b, 1.0)
 }
 
@@ -80,7 +89,7 @@ pub fn nutrient_gradient(grid: &NutrientGrid, x: f32, y: f32, grid_size: usize)
 }
 
 // Compute gradient of memory grid (for network intelligence)
-pub fn memory_gradient(memory: &[Vec<f32>], x: f32, y: f32, grid_size: usize) -> (f32, f32) {
+pub fn memory_gradient(memory: &Grid2D<f32>, x: f32, y: f32, grid_size: usize) -> (f32, f32) {
     let xi = x as usize;
     let yi = y as usize;
     if xi < 1 || yi < 1 || xi >= grid_size - 1 || yi >= grid_size - 1 {
@@ -0,0 +1,420 @@
+// Step profiling - wall time and work counters for each phase of Simulation::step
+// Recorded on every step (a handful of clock reads and counter bumps), so benchmarks and long runs
+// can tell which subsystem a slowdown comes from. ProfileStats keeps a rolling window next to the
+// run totals: a phase whose recent cost drifts above its run mean is the one getting slower.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+// Steps in the rolling window of ProfileStats
+pub const PROFILE_WINDOW: usize = 120;
+
+/// Timed phases of `Simulation::step`, in the order they run
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StepPhase {
+    Weather,
+    Moisture,    // Soil moisture diffusion, rain and evaporation
+    MemoryDecay, // Decay of the nutrient memory grid
+    Growth,      // Culling, signal decay, tip growth and branching
+    Fusion,      // Merging of touching hyphae
+    Anastomosis, // Connection detection and cleanup of dead connections
+    Flow,        // Pressure-driven resource flow, signals and pruning along connections
+    FlowField,   // Water flow velocity field
+    Zones,       // Zone growth and competitor consumption
+    DensityMap,  // Mycelial density map decay and stamping
+    Diffusion,   // Nutrient diffusion and regeneration
+    Spores,      // Spores, germination and fruiting bodies
+}
+
+impl StepPhase {
+    pub const ALL: [StepPhase; 12] = [
+        StepPhase::Weather,
+        StepPhase::Moisture,
+        StepPhase::MemoryDecay,
+        StepPhase::Growth,
+        StepPhase::Fusion,
+        StepPhase::Anastomosis,
+        StepPhase::Flow,
+        StepPhase::FlowField,
+        StepPhase::Zones,
+        StepPhase::DensityMap,
+        StepPhase::Diffusion,
+        StepPhase::Spores,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StepPhase::Weather => "weather",
+            StepPhase::Moisture => "moisture",
+            StepPhase::MemoryDecay => "memory_decay",
+            StepPhase::Growth => "growth",
+            StepPhase::Fusion => "fusion",
+            StepPhase::Anastomosis => "anastomosis",
+            StepPhase::Flow => "flow",
+            StepPhase::FlowField => "flow_field",
+            StepPhase::Zones => "zones",
+            StepPhase::DensityMap => "density_map",
+            StepPhase::Diffusion => "diffusion",
+            StepPhase::Spores => "spores",
+        }
+    }
+}
+
+/// Work done by a phase, counted per step
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StepCounter {
+    MoistureCells,
+    MemoryCells,
+    TipsGrown,
+    GrowthGridQueries, // Spatial-grid buckets scanned for density and collision checks
+    GrowthNeighborChecks,
+    Branches,
+    FusionGridQueries,
+    FusionPairs,
+    Fusions,
+    ConnectionGridQueries,
+    ConnectionPairs,
+    ConnectionsCreated,
+    ConnectionsRemoved, // Dead connections cleaned up
+    ConnectionsFlowed,
+    ConnectionsPruned,
+    FlowFieldCells,
+    ZoneCells,
+    DensityCells,
+    DensityStamps, // Hyphae stamped onto the density map
+    DiffusedCells,
+    RegenSamples,
+    SporesMoved,
+}
+
+impl StepCounter {
+    pub const ALL: [StepCounter; 22] = [
+        StepCounter::MoistureCells,
+        StepCounter::MemoryCells,
+        StepCounter::TipsGrown,
+        StepCounter::GrowthGridQueries,
+        StepCounter::GrowthNeighborChecks,
+        StepCounter::Branches,
+        StepCounter::FusionGridQueries,
+        StepCounter::FusionPairs,
+        StepCounter::Fusions,
+        StepCounter::ConnectionGridQueries,
+        StepCounter::ConnectionPairs,
+        StepCounter::ConnectionsCreated,
+        StepCounter::ConnectionsRemoved,
+        StepCounter::ConnectionsFlowed,
+        StepCounter::ConnectionsPruned,
+        StepCounter::FlowFieldCells,
+        StepCounter::ZoneCells,
+        StepCounter::DensityCells,
+        StepCounter::DensityStamps,
+        StepCounter::DiffusedCells,
+        StepCounter::RegenSamples,
+        StepCounter::SporesMoved,
+    ];
+
+    /// The phase doing this work
+    pub fn phase(self) -> StepPhase {
+        match self {
+            StepCounter::MoistureCells => StepPhase::Moisture,
+            StepCounter::MemoryCells => StepPhase::MemoryDecay,
+            StepCounter::TipsGrown
+            | StepCounter::GrowthGridQueries
+            | StepCounter::GrowthNeighborChecks
+            | StepCounter::Branches => StepPhase::Growth,
+            StepCounter::FusionGridQueries | StepCounter::FusionPairs | StepCounter::Fusions => {
+                StepPhase::Fusion
+            }
+            StepCounter::ConnectionGridQueries
+            | StepCounter::ConnectionPairs
+            | StepCounter::ConnectionsCreated
+            | StepCounter::ConnectionsRemoved => StepPhase::Anastomosis,
+            StepCounter::ConnectionsFlowed | StepCounter::ConnectionsPruned => StepPhase::Flow,
+            StepCounter::FlowFieldCells => StepPhase::FlowField,
+            StepCounter::ZoneCells => StepPhase::Zones,
+            StepCounter::DensityCells | StepCounter::DensityStamps => StepPhase::DensityMap,
+            StepCounter::DiffusedCells | StepCounter::RegenSamples => StepPhase::Diffusion,
+            StepCounter::SporesMoved => StepPhase::Spores,
+        }
+    }
+
+    /// Name within its phase
+    pub fn name(self) -> &'static str {
+        match self {
+            StepCounter::MoistureCells
+            | StepCounter::MemoryCells
+            | StepCounter::FlowFieldCells
+            | StepCounter::ZoneCells
+            | StepCounter::DensityCells
+            | StepCounter::DiffusedCells => "cells",
+            StepCounter::TipsGrown => "tips",
+            StepCounter::GrowthGridQueries
+            | StepCounter::FusionGridQueries
+            | StepCounter::ConnectionGridQueries => "grid_queries",
+            StepCounter::GrowthNeighborChecks => "neighbor_checks",
+            StepCounter::Branches => "branches",
+            StepCounter::FusionPairs | StepCounter::ConnectionPairs => "pairs_checked",
+            StepCounter::Fusions => "fusions",
+            StepCounter::ConnectionsCreated => "created",
+            StepCounter::ConnectionsRemoved => "removed",
+            StepCounter::ConnectionsFlowed => "connections",
+            StepCounter::ConnectionsPruned => "pruned",
+            StepCounter::DensityStamps => "hyphae",
+            StepCounter::RegenSamples => "regen_samples",
+            StepCounter::SporesMoved => "spores",
+        }
+    }
+}
+
+/// Timings and work counters of one step
+#[derive(Clone, Debug, Default)]
+pub struct StepProfile {
+    pub total: Duration,
+    phases: [Duration; StepPhase::ALL.len()],
+    counters: [u64; StepCounter::ALL.len()],
+}
+
+impl StepProfile {
+    pub fn phase(&self, phase: StepPhase) -> Duration {
+        self.phases[phase as usize]
+    }
+
+    pub fn count(&self, counter: StepCounter) -> u64 {
+        self.counters[counter as usize]
+    }
+
+    /// Time spent outside the timed phases (history, time-lapse, event bookkeeping, ...)
+    pub fn other(&self) -> Duration {
+        self.total.saturating_sub(self.phases.iter().sum())
+    }
+
+    pub(crate) fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Add the time since `started` to `phase`
+    pub(crate) fn record(&mut self, phase: StepPhase, started: Instant) {
+        self.phases[phase as usize] += started.elapsed();
+    }
+
+    pub(crate) fn add(&mut self, counter: StepCounter, amount: u64) {
+        self.counters[counter as usize] += amount;
+    }
+
+    fn accumulate(&mut self, step: &StepProfile) {
+        self.total += step.total;
+        for (sum, phase) in self.phases.iter_mut().zip(step.phases) {
+            *sum += phase;
+        }
+        for (sum, count) in self.counters.iter_mut().zip(step.counters) {
+            *sum += count;
+        }
+    }
+
+    fn remove(&mut self, step: &StepProfile) {
+        self.total = self.total.saturating_sub(step.total);
+        for (sum, phase) in self.phases.iter_mut().zip(step.phases) {
+            *sum = sum.saturating_sub(phase);
+        }
+        for (sum, count) in self.counters.iter_mut().zip(step.counters) {
+            *sum = sum.saturating_sub(count);
+        }
+    }
+}
+
+/// Step profiles aggregated over the run and over the last `PROFILE_WINDOW` steps
+#[derive(Clone, Debug, Default)]
+pub struct ProfileStats {
+    steps: u64,
+    run: StepProfile, // Sums since the start of the run
+    recent: VecDeque<StepProfile>,
+    recent_sum: StepProfile,
+}
+
+impl ProfileStats {
+    pub(crate) fn push(&mut self, step: &StepProfile) {
+        self.steps += 1;
+        self.run.accumulate(step);
+        self.recent_sum.accumulate(step);
+        self.recent.push_back(step.clone());
+        if self.recent.len() > PROFILE_WINDOW {
+            if let Some(oldest) = self.recent.pop_front() {
+                self.recent_sum.remove(&oldest);
+            }
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Summarize the last step, the recent window and the run so far
+    pub fn report(&self, frame_index: u64) -> ProfileReport {
+        let default = StepProfile::default();
+        let last = self.recent.back().unwrap_or(&default);
+        let window = self.recent.len();
+        let recent_ms = |d: Duration| mean_ms(d, window as u64);
+        let run_ms = |d: Duration| mean_ms(d, self.steps);
+        let share = |d: Duration| {
+            if self.recent_sum.total.is_zero() {
+                0.0
+            } else {
+                d.as_secs_f64() / self.recent_sum.total.as_secs_f64()
+            }
+        };
+
+        let mut phases: Vec<PhaseReport> = StepPhase::ALL
+            .iter()
+            .map(|&phase| PhaseReport {
+                name: phase.name(),
+                last_ms: ms(last.phase(phase)),
+                recent_ms: recent_ms(self.recent_sum.phase(phase)),
+                mean_ms: run_ms(self.run.phase(phase)),
+                share: share(self.recent_sum.phase(phase)),
+                counters: StepCounter::ALL
+                    .iter()
+                    .filter(|counter| counter.phase() == phase)
+                    .map(|&counter| {
+                        let report = CounterReport {
+                            last: last.count(counter),
+                            recent: mean(self.recent_sum.count(counter), window as u64),
+                            mean: mean(self.run.count(counter), self.steps),
+                        };
+                        (counter.name(), report)
+                    })
+                    .collect(),
+            })
+            .collect();
+        phases.push(PhaseReport {
+            name: "other",
+            last_ms: ms(last.other()),
+            recent_ms: recent_ms(self.recent_sum.other()),
+            mean_ms: run_ms(self.run.other()),
+            share: share(self.recent_sum.other()),
+            counters: BTreeMap::new(),
+        });
+
+        ProfileReport {
+            frame_index,
+            steps: self.steps,
+            window,
+            last_step_ms: ms(last.total),
+            recent_step_ms: recent_ms(self.recent_sum.total),
+            mean_step_ms: run_ms(self.run.total),
+            phases,
+        }
+    }
+}
+
+fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn mean_ms(duration: Duration, steps: u64) -> f64 {
+    if steps == 0 {
+        0.0
+    } else {
+        ms(duration) / steps as f64
+    }
+}
+
+fn mean(count: u64, steps: u64) -> f64 {
+    if steps == 0 {
+        0.0
+    } else {
+        count as f64 / steps as f64
+    }
+}
+
+/// Per-phase cost of recent steps, as served by `GET /profile`
+#[derive(Clone, Debug, Serialize)]
+pub struct ProfileReport {
+    pub frame_index: u64,
+    pub steps: u64,               // Steps profiled since the run started
+    pub window: usize,            // Steps in the recent window
+    pub last_step_ms: f64,        // Last step
+    pub recent_step_ms: f64,      // Mean over the recent window
+    pub mean_step_ms: f64,        // Mean over the run
+    pub phases: Vec<PhaseReport>, // In step order, then "other"
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PhaseReport {
+    pub name: &'static str,
+    pub last_ms: f64,
+    pub recent_ms: f64,
+    pub mean_ms: f64,
+    pub share: f64, // Fraction of the recent window's step time
+    pub counters: BTreeMap<&'static str, CounterReport>,
+}
+
+/// Work per step: last step, recent window mean and run mean
+#[derive(Clone, Debug, Serialize)]
+pub struct CounterReport {
+    pub last: u64,
+    pub recent: f64,
+    pub mean: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{create_rng, Simulation};
+    use crate::SimulationConfig;
+
+    /// Test that step profiles time every phase and count the work done
+    #[test]
+    fn test_step_profile() {
+        let mut rng = create_rng(3);
+        let mut sim = Simulation::with_config(&mut rng, SimulationConfig::default());
+        for _ in 0..5 {
+            sim.step(&mut rng);
+        }
+        let profile = &sim.profile;
+        assert!(profile.total > Duration::ZERO);
+        assert!(profile.phase(StepPhase::Growth) > Duration::ZERO);
+        let phases: Duration = StepPhase::ALL.iter().map(|&p| profile.phase(p)).sum();
+        assert_eq!(phases + profile.other(), profile.total);
+        assert_eq!(
+            StepPhase::ALL[StepPhase::Spores as usize],
+            StepPhase::Spores
+        );
+        assert!(StepCounter::ALL
+            .iter()
+            .enumerate()
+            .all(|(i, &counter)| counter as usize == i));
+
+        // Work counters
+        let grid = sim.config.grid_size as u64;
+        assert_eq!(profile.count(StepCounter::MemoryCells), grid * grid);
+        let tips = profile.count(StepCounter::TipsGrown);
+        assert!(tips > 0);
+        assert!(profile.count(StepCounter::GrowthGridQueries) >= tips);
+        assert!(profile.count(StepCounter::DiffusedCells) > 0);
+
+        // Rolling window and run means
+        for _ in 0..PROFILE_WINDOW {
+            sim.step(&mut rng);
+        }
+        let report = sim.profile_report();
+        assert_eq!(report.steps, PROFILE_WINDOW as u64 + 5);
+        assert_eq!(report.window, PROFILE_WINDOW);
+        assert_eq!(report.phases.len(), StepPhase::ALL.len() + 1);
+        let share: f64 = report.phases.iter().map(|p| p.share).sum();
+        assert!((share - 1.0).abs() < 1e-6);
+        let memory = &report.phases[StepPhase::MemoryDecay as usize];
+        assert_eq!(memory.counters["cells"].recent, (grid * grid) as f64);
+        assert_eq!(memory.counters["cells"].last, grid * grid);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["phases"][StepPhase::Fusion as usize]["name"], "fusion");
+
+        sim.reset(&mut rng);
+        assert_eq!(sim.profile_report().steps, 0);
+    }
+}
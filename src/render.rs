@@ -0,0 +1,814 @@
+// Offscreen rendering - a small software rasteriser that draws the colony into an RGBA image
+// without a window or GPU, so headless servers and batch runs can still produce pictures.
+// Colors follow visualization.rs; animated effects (pulsing flow and signals) are drawn at rest.
+
+use image::{Rgba, RgbaImage};
+use std::path::Path;
+
+use crate::grid::Grid2D;
+use crate::nutrients::nutrient_rgb;
+use crate::simulation::Simulation;
+use crate::types::ZoneType;
+
+// Same background as the window
+pub(crate) const BACKGROUND: [f32; 3] = [0.05, 0.10, 0.35];
+
+// Largest accepted scale factor (keeps API renders of big grids to a sane size)
+pub const MAX_RENDER_SCALE: f32 = 8.0;
+
+// Largest accepted image side in pixels (a scale that suits a small grid is too much for a big one)
+pub const MAX_RENDER_SIDE: u32 = 4096;
+
+/// Drawable layers, in drawing order
+pub const RENDER_LAYERS: [&str; 10] = [
+    "nutrients",
+    "moisture",
+    "age",
+    "flow",
+    "growth",
+    "segments",
+    "connections",
+    "memory",
+    "hyphae",
+    "fruit_bodies",
+];
+
+/// What to draw and how large. Obstacles and zones are always drawn, like in the window.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderOptions {
+    // Output pixels per window pixel (the window is grid_size * cell_size pixels wide)
+    pub scale: f32,
+    pub nutrients: bool,
+    pub moisture: bool,
+    pub age: bool,
+    pub flow: bool,
+    pub growth: bool,
+    pub segments: bool,
+    pub connections: bool,
+    pub memory: bool,
+    pub hyphae: bool,
+    pub fruit_bodies: bool,
+}
+
+impl Default for RenderOptions {
+    // Matches what the window shows on startup
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            nutrients: true,
+            moisture: false,
+            age: false,
+            flow: false,
+            growth: false,
+            segments: true,
+            connections: true,
+            memory: false,
+            hyphae: false,
+            fruit_bodies: true,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Options with only the given layers enabled (comma-separated names from `RENDER_LAYERS`)
+    pub fn with_layers(layers: &str, scale: f32) -> Result<Self, String> {
+        let mut options = Self {
+            scale,
+            nutrients: false,
+            moisture: false,
+            age: false,
+            flow: false,
+            growth: false,
+            segments: false,
+            connections: false,
+            memory: false,
+            hyphae: false,
+            fruit_bodies: false,
+        };
+        for name in layers.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let layer = match name {
+                "nutrients" => &mut options.nutrients,
+                "moisture" => &mut options.moisture,
+                "age" => &mut options.age,
+                "flow" => &mut options.flow,
+                "growth" => &mut options.growth,
+                "segments" => &mut options.segments,
+                "connections" => &mut options.connections,
+                "memory" => &mut options.memory,
+                "hyphae" => &mut options.hyphae,
+                "fruit_bodies" => &mut options.fruit_bodies,
+                _ => {
+                    return Err(format!(
+                        "Unknown layer '{}' (known layers: {})",
+                        name,
+                        RENDER_LAYERS.join(", ")
+                    ))
+                }
+            };
+            *layer = true;
+        }
+        options.validate()?;
+        Ok(options)
+    }
+
+    /// The layers currently toggled on in the window (heatmap and visibility keys)
+    pub fn from_view(sim: &Simulation, scale: f32) -> Self {
+        Self {
+            scale,
+            nutrients: sim.heatmap_nutrients,
+            moisture: sim.heatmap_moisture,
+            age: sim.heatmap_age,
+            flow: sim.heatmap_flow,
+            growth: sim.heatmap_growth,
+            segments: sim.hyphae_visible,
+            connections: sim.connections_visible,
+            memory: sim.memory_visible,
+            hyphae: sim.enhanced_visualization,
+            fruit_bodies: true,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.scale > 0.0 && self.scale <= MAX_RENDER_SCALE) {
+            return Err(format!(
+                "scale must be greater than 0 and at most {}",
+                MAX_RENDER_SCALE
+            ));
+        }
+        Ok(())
+    }
+}
+
+// RGBA image with "over" alpha blending; coordinates are window pixels
+struct Canvas {
+    image: RgbaImage,
+    scale: f32,
+}
+
+impl Canvas {
+    // Opaque window background
+    fn new(width: u32, height: u32, scale: f32) -> Self {
+        let [r, g, b] = BACKGROUND.map(to_byte);
+        Self {
+            image: RgbaImage::from_pixel(width, height, Rgba([r, g, b, 255])),
+            scale,
+        }
+    }
+
+    fn transparent(width: u32, height: u32, scale: f32) -> Self {
+        Self {
+            image: RgbaImage::new(width, height),
+            scale,
+        }
+    }
+
+    fn paint(&mut self, shapes: &[Shape]) {
+        for shape in shapes {
+            match *shape {
+                Shape::Rect { x, y, w, h, color } => self.fill_rect(x, y, w, h, color),
+                Shape::Line {
+                    from,
+                    to,
+                    width,
+                    color,
+                } => self.line(from.0, from.1, to.0, to.1, width, color),
+                Shape::Circle {
+                    center,
+                    radius,
+                    color,
+                } => self.circle(center.0, center.1, radius, color),
+            }
+        }
+    }
+
+    fn blend(&mut self, x: u32, y: u32, color: [f32; 4], coverage: f32) {
+        let alpha = (color[3] * coverage).clamp(0.0, 1.0);
+        if alpha <= 0.0 {
+            return;
+        }
+        let pixel = self.image.get_pixel_mut(x, y);
+        let dst_alpha = pixel[3] as f32 / 255.0;
+        let out_alpha = alpha + dst_alpha * (1.0 - alpha);
+        for c in 0..3 {
+            let dst = pixel[c] as f32 / 255.0;
+            let src = color[c].clamp(0.0, 1.0);
+            pixel[c] = to_byte((src * alpha + dst * dst_alpha * (1.0 - alpha)) / out_alpha);
+        }
+        pixel[3] = to_byte(out_alpha);
+    }
+
+    // Pixel range [min, max) covering window coordinates lo..hi along one axis
+    fn span(&self, lo: f32, hi: f32, size: u32) -> (u32, u32) {
+        let min = (lo * self.scale).floor().max(0.0) as u32;
+        let max = ((hi * self.scale).ceil().max(0.0) as u32).min(size);
+        (min.min(size), max)
+    }
+
+    fn fill_rect(&mut self, x: f32, y: f32, w: f32, h: f32, color: [f32; 4]) {
+        // Snap edges to pixel boundaries so adjacent grid cells tile without seams
+        let x0 = ((x * self.scale).round().max(0.0) as u32).min(self.image.width());
+        let y0 = ((y * self.scale).round().max(0.0) as u32).min(self.image.height());
+        let x1 = (((x + w) * self.scale).round().max(0.0) as u32).min(self.image.width());
+        let y1 = (((y + h) * self.scale).round().max(0.0) as u32).min(self.image.height());
+        for py in y0..y1 {
+            for px in x0..x1 {
+                self.blend(px, py, color, 1.0);
+            }
+        }
+    }
+
+    // Antialiased: coverage falls off over one pixel at the edge
+    fn circle(&mut self, cx: f32, cy: f32, radius: f32, color: [f32; 4]) {
+        let margin = radius + 1.0;
+        let (x0, x1) = self.span(cx - margin, cx + margin, self.image.width());
+        let (y0, y1) = self.span(cy - margin, cy + margin, self.image.height());
+        let r = radius * self.scale;
+        let (sx, sy) = (cx * self.scale, cy * self.scale);
+        for py in y0..y1 {
+            for px in x0..x1 {
+                let dx = px as f32 + 0.5 - sx;
+                let dy = py as f32 + 0.5 - sy;
+                let d = (dx * dx + dy * dy).sqrt();
+                self.blend(px, py, color, (r + 0.5 - d).clamp(0.0, 1.0));
+            }
+        }
+    }
+
+    // Antialiased thick line: coverage from each pixel center's distance to the segment
+    fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: [f32; 4]) {
+        let half = (thickness * self.scale * 0.5).max(0.5);
+        let margin = half / self.scale + 1.0;
+        let (px0, px1) = self.span(x1.min(x2) - margin, x1.max(x2) + margin, self.image.width());
+        let (py0, py1) = self.span(
+            y1.min(y2) - margin,
+            y1.max(y2) + margin,
+            self.image.height(),
+        );
+        let (ax, ay) = (x1 * self.scale, y1 * self.scale);
+        let (dx, dy) = ((x2 - x1) * self.scale, (y2 - y1) * self.scale);
+        let len_sq = dx * dx + dy * dy;
+        for py in py0..py1 {
+            for px in px0..px1 {
+                let (qx, qy) = (px as f32 + 0.5 - ax, py as f32 + 0.5 - ay);
+                let t = if len_sq > 0.0 {
+                    ((qx * dx + qy * dy) / len_sq).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let (ex, ey) = (qx - t * dx, qy - t * dy);
+                let d = (ex * ex + ey * ey).sqrt();
+                self.blend(px, py, color, (half + 0.5 - d).clamp(0.0, 1.0));
+            }
+        }
+    }
+}
+
+fn to_byte(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+// A drawing primitive in window pixels, shared by the rasteriser and the SVG writer
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Shape {
+    Rect {
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        color: [f32; 4],
+    },
+    Line {
+        from: (f32, f32),
+        to: (f32, f32),
+        width: f32,
+        color: [f32; 4],
+    },
+    Circle {
+        center: (f32, f32),
+        radius: f32,
+        color: [f32; 4],
+    },
+}
+
+impl Simulation {
+    fn render_side(&self, scale: f32) -> f32 {
+        self.config.grid_size as f32 * self.config.cell_size * scale
+    }
+
+    /// Reject options whose image would be more than `MAX_RENDER_SIDE` pixels per side
+    pub fn check_render_size(&self, options: &RenderOptions) -> Result<(), String> {
+        let side = self.render_side(options.scale).round();
+        if side > MAX_RENDER_SIDE as f32 {
+            return Err(format!(
+                "image would be {} pixels per side (grid_size * cell_size * scale), at most {} allowed",
+                side, MAX_RENDER_SIDE
+            ));
+        }
+        Ok(())
+    }
+
+    /// `scale`, lowered as far as needed to keep the image within `MAX_RENDER_SIDE` pixels
+    /// per side. For renders that should shrink on big grids instead of failing.
+    pub fn fit_render_scale(&self, scale: f32) -> f32 {
+        scale.min(MAX_RENDER_SIDE as f32 / self.render_side(1.0))
+    }
+
+    /// Draw the colony into an image without a window. The image covers the whole grid:
+    /// `grid_size * cell_size * scale` pixels per side.
+    pub fn render_image(&self, options: &RenderOptions) -> RgbaImage {
+        let side = (self.render_side(options.scale).round() as u32).max(1);
+        let mut canvas = Canvas::new(side, side, options.scale);
+        // Same order as the window: heatmaps, terrain, trails, connections, memory, hyphae, fruit
+        canvas.paint(&self.heatmap_shapes(options));
+        canvas.paint(&self.terrain_shapes());
+        if options.segments {
+            canvas.paint(&self.segment_shapes());
+        }
+        if options.connections {
+            canvas.paint(&self.connection_shapes());
+        }
+        if options.memory {
+            canvas.paint(&self.memory_shapes());
+        }
+        if options.hyphae {
+            canvas.paint(&self.hypha_shapes());
+        }
+        if options.fruit_bodies {
+            canvas.paint(&self.fruit_body_shapes());
+        }
+        canvas.image
+    }
+
+    /// Render and save as PNG. Fails for images larger than `MAX_RENDER_SIDE` per side.
+    pub fn save_png<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: &RenderOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.check_render_size(options)?;
+        self.render_image(options)
+            .save_with_format(path, image::ImageFormat::Png)?;
+        Ok(())
+    }
+
+    /// Render and encode as PNG bytes. Fails for images larger than `MAX_RENDER_SIDE` per side.
+    pub fn render_png(
+        &self,
+        options: &RenderOptions,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        self.check_render_size(options)?;
+        let mut bytes = Vec::new();
+        self.render_image(options).write_to(
+            &mut std::io::Cursor::new(&mut bytes),
+            image::ImageFormat::Png,
+        )?;
+        Ok(bytes)
+    }
+
+    /// Rasterise grid-cell shapes at one pixel per cell on a transparent background
+    /// (used to embed heatmaps in vector exports). None when there is nothing to draw.
+    pub(crate) fn render_cell_layer(&self, shapes: &[Shape]) -> Option<RgbaImage> {
+        if shapes.is_empty() {
+            return None;
+        }
+        let side = self.config.grid_size as u32;
+        let mut canvas = Canvas::transparent(side, side, 1.0 / self.config.cell_size);
+        canvas.paint(shapes);
+        Some(canvas.image)
+    }
+
+    // One rect per grid cell the color function returns a color for
+    fn cell_shapes<F>(&self, shapes: &mut Vec<Shape>, color: F)
+    where
+        F: Fn(usize, usize) -> Option<[f32; 4]>,
+    {
+        let cell = self.config.cell_size;
+        for x in 0..self.config.grid_size {
+            for y in 0..self.config.grid_size {
+                if let Some(color) = color(x, y) {
+                    shapes.push(Shape::Rect {
+                        x: x as f32 * cell,
+                        y: y as f32 * cell,
+                        w: cell,
+                        h: cell,
+                        color,
+                    });
+                }
+            }
+        }
+    }
+
+    // Nutrients, moisture, age, flow and growth heatmaps (base layers first)
+    pub(crate) fn heatmap_shapes(&self, options: &RenderOptions) -> Vec<Shape> {
+        let grid_size = self.config.grid_size;
+        let mut shapes = Vec::new();
+        if options.nutrients {
+            self.cell_shapes(&mut shapes, |x, y| {
+                let [r, g, b] =
+                    nutrient_rgb(self.nutrients.sugar[x][y], self.nutrients.nitrogen[x][y]);
+                Some([r, g, b, 1.0])
+            });
+        }
+        if options.moisture && self.config.soil_moisture_enabled {
+            self.cell_shapes(&mut shapes, |x, y| {
+                Some([0.0, 0.3, 0.8, self.soil_moisture[x][y] * 0.6])
+            });
+        }
+        if options.age {
+            let age_map = self.per_cell_average(|h| h.age);
+            self.cell_shapes(&mut shapes, |x, y| {
+                let normalized = (age_map[x][y]? / 100.0).min(1.0);
+                Some(if normalized < 0.5 {
+                    [normalized * 2.0, 0.8, 0.0, normalized * 0.6]
+                } else {
+                    [1.0, 0.8 - (normalized - 0.5) * 1.6, 0.0, normalized * 0.6]
+                })
+            });
+        }
+        if options.flow {
+            let flow = self.hypha_flow();
+            let mut flow_map = Grid2D::new(grid_size, grid_size, 0.0f32);
+            for (idx, h) in self.hyphae.iter().enumerate().filter(|(_, h)| h.alive) {
+                let (xi, yi) = (h.x as usize, h.y as usize);
+                if xi < grid_size && yi < grid_size {
+                    flow_map[xi][yi] += flow[idx];
+                }
+            }
+            self.cell_shapes(&mut shapes, |x, y| {
+                let flow = flow_map[x][y];
+                (flow > 0.01).then(|| {
+                    let n = (flow * 10.0).min(1.0);
+                    [n * 0.5, 0.8 + n * 0.2, 1.0, n * 0.7]
+                })
+            });
+        }
+        if options.growth {
+            self.cell_shapes(&mut shapes, |x, y| {
+                let p = self.growth_probability(x, y);
+                (p > 0.01).then(|| {
+                    let n = p.min(1.0);
+                    [0.8 + n * 0.2, 0.2 + n * 0.3, 0.6 + n * 0.4, n * 0.6]
+                })
+            });
+        }
+        shapes
+    }
+
+    // Obstacles and zones (always drawn, like in the window)
+    pub(crate) fn terrain_shapes(&self) -> Vec<Shape> {
+        let mut shapes = Vec::new();
+        self.cell_shapes(&mut shapes, |x, y| {
+            self.obstacles[x][y].then_some([0.05, 0.05, 0.05, 1.0])
+        });
+        if self.config.zones_enabled {
+            self.cell_shapes(&mut shapes, |x, y| {
+                let zone = self.zones.get(x, y)?;
+                let [r, g, b] = zone_rgb(zone.zone_type)?;
+                Some([r, g, b, zone.intensity * 0.3])
+            });
+        }
+        shapes
+    }
+
+    pub(crate) fn memory_shapes(&self) -> Vec<Shape> {
+        let mut shapes = Vec::new();
+        self.cell_shapes(&mut shapes, |x, y| {
+            let value = self.nutrient_memory[x][y];
+            (value > 0.001).then(|| [0.6, 0.2, 0.9, (value * 0.5).min(0.6)])
+        });
+        shapes
+    }
+
+    // Hyphal trails fading with age: young = white, old = dark gray/blue
+    pub(crate) fn segment_shapes(&self) -> Vec<Shape> {
+        let max_age = self.config.max_segment_age;
+        self.segments
+            .iter()
+            .filter_map(|segment| {
+                let alpha = (1.0 - segment.age / max_age).clamp(0.0, 1.0);
+                if alpha < 0.02 {
+                    return None;
+                }
+                let age_normalized = (segment.age / max_age).min(1.0);
+                Some(Shape::Line {
+                    from: (segment.from.x, segment.from.y),
+                    to: (segment.to.x, segment.to.y),
+                    width: 1.5,
+                    color: [
+                        1.0 - age_normalized * 0.7,
+                        1.0 - age_normalized * 0.7,
+                        1.0 - age_normalized * 0.5,
+                        alpha,
+                    ],
+                })
+            })
+            .collect()
+    }
+
+    // Anastomosis connections: thicker and brighter with strength, red/orange while signalling
+    pub(crate) fn connection_shapes(&self) -> Vec<Shape> {
+        let cell = self.config.cell_size;
+        // The window pulses between 0.25 and 0.75; use the middle
+        let pulse = 0.5;
+        let mut shapes = Vec::new();
+        for conn in &self.connections {
+            let (Some(h1), Some(h2)) = (self.hypha(conn.hypha1), self.hypha(conn.hypha2)) else {
+                continue;
+            };
+            if !h1.alive || !h2.alive {
+                continue;
+            }
+            let age_fade = (1.0 / (1.0 + (h1.age + h2.age) * 0.5 * 0.2)).clamp(0.2, 1.0);
+            let width = 1.0 + conn.strength * 2.0;
+            let base_alpha = (0.4 + pulse * 0.4) * age_fade;
+            let signal = conn.signal.min(1.0);
+            let (width, color) = if signal > 0.1 {
+                (
+                    width + signal,
+                    [1.0, 0.3 + signal * 0.4, 0.0, base_alpha * signal],
+                )
+            } else {
+                (
+                    width,
+                    [
+                        0.0,
+                        0.3 + conn.strength * 0.7,
+                        0.5,
+                        base_alpha * (0.5 + conn.strength * 0.5),
+                    ],
+                )
+            };
+            shapes.push(Shape::Line {
+                from: (h1.x * cell, h1.y * cell),
+                to: (h2.x * cell, h2.y * cell),
+                width,
+                color,
+            });
+        }
+        shapes
+    }
+
+    // Living hyphae as points colored by decay, stress and flow
+    pub(crate) fn hypha_shapes(&self) -> Vec<Shape> {
+        let cell = self.config.cell_size;
+        let flow = self.hypha_flow();
+        self.hyphae
+            .iter()
+            .enumerate()
+            .filter(|(_, h)| h.alive)
+            .map(|(idx, h)| {
+                let flow_radius = if flow[idx] > 0.01 {
+                    (flow[idx] * 10.0).min(1.0) * 1.5
+                } else {
+                    0.0
+                };
+                Shape::Circle {
+                    center: (h.x * cell, h.y * cell),
+                    radius: (2.0 + flow_radius + h.age * 0.1).min(4.0),
+                    color: hypha_color(h.senescence_factor, h.energy, flow[idx]),
+                }
+            })
+            .collect()
+    }
+
+    // Fruit bodies with energy transfer lines from nearby hyphae
+    pub(crate) fn fruit_body_shapes(&self) -> Vec<Shape> {
+        let cell = self.config.cell_size;
+        let (stem_w, stem_h) = (3.0, 10.0);
+        let transfer_radius = 20.0f32;
+        let mut shapes = Vec::new();
+        for f in &self.fruit_bodies {
+            let (px, py) = (f.x * cell, f.y * cell);
+            for h in self.hyphae.iter().filter(|h| h.alive && h.energy > 0.05) {
+                let (dx, dy) = (f.x - h.x, f.y - h.y);
+                let dist_sq = dx * dx + dy * dy;
+                if dist_sq < transfer_radius * transfer_radius && dist_sq > 0.1 {
+                    let intensity = (1.0 - dist_sq.sqrt() / transfer_radius).max(0.0) * h.energy;
+                    shapes.push(Shape::Line {
+                        from: (h.x * cell, h.y * cell),
+                        to: (px, py),
+                        width: 2.0 + intensity * 1.5,
+                        color: [1.0, 0.8, 0.2, (intensity * 0.6 + 0.2).min(0.8)],
+                    });
+                }
+            }
+
+            let energy = f.energy.clamp(0.0, 1.0);
+            let cap_size = 6.0 + energy * 4.0;
+            shapes.push(Shape::Rect {
+                x: px - stem_w / 2.0,
+                y: py - stem_h,
+                w: stem_w,
+                h: stem_h,
+                color: [0.9, 0.9, 0.8, 0.7 + energy * 0.2],
+            });
+            shapes.push(Shape::Circle {
+                center: (px, py - stem_h),
+                radius: cap_size,
+                color: [0.8 - energy * 0.3, 0.2 + energy * 0.4, 0.2, 0.9],
+            });
+            if energy > 0.3 {
+                shapes.push(Shape::Circle {
+                    center: (px, py - stem_h),
+                    radius: cap_size + 2.0,
+                    color: [1.0, 1.0, 0.5, (energy - 0.3) * 0.4],
+                });
+            }
+        }
+        shapes
+    }
+
+    // Average of a hypha value per grid cell (None where no living hypha is)
+    fn per_cell_average<F>(&self, value: F) -> Grid2D<Option<f32>>
+    where
+        F: Fn(&crate::hypha::Hypha) -> f32,
+    {
+        let grid_size = self.config.grid_size;
+        let mut sums = Grid2D::new(grid_size, grid_size, (0.0f32, 0u32));
+        for h in self.hyphae.iter().filter(|h| h.alive) {
+            let (xi, yi) = (h.x as usize, h.y as usize);
+            if xi < grid_size && yi < grid_size {
+                sums[xi][yi].0 += value(h);
+                sums[xi][yi].1 += 1;
+            }
+        }
+        sums.map(|&(sum, count)| (count > 0).then(|| sum / count as f32))
+    }
+
+    // Accumulated connection flow per hypha (what the window caches in hypha_flow_cache)
+    fn hypha_flow(&self) -> Vec<f32> {
+        let mut flow = vec![0.0f32; self.hyphae.len()];
+        for conn in &self.connections {
+            for id in [conn.hypha1, conn.hypha2] {
+                if let Some(slot) = self.hypha_slot(id) {
+                    flow[slot] += conn.flow_accumulator;
+                }
+            }
+        }
+        flow
+    }
+
+    // Growth probability shown by the growth heatmap
+    fn growth_probability(&self, x: usize, y: usize) -> f32 {
+        let total_nutrient = self.nutrients.sugar[x][y] + self.nutrients.nitrogen[x][y] * 0.5;
+        let moisture_factor = if self.config.soil_moisture_enabled {
+            let m = self.soil_moisture[x][y];
+            if m < 0.3 {
+                0.4 + (m / 0.3) * 0.4
+            } else if m <= 0.8 {
+                1.0
+            } else {
+                1.0 - (m - 0.8) / 0.2 * 0.3
+            }
+        } else {
+            1.0
+        };
+        let light_factor = if self.config.light_exposure_enabled {
+            1.0 - self.light_exposure[x][y] * self.config.light_growth_penalty
+        } else {
+            1.0
+        };
+        total_nutrient * moisture_factor * light_factor
+    }
+}
+
+// Zone colors of the window: red toxic, yellow competitor, brown deadwood
+pub(crate) fn zone_rgb(zone_type: ZoneType) -> Option<[f32; 3]> {
+    match zone_type {
+        ZoneType::Toxic => Some([1.0, 0.2, 0.2]),
+        ZoneType::Competitor => Some([0.8, 0.8, 0.2]),
+        ZoneType::Deadwood => Some([0.4, 0.3, 0.2]),
+        ZoneType::None => None,
+    }
+}
+
+// Hypha point color: senescence decay, then energy stress, then flow tint (as in the window)
+fn hypha_color(senescence: f32, energy: f32, flow: f32) -> [f32; 4] {
+    let mut color = [1.0f32, 1.0, 1.0, 0.8];
+    if senescence > 0.3 {
+        let decay = ((senescence - 0.3) / 0.7).min(1.0);
+        // Brown for early decay, grey for advanced decay
+        let (target, blend) = if decay < 0.5 {
+            ([0.545, 0.271, 0.075], decay * 2.0)
+        } else {
+            ([0.5, 0.5, 0.5], (decay - 0.5) * 2.0)
+        };
+        for c in 0..3 {
+            color[c] = color[c] * (1.0 - blend) + target[c] * blend;
+        }
+        color[3] *= 1.0 - decay * 0.5;
+    } else {
+        let stress = 1.0 - energy;
+        color = if stress > 0.3 {
+            [1.0, 0.5 + stress * 0.5, 0.2, color[3]]
+        } else {
+            [
+                0.8 + energy * 0.2,
+                0.8 + energy * 0.2,
+                0.9 + energy * 0.1,
+                color[3],
+            ]
+        };
+    }
+    if flow > 0.01 {
+        let n = (flow * 10.0).min(1.0);
+        color[1] = (color[1] + n * 0.5).min(1.0);
+        color[2] = (color[2] - n * 0.3).max(0.0);
+    }
+    color
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimulationConfig;
+    use crate::simulation::create_rng;
+
+    // A 40x40 colony after a few steps, so there are segments to draw
+    fn grown_simulation() -> Simulation {
+        let config = SimulationConfig {
+            grid_size: 40,
+            ..SimulationConfig::default()
+        };
+        let mut rng = create_rng(5);
+        let mut sim = Simulation::with_config(&mut rng, config);
+        for _ in 0..40 {
+            sim.step(&mut rng);
+        }
+        sim
+    }
+
+    /// Test that the image covers the grid at the requested scale and encodes as PNG
+    #[test]
+    fn test_render_dimensions() {
+        let sim = grown_simulation();
+        let side = (40.0 * sim.config.cell_size) as u32;
+        let full = sim.render_image(&RenderOptions::default());
+        assert_eq!(full.dimensions(), (side, side));
+
+        let half = sim.render_image(&RenderOptions {
+            scale: 0.5,
+            ..RenderOptions::default()
+        });
+        assert_eq!(half.dimensions(), (side / 2, side / 2));
+
+        let png = sim.render_png(&RenderOptions::default()).unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+    }
+
+    /// Test that a layer draws on top of an otherwise empty background
+    #[test]
+    fn test_render_layers() {
+        let sim = grown_simulation();
+        let background = RenderOptions::with_layers("", 1.0).unwrap();
+        let empty = sim.render_image(&background);
+        let segments = RenderOptions::with_layers("segments", 1.0).unwrap();
+        let drawn = sim.render_image(&segments);
+        let changed = empty
+            .pixels()
+            .zip(drawn.pixels())
+            .filter(|(a, b)| a != b)
+            .count();
+        assert!(changed > 0);
+    }
+
+    /// Test that unknown layers and out-of-range scales are rejected
+    #[test]
+    fn test_render_options_validation() {
+        assert!(RenderOptions::with_layers("nutrients,segments", 8.0).is_ok());
+        assert!(RenderOptions::with_layers("nutrients,clouds", 1.0).is_err());
+        assert!(RenderOptions::with_layers("nutrients", 0.0).is_err());
+        assert!(RenderOptions::with_layers("nutrients", 9.0).is_err());
+    }
+
+    /// Test that the pixel limit depends on the grid, not just the scale
+    #[test]
+    fn test_render_size_limit() {
+        let mut rng = create_rng(5);
+        let mut sim = Simulation::with_config(
+            &mut rng,
+            SimulationConfig {
+                grid_size: 40,
+                ..SimulationConfig::default()
+            },
+        );
+        let double = RenderOptions {
+            scale: 2.0,
+            ..RenderOptions::default()
+        };
+        assert!(sim.check_render_size(&double).is_ok());
+        sim.config.grid_size = 1000;
+        assert!(sim.check_render_size(&RenderOptions::default()).is_ok());
+        assert!(sim.check_render_size(&double).is_err());
+        assert!(sim.render_png(&double).is_err());
+
+        // Batch images and time-lapse frames shrink to fit instead
+        let fitted = RenderOptions {
+            scale: sim.fit_render_scale(2.0),
+            ..RenderOptions::default()
+        };
+        assert!(fitted.scale < 2.0);
+        assert!(sim.check_render_size(&fitted).is_ok());
+        assert_eq!(sim.fit_render_scale(0.5), 0.5);
+    }
+}
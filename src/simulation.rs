@@ -1,15 +1,24 @@
 use ::rand as external_rand;
-use external_rand::Rng;
+use external_rand::{Rng, SeedableRng};
 #[cfg(not(test))]
 #[cfg(feature = "ui")]
 use macroquad::prelude::*;
-use std::collections::HashSet;
+use rand_chacha::{ChaCha12Rng, ChaCha8Rng};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
 
 use crate::config::SimulationConfig;
+use crate::env_maps::EnvironmentMaps;
+use crate::grid::Grid2D;
+use crate::history::HistoryRecorder;
 use crate::hypha::Hypha;
 use crate::nutrients::{memory_gradient, nutrient_gradient, NutrientGrid};
+use crate::profile::{ProfileReport, ProfileStats, StepCounter, StepPhase, StepProfile};
 use crate::spore::Spore;
+use crate::timelapse::Timelapse;
 use crate::types::{Connection, FruitBody, Segment, Zone, ZoneType};
 use crate::weather::Weather;
 
@@ -21,33 +30,38 @@ pub fn set_headless_mode(headless: bool) {
     HEADLESS_MODE.store(headless, Ordering::Relaxed);
 }
 
-// Helper function to get FPS - use default for tests, actual FPS for runtime
-// In headless mode or when macroquad isn't initialized, returns a safe default
-#[cfg(test)]
-fn get_fps() -> f32 {
-    60.0 // Default FPS for tests
+#[cfg(all(not(test), feature = "ui"))]
+fn is_headless_mode() -> bool {
+    HEADLESS_MODE.load(Ordering::Relaxed)
 }
 
-#[cfg(not(test))]
-#[cfg(feature = "ui")]
-fn get_fps() -> f32 {
-    // If we're in headless mode, don't call macroquad
-    if HEADLESS_MODE.load(Ordering::Relaxed) {
-        return 60.0;
-    }
-
-    // Try to get FPS from macroquad, but fall back to default if not available
-    // This handles the case where UI features are compiled in but we're running headless
-    // Macroquad has thread-local state that panics if accessed from wrong thread/context
-    use std::panic;
-    panic::catch_unwind(|| macroquad::prelude::get_fps() as f32).unwrap_or(60.0)
-    // Default FPS if macroquad isn't available or panics
+/// Generator type of a run: ChaCha12 like rand's `StdRng`, but serializable, so snapshots
+/// can store it and a restored run continues with exactly the draws it would have made.
+pub type SimRng = ChaCha12Rng;
+
+/// Create the random number generator that drives a simulation run.
+/// Every random draw (initialization, weather, growth, spores) comes from this
+/// generator, directly or through per-hypha generators seeded from it, so the same
+/// seed and config reproduce the same run.
+pub fn create_rng(seed: u64) -> SimRng {
+    SimRng::seed_from_u64(seed)
 }
 
-#[cfg(not(test))]
-#[cfg(not(feature = "ui"))]
-fn get_fps() -> f32 {
-    60.0 // Default FPS for headless mode
+// Per-hypha generator for the parallel growth phases, derived from the step seed and the
+// hypha id so the draws don't depend on which thread handles the hypha. One is seeded per
+// hypha per step, so it is ChaCha8: cheaper than SimRng, and unlike SmallRng its output is
+// the same on every platform and rand version.
+fn hypha_rng(step_seed: u64, id: u64) -> ChaCha8Rng {
+    ChaCha8Rng::seed_from_u64(step_seed ^ id.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
+
+// What one hypha's senescence/branching pass produced, applied in slot order afterwards
+#[derive(Default)]
+struct TipOutcome {
+    transfer: Option<(usize, usize, f32)>, // (from, to parent, energy) exchange with the parent
+    branch: Option<Hypha>,
+    branch_segment: Option<Segment>, // Joins the parent to the new branch
+    trail: Option<Segment>,
 }
 
 #[inline]
@@ -55,55 +69,106 @@ fn in_bounds(x: f32, y: f32, grid_size: usize) -> bool {
     x >= 0.0 && y >= 0.0 && x < grid_size as f32 && y < grid_size as f32
 }
 
+/// Opt-in quality settings that trade accuracy for speed.
+/// They are recorded in the state so every run (UI, headless, tests) advances the same model.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QualitySettings {
+    /// Diffuse nutrients only on even frames
+    pub skip_diffusion_every_other_step: bool,
+}
+
+impl QualitySettings {
+    pub fn from_config(config: &SimulationConfig) -> Self {
+        Self {
+            skip_diffusion_every_other_step: config.lod_skip_diffusion,
+        }
+    }
+}
+
+/// Discrete events that happened during the most recent step.
+/// Short-lived events (spore bursts, fruit deaths) are recorded here so observers
+/// see them even if the affected objects are gone by the time they look at the state.
+#[derive(Clone, Debug, Default)]
+pub struct StepEvents {
+    pub spores_released: Vec<(f32, f32)>,
+    pub spores_germinated: Vec<(f32, f32)>,
+    pub fruit_spawned: Vec<(f32, f32)>,
+    pub fruit_died: Vec<(f32, f32)>,
+}
+
+impl StepEvents {
+    pub fn clear(&mut self) {
+        self.spores_released.clear();
+        self.spores_germinated.clear();
+        self.fruit_spawned.clear();
+        self.fruit_died.clear();
+    }
+}
+
+/// Buckets of the spatial hash grid along x and y for this config
+pub(crate) fn spatial_grid_size(config: &SimulationConfig) -> (usize, usize) {
+    let buckets = ((config.grid_size as f32) / config.cell_size).ceil() as usize;
+    (buckets, buckets)
+}
+
 // Simulation state - contains all mutable state data
+#[derive(Serialize, Deserialize)]
 pub struct SimulationState {
     pub nutrients: NutrientGrid,
     pub nutrients_back: NutrientGrid, // Double buffer for diffusion
-    pub nutrient_memory: Vec<Vec<f32>>, // Memory grid: decaying weights of nutrient locations
-    pub obstacles: Vec<Vec<bool>>,
-    pub hyphae: Vec<Hypha>,
+    pub nutrient_memory: Grid2D<f32>, // Memory grid: decaying weights of nutrient locations
+    pub obstacles: Grid2D<bool>,
+    pub hyphae: Vec<Hypha>, // Added and removed through add_hypha/remove_hypha so ids stay resolvable
+    pub next_hypha_id: u64, // Id given to the next new hypha
+    // Hypha id -> slot in `hyphae` (rebuilt after loading a snapshot)
+    #[serde(skip)]
+    pub hypha_slots: HashMap<u64, usize>,
     pub spores: Vec<Spore>,
     pub segments: Vec<Segment>,
     pub connections: Vec<Connection>,
-    pub connection_set: HashSet<(usize, usize)>, // Fast lookup for connections
+    pub connection_set: HashSet<(u64, u64)>, // Fast lookup for connections (see `Connection::key`)
     pub fruit_bodies: Vec<FruitBody>,
     pub fruit_cooldown_timer: f32,
     pub fruiting_failed_attempts: u32,
     pub frame_index: u64,
-    // Reusable spatial hash grid to avoid allocations
-    pub spatial_grid: Vec<Vec<Vec<usize>>>,
+    // Fixed time step advanced by each step (never derived from render FPS)
+    pub dt: f32,
+    pub quality: QualitySettings,
+    // Reusable spatial hash grid to avoid allocations (rebuilt every step, not saved in snapshots)
+    #[serde(skip)]
+    pub spatial_grid: Grid2D<Vec<usize>>,
     pub spatial_grid_nx: usize,
     pub spatial_grid_ny: usize,
     // Weather system
     pub weather: Weather,
     // Water flow field (for directional nutrient transport)
-    pub flow_velocity_x: Vec<Vec<f32>>, // Flow velocity in X direction
-    pub flow_velocity_y: Vec<Vec<f32>>, // Flow velocity in Y direction
+    pub flow_velocity_x: Grid2D<f32>, // Flow velocity in X direction
+    pub flow_velocity_y: Grid2D<f32>, // Flow velocity in Y direction
     // Mycelial density map (for self-inhibition)
-    pub density_map: Vec<Vec<f32>>, // Density map: tracks hyphae density per region
-    pub density_map_size: usize,    // Size of density map (grid_size * density_map_resolution)
+    pub density_map: Grid2D<f32>, // Density map: tracks hyphae density per region
+    pub density_map_size: usize,  // Size of density map (grid_size * density_map_resolution)
     // Contaminants/competitors zones
-    pub zones: Vec<Vec<Zone>>, // Zone grid: toxic zones, competitors, deadwood patches
+    pub zones: Grid2D<Zone>, // Zone grid: toxic zones, competitors, deadwood patches
     // Soil moisture system
-    pub soil_moisture: Vec<Vec<f32>>, // Soil moisture grid (0.0 = dry, 1.0 = saturated)
+    pub soil_moisture: Grid2D<f32>, // Soil moisture grid (0.0 = dry, 1.0 = saturated)
     // Light exposure system
-    pub light_exposure: Vec<Vec<f32>>, // Light exposure grid (0.0 = shaded, 1.0 = full sun)
+    pub light_exposure: Grid2D<f32>, // Light exposure grid (0.0 = shaded, 1.0 = full sun)
 }
 
 impl SimulationState {
     pub fn new(config: &SimulationConfig) -> Self {
         // Pre-allocate spatial grid
-        let cell_size = config.cell_size;
         let grid_size = config.grid_size;
-        let nx = ((grid_size as f32) / cell_size).ceil() as usize;
-        let ny = ((grid_size as f32) / cell_size).ceil() as usize;
-        let spatial_grid = vec![vec![Vec::new(); ny]; nx];
+        let (nx, ny) = spatial_grid_size(config);
+        let spatial_grid = Grid2D::new(nx, ny, Vec::new());
         Self {
             nutrients: NutrientGrid::new(grid_size),
             nutrients_back: NutrientGrid::new(grid_size),
-            nutrient_memory: vec![vec![0.0f32; grid_size]; grid_size],
-            obstacles: vec![vec![false; grid_size]; grid_size],
+            nutrient_memory: Grid2D::new(grid_size, grid_size, 0.0),
+            obstacles: Grid2D::new(grid_size, grid_size, false),
             hyphae: Vec::new(),
+            next_hypha_id: 0,
+            hypha_slots: HashMap::new(),
             spores: Vec::new(),
             segments: Vec::new(),
             connections: Vec::new(),
@@ -112,31 +177,77 @@ impl SimulationState {
             fruit_cooldown_timer: 0.0,
             fruiting_failed_attempts: 0,
             frame_index: 0,
+            dt: config.time_step,
+            quality: QualitySettings::from_config(config),
             spatial_grid,
             spatial_grid_nx: nx,
             spatial_grid_ny: ny,
             weather: Weather::new(),
-            flow_velocity_x: vec![vec![0.0f32; grid_size]; grid_size],
-            flow_velocity_y: vec![vec![0.0f32; grid_size]; grid_size],
+            flow_velocity_x: Grid2D::new(grid_size, grid_size, 0.0),
+            flow_velocity_y: Grid2D::new(grid_size, grid_size, 0.0),
             density_map_size: grid_size * config.density_map_resolution,
-            density_map: vec![
-                vec![0.0f32; grid_size * config.density_map_resolution];
-                grid_size * config.density_map_resolution
-            ],
-            zones: vec![
-                vec![
-                    Zone {
-                        zone_type: ZoneType::None,
-                        intensity: 0.0,
-                        age: 0.0,
-                    };
-                    grid_size
-                ];
-                grid_size
-            ],
-            soil_moisture: vec![vec![0.5f32; grid_size]; grid_size], // Start at moderate moisture
-            light_exposure: vec![vec![0.5f32; grid_size]; grid_size], // Start at moderate light
+            density_map: Grid2D::new(
+                grid_size * config.density_map_resolution,
+                grid_size * config.density_map_resolution,
+                0.0,
+            ),
+            zones: Grid2D::new(
+                grid_size,
+                grid_size,
+                Zone {
+                    zone_type: ZoneType::None,
+                    intensity: 0.0,
+                    age: 0.0,
+                },
+            ),
+            soil_moisture: Grid2D::new(grid_size, grid_size, 0.5), // Start at moderate moisture
+            light_exposure: Grid2D::new(grid_size, grid_size, 0.5), // Start at moderate light
+        }
+    }
+
+    /// Re-allocate the spatial hash buckets (they are not part of snapshots)
+    pub fn rebuild_spatial_grid(&mut self) {
+        self.spatial_grid = Grid2D::new(self.spatial_grid_nx, self.spatial_grid_ny, Vec::new());
+    }
+
+    /// Give a hypha the next unused id and append it. Returns the id.
+    pub fn add_hypha(&mut self, mut hypha: Hypha) -> u64 {
+        let id = self.next_hypha_id;
+        self.next_hypha_id += 1;
+        hypha.id = id;
+        self.hypha_slots.insert(id, self.hyphae.len());
+        self.hyphae.push(hypha);
+        id
+    }
+
+    /// Remove the hypha in `slot`. The last hypha moves into the slot (like `swap_remove`),
+    /// so remove several in descending slot order.
+    pub fn remove_hypha(&mut self, slot: usize) -> Hypha {
+        let removed = self.hyphae.swap_remove(slot);
+        self.hypha_slots.remove(&removed.id);
+        if let Some(moved) = self.hyphae.get(slot) {
+            self.hypha_slots.insert(moved.id, slot);
         }
+        removed
+    }
+
+    /// Current slot in `hyphae` of the hypha with this id (None once it was removed)
+    pub fn hypha_slot(&self, id: u64) -> Option<usize> {
+        self.hypha_slots.get(&id).copied()
+    }
+
+    pub fn hypha(&self, id: u64) -> Option<&Hypha> {
+        self.hypha_slot(id).map(|slot| &self.hyphae[slot])
+    }
+
+    /// Rebuild the id -> slot map (it is not part of snapshots)
+    pub fn rebuild_hypha_slots(&mut self) {
+        self.hypha_slots = self
+            .hyphae
+            .iter()
+            .enumerate()
+            .map(|(slot, h)| (h.id, slot))
+            .collect();
     }
 }
 
@@ -163,6 +274,20 @@ pub struct Simulation {
     pub speed_accumulator: f32,
     // Performance: Cache for visualization (computed once per frame)
     pub hypha_flow_cache: Vec<f32>, // Pre-computed flow values per hypha
+    // Events from the last step (for streaming observers, rebuilt every step)
+    pub events: StepEvents,
+    // Phase timings and work counters of the last step, and their rolling aggregates
+    // (not saved in snapshots)
+    pub profile: StepProfile,
+    pub profile_stats: ProfileStats,
+    pub profile_overlay_visible: bool, // Show the step profile overlay
+    // Time-series samples of run metrics (not saved in snapshots)
+    pub history: HistoryRecorder,
+    // Active time-lapse recording, if any (not saved in snapshots)
+    pub timelapse: Option<Timelapse>,
+    // Grids from the `*_map` config fields, loaded once and reapplied on reset
+    // (not saved in snapshots, which store the grids themselves)
+    pub env_maps: EnvironmentMaps,
     // Camera for pan/zoom (only in UI mode)
     #[cfg(feature = "ui")]
     pub camera: crate::camera::Camera,
@@ -190,8 +315,17 @@ impl Simulation {
         Self::with_config(rng, SimulationConfig::default())
     }
 
+    /// Create a simulation. Environment maps that fail to load are left out and the
+    /// generated grids are kept; use `try_with_config` to get the error instead.
     pub fn with_config<R: Rng>(rng: &mut R, config: SimulationConfig) -> Self {
-        Self::with_config_internal(rng, config, true)
+        let maps = EnvironmentMaps::load(&config).unwrap_or_default();
+        Self::with_config_internal(rng, config, maps, true)
+    }
+
+    /// Create a simulation, failing if an environment map can't be loaded.
+    pub fn try_with_config<R: Rng>(rng: &mut R, config: SimulationConfig) -> Result<Self, String> {
+        let maps = EnvironmentMaps::load(&config)?;
+        Ok(Self::with_config_internal(rng, config, maps, true))
     }
 
     // Internal function that allows skipping camera initialization for tests
@@ -200,25 +334,27 @@ impl Simulation {
     fn with_config_internal<R: Rng>(
         rng: &mut R,
         config: SimulationConfig,
+        maps: EnvironmentMaps,
         _init_camera: bool,
     ) -> Self {
         let grid_size = config.grid_size;
-        let camera_enabled_for_camera = config.camera_enabled;
         let mut state = SimulationState::new(&config);
         let center = grid_size as f32 / 2.0;
 
         // Initialize nutrients with realistic organic distribution
         Self::initialize_realistic_nutrients(&mut state.nutrients, grid_size, rng);
 
-        // Initialize obstacles
-        for _ in 0..config.obstacle_count {
-            let x = rng.gen_range(0..grid_size);
-            let y = rng.gen_range(0..grid_size);
-            state.obstacles[x][y] = true;
+        // Initialize obstacles (unless a map provides them)
+        if maps.obstacles.is_none() {
+            for _ in 0..config.obstacle_count {
+                let x = rng.gen_range(0..grid_size);
+                let y = rng.gen_range(0..grid_size);
+                state.obstacles[x][y] = true;
+            }
         }
 
-        // Initialize contaminant/competitor zones
-        if config.zones_enabled {
+        // Initialize contaminant/competitor zones (unless a map provides them)
+        if config.zones_enabled && maps.zones.is_none() {
             // Toxic zones
             for _ in 0..config.toxic_zone_count {
                 let center_x = rng.gen_range(0..grid_size);
@@ -231,7 +367,6 @@ impl Simulation {
                     radius,
                     ZoneType::Toxic,
                     0.8,
-                    grid_size,
                 );
             }
 
@@ -247,7 +382,6 @@ impl Simulation {
                     radius,
                     ZoneType::Competitor,
                     0.7,
-                    grid_size,
                 );
             }
 
@@ -263,7 +397,6 @@ impl Simulation {
                     radius,
                     ZoneType::Deadwood,
                     0.5,
-                    grid_size,
                 );
                 // Add nutrients to deadwood patches
                 for dx in -radius as isize..=radius as isize {
@@ -283,12 +416,16 @@ impl Simulation {
             }
         }
 
+        // Image maps replace the generated grids
+        maps.apply(&mut state);
+
         // Initialize hyphae
         state.hyphae = Vec::with_capacity(config.initial_hyphae_count);
         for _ in 0..config.initial_hyphae_count {
             let cx = center + rng.gen_range(-10.0..10.0);
             let cy = center + rng.gen_range(-10.0..10.0);
-            state.hyphae.push(Hypha {
+            state.add_hypha(Hypha {
+                id: 0,
                 x: cx,
                 y: cy,
                 prev_x: cx,
@@ -307,40 +444,16 @@ impl Simulation {
             });
         }
 
-        #[cfg(feature = "ui")]
-        let camera = crate::camera::Camera::new(camera_enabled_for_camera, &config);
-
-        Self {
-            state,
-            config,
-            paused: false,
-            connections_visible: true,
-            minimap_visible: false,
-            hyphae_visible: true,
-            memory_visible: false,
-            enhanced_visualization: false,
-            show_flow: true,
-            show_stress: true,
-            speed_multiplier: 1.0,
-            speed_accumulator: 0.0,
-            hypha_flow_cache: Vec::new(),
-            heatmap_nutrients: true, // Default: show nutrients
-            heatmap_moisture: false,
-            heatmap_age: false,
-            heatmap_flow: false,
-            heatmap_growth: false,
-            #[cfg(feature = "ui")]
-            camera,
-            #[cfg(feature = "ui")]
-            take_screenshot: false,
-            help_popup_visible: false,
-        }
+        let mut sim = Self::from_state(state, config);
+        sim.env_maps = maps;
+        sim
     }
 
     #[cfg(not(test))]
     fn with_config_internal<R: Rng>(
         rng: &mut R,
         config: SimulationConfig,
+        maps: EnvironmentMaps,
         _init_camera: bool,
     ) -> Self {
         let mut state = SimulationState::new(&config);
@@ -350,15 +463,17 @@ impl Simulation {
         // Initialize nutrients with realistic organic distribution
         Self::initialize_realistic_nutrients(&mut state.nutrients, grid_size, rng);
 
-        // Initialize obstacles
-        for _ in 0..config.obstacle_count {
-            let x = rng.gen_range(0..grid_size);
-            let y = rng.gen_range(0..grid_size);
-            state.obstacles[x][y] = true;
+        // Initialize obstacles (unless a map provides them)
+        if maps.obstacles.is_none() {
+            for _ in 0..config.obstacle_count {
+                let x = rng.gen_range(0..grid_size);
+                let y = rng.gen_range(0..grid_size);
+                state.obstacles[x][y] = true;
+            }
         }
 
-        // Initialize contaminant/competitor zones
-        if config.zones_enabled {
+        // Initialize contaminant/competitor zones (unless a map provides them)
+        if config.zones_enabled && maps.zones.is_none() {
             // Toxic zones
             for _ in 0..config.toxic_zone_count {
                 let center_x = rng.gen_range(0..grid_size);
@@ -371,7 +486,6 @@ impl Simulation {
                     radius,
                     ZoneType::Toxic,
                     0.8,
-                    grid_size,
                 );
             }
 
@@ -387,7 +501,6 @@ impl Simulation {
                     radius,
                     ZoneType::Competitor,
                     0.7,
-                    grid_size,
                 );
             }
 
@@ -403,7 +516,6 @@ impl Simulation {
                     radius,
                     ZoneType::Deadwood,
                     0.5,
-                    grid_size,
                 );
                 // Add nutrients to deadwood patches
                 for dx in -radius as isize..=radius as isize {
@@ -423,12 +535,16 @@ impl Simulation {
             }
         }
 
+        // Image maps replace the generated grids
+        maps.apply(&mut state);
+
         // Initialize hyphae
         state.hyphae = Vec::with_capacity(config.initial_hyphae_count);
         for _ in 0..config.initial_hyphae_count {
             let cx = center + rng.gen_range(-10.0..10.0);
             let cy = center + rng.gen_range(-10.0..10.0);
-            state.hyphae.push(Hypha {
+            state.add_hypha(Hypha {
+                id: 0,
                 x: cx,
                 y: cy,
                 prev_x: cx,
@@ -447,11 +563,17 @@ impl Simulation {
             });
         }
 
-        // Read camera_enabled before moving config
-        let camera_enabled = config.camera_enabled;
+        let mut sim = Self::from_state(state, config);
+        sim.env_maps = maps;
+        sim
+    }
 
-        // Clone config for camera (since we need to move original into Self)
-        let config_for_camera = config.clone();
+    /// Wrap an existing state (freshly initialized or restored from a snapshot)
+    /// with the default control flags
+    pub(crate) fn from_state(state: SimulationState, config: SimulationConfig) -> Self {
+        #[cfg(feature = "ui")]
+        let camera = crate::camera::Camera::new(config.camera_enabled, &config);
+        let history = HistoryRecorder::from_config(&config);
 
         Self {
             state,
@@ -468,13 +590,20 @@ impl Simulation {
             speed_accumulator: 0.0,
             help_popup_visible: false,
             hypha_flow_cache: Vec::new(),
+            events: StepEvents::default(),
+            profile: StepProfile::default(),
+            profile_stats: ProfileStats::default(),
+            profile_overlay_visible: false,
+            history,
+            timelapse: None,
+            env_maps: EnvironmentMaps::default(),
             heatmap_nutrients: true, // Default: show nutrients
             heatmap_moisture: false,
             heatmap_age: false,
             heatmap_flow: false,
             heatmap_growth: false,
             #[cfg(feature = "ui")]
-            camera: crate::camera::Camera::new(camera_enabled, &config_for_camera),
+            camera,
             #[cfg(feature = "ui")]
             take_screenshot: false,
         }
@@ -512,7 +641,7 @@ impl Simulation {
 
         // Create organic patches for sugar (more widespread, like plant matter)
         let sugar_patches = 8 + rng.gen_range(0..5);
-        let mut sugar_field = vec![vec![0.0f32; grid_size]; grid_size];
+        let mut sugar_field = Grid2D::new(grid_size, grid_size, 0.0f32);
 
         for _ in 0..sugar_patches {
             let patch_x = rng.gen_range(0.0..grid_size as f32);
@@ -521,7 +650,6 @@ impl Simulation {
             let patch_intensity = rng.gen_range(0.4..0.9);
             let seed = rng.gen::<u64>();
 
-            #[allow(clippy::needless_range_loop)]
             for x in 0..grid_size {
                 for y in 0..grid_size {
                     let dx = x as f32 - patch_x;
@@ -539,7 +667,7 @@ impl Simulation {
 
         // Create concentrated patches for nitrogen (rarer, like animal waste or nitrogen-fixing zones)
         let nitrogen_patches = 3 + rng.gen_range(0..4);
-        let mut nitrogen_field = vec![vec![0.0f32; grid_size]; grid_size];
+        let mut nitrogen_field = Grid2D::new(grid_size, grid_size, 0.0f32);
 
         for _ in 0..nitrogen_patches {
             let patch_x = rng.gen_range(0.0..grid_size as f32);
@@ -548,7 +676,6 @@ impl Simulation {
             let patch_intensity = rng.gen_range(0.5..1.0);
             let seed = rng.gen::<u64>();
 
-            #[allow(clippy::needless_range_loop)]
             for x in 0..grid_size {
                 for y in 0..grid_size {
                     let dx = x as f32 - patch_x;
@@ -581,33 +708,44 @@ impl Simulation {
     }
 
     fn create_zone(
-        zones: &mut Vec<Vec<Zone>>,
+        zones: &mut Grid2D<Zone>,
         center_x: usize,
         center_y: usize,
         radius: f32,
         zone_type: ZoneType,
         base_intensity: f32,
-        grid_size: usize,
     ) {
         let radius_sq = radius * radius;
-        for x in 0..grid_size {
-            for y in 0..grid_size {
-                let dx = x as f32 - center_x as f32;
-                let dy = y as f32 - center_y as f32;
-                let dist_sq = dx * dx + dy * dy;
-                if dist_sq <= radius_sq {
-                    let dist = dist_sq.sqrt();
-                    let intensity = base_intensity * (1.0 - (dist / radius).min(1.0));
-                    if intensity > zones[x][y].intensity {
-                        zones[x][y].zone_type = zone_type;
-                        zones[x][y].intensity = intensity;
-                        zones[x][y].age = 0.0;
-                    }
+        let cells: Vec<_> = zones
+            .neighborhood(center_x, center_y, radius.max(0.0).ceil() as usize)
+            .collect();
+        for (x, y) in cells {
+            let dx = x as f32 - center_x as f32;
+            let dy = y as f32 - center_y as f32;
+            let dist_sq = dx * dx + dy * dy;
+            if dist_sq <= radius_sq {
+                let dist = dist_sq.sqrt();
+                let intensity = base_intensity * (1.0 - (dist / radius).min(1.0));
+                let zone = &mut zones[(x, y)];
+                if intensity > zone.intensity {
+                    zone.zone_type = zone_type;
+                    zone.intensity = intensity;
+                    zone.age = 0.0;
                 }
             }
         }
     }
 
+    /// Swap in a new config on a running simulation.
+    /// Callers must only use this for fields that are safe to change live
+    /// (see `config::FieldEffect`); grid sizes are not re-allocated.
+    pub fn update_config(&mut self, config: SimulationConfig) {
+        self.state.dt = config.time_step;
+        self.state.quality = QualitySettings::from_config(&config);
+        self.history.configure(&config);
+        self.config = config;
+    }
+
     pub fn toggle_pause(&mut self) {
         self.paused = !self.paused;
     }
@@ -656,6 +794,14 @@ impl Simulation {
     pub fn toggle_help_popup(&mut self) {
         self.help_popup_visible = !self.help_popup_visible;
     }
+    pub fn toggle_profile_overlay(&mut self) {
+        self.profile_overlay_visible = !self.profile_overlay_visible;
+    }
+
+    /// Per-phase timings and work counters: last step, recent window and run means
+    pub fn profile_report(&self) -> ProfileReport {
+        self.profile_stats.report(self.state.frame_index)
+    }
 
     pub fn increase_speed(&mut self) {
         self.speed_multiplier = (self.speed_multiplier * 1.5).min(10.0);
@@ -668,6 +814,7 @@ impl Simulation {
     }
     pub fn reset<R: Rng>(&mut self, rng: &mut R) {
         self.state.hyphae.clear();
+        self.state.hypha_slots.clear();
         self.state.spores.clear();
         self.state.segments.clear();
         self.state.connections.clear();
@@ -675,14 +822,12 @@ impl Simulation {
         self.state.fruit_bodies.clear();
         self.state.fruit_cooldown_timer = 0.0;
         self.state.fruiting_failed_attempts = 0;
+        // Profile run means start over with the new colony
+        self.profile_stats.clear();
 
         // Network Intelligence: Clear memory
         if self.config.memory_enabled {
-            for x in 0..self.config.grid_size {
-                for y in 0..self.config.grid_size {
-                    self.state.nutrient_memory[x][y] = 0.0;
-                }
-            }
+            self.state.nutrient_memory.fill(0.0);
         }
 
         // Regenerate nutrients with new realistic distribution
@@ -693,10 +838,13 @@ impl Simulation {
             self.config.grid_size,
             rng,
         );
+        // Nutrient maps come back too (obstacles, zones, moisture and light are kept as they are)
+        self.env_maps.apply_nutrients(&mut self.state.nutrients);
 
         let cx = self.config.grid_size as f32 / 2.0;
         let cy = self.config.grid_size as f32 / 2.0;
-        self.state.hyphae.push(Hypha {
+        self.state.add_hypha(Hypha {
+            id: 0,
             x: cx,
             y: cy,
             prev_x: cx,
@@ -718,7 +866,8 @@ impl Simulation {
         self.state.segments.clear();
     }
     pub fn spawn_hypha_at<R: Rng>(&mut self, rng: &mut R, gx: f32, gy: f32) {
-        self.state.hyphae.push(Hypha {
+        self.state.add_hypha(Hypha {
+            id: 0,
             x: gx,
             y: gy,
             prev_x: gx,
@@ -769,6 +918,73 @@ impl Simulation {
         self.state.nutrients.add_nitrogen(gx, gy, 1.0);
     }
 
+    /// Grid cells within `radius` of (gx, gy), clipped to the grid (radius 0 = just that cell)
+    fn cells_in_radius(&self, gx: usize, gy: usize, radius: f32) -> Vec<(usize, usize)> {
+        let radius = radius.max(0.0);
+        self.state
+            .obstacles
+            .neighborhood(gx, gy, radius.ceil() as usize)
+            .filter(|&(x, y)| {
+                let dx = x as isize - gx as isize;
+                let dy = y as isize - gy as isize;
+                ((dx * dx + dy * dy) as f32).sqrt() <= radius
+            })
+            .collect()
+    }
+
+    /// Place (or clear) obstacles in a disc. Returns the number of cells touched.
+    pub fn set_obstacles(&mut self, gx: usize, gy: usize, radius: f32, present: bool) -> usize {
+        let cells = self.cells_in_radius(gx, gy, radius);
+        for &(x, y) in &cells {
+            self.state.obstacles[x][y] = present;
+        }
+        cells.len()
+    }
+
+    /// Paint a zone of uniform intensity in a disc (`ZoneType::None` erases zones).
+    /// Returns the number of cells touched.
+    pub fn paint_zone(
+        &mut self,
+        gx: usize,
+        gy: usize,
+        radius: f32,
+        zone_type: ZoneType,
+        intensity: f32,
+    ) -> usize {
+        let cells = self.cells_in_radius(gx, gy, radius);
+        let intensity = if zone_type == ZoneType::None {
+            0.0
+        } else {
+            intensity.clamp(0.0, 1.0)
+        };
+        for &(x, y) in &cells {
+            self.state.zones[x][y] = Zone {
+                zone_type,
+                intensity,
+                age: 0.0,
+            };
+        }
+        cells.len()
+    }
+
+    /// Set soil moisture to `value` in a disc. Returns the number of cells touched.
+    pub fn set_moisture_region(&mut self, gx: usize, gy: usize, radius: f32, value: f32) -> usize {
+        let cells = self.cells_in_radius(gx, gy, radius);
+        for &(x, y) in &cells {
+            self.state.soil_moisture[x][y] = value.clamp(0.0, 1.0);
+        }
+        cells.len()
+    }
+
+    /// Set light exposure to `value` in a disc. Returns the number of cells touched.
+    pub fn set_light_region(&mut self, gx: usize, gy: usize, radius: f32, value: f32) -> usize {
+        let cells = self.cells_in_radius(gx, gy, radius);
+        for &(x, y) in &cells {
+            self.state.light_exposure[x][y] = value.clamp(0.0, 1.0);
+        }
+        cells.len()
+    }
+
     fn spawn_fruit_body_at<R: Rng>(
         &mut self,
         rng: &mut R,
@@ -803,6 +1019,7 @@ impl Simulation {
         let initial_release_age =
             (lifespan * self.config.fruiting_spore_release_fraction).clamp(0.0, lifespan);
 
+        self.events.fruit_spawned.push((cx, cy));
         self.state.fruit_bodies.push(FruitBody {
             x: cx,
             y: cy,
@@ -878,26 +1095,26 @@ impl Simulation {
     }
 
     pub fn step<R: Rng>(&mut self, rng: &mut R) {
+        let step_started = Instant::now();
         self.state.frame_index = self.state.frame_index.wrapping_add(1);
+        self.events.clear();
+        self.profile.clear();
 
         // Weather: Update weather conditions
+        let phase_started = Instant::now();
         if self.config.weather_enabled {
-            let fps = get_fps();
-            let dt = 1.0 / fps.max(1.0);
+            let dt = self.state.dt;
             self.state.weather.seasonal_cycle_enabled = self.config.seasonal_cycles_enabled;
             self.state.weather.update(dt, rng);
         }
+        self.profile.record(StepPhase::Weather, phase_started);
 
         // Update soil moisture system
+        let phase_started = Instant::now();
         if self.config.soil_moisture_enabled {
             // Moisture diffusion (spread moisture to neighbors)
             let grid_size = self.config.grid_size;
-            let mut moisture_back = vec![vec![0.0f32; grid_size]; grid_size];
-            for x in 0..grid_size {
-                for y in 0..grid_size {
-                    moisture_back[x][y] = self.state.soil_moisture[x][y];
-                }
-            }
+            let moisture_back = self.state.soil_moisture.clone();
 
             for x in 1..grid_size - 1 {
                 for y in 1..grid_size - 1 {
@@ -912,33 +1129,33 @@ impl Simulation {
                         + avg * self.config.moisture_diffusion_rate;
                 }
             }
+            self.profile.add(
+                StepCounter::MoistureCells,
+                (grid_size.saturating_sub(2) as u64).pow(2),
+            );
 
             // Rain adds moisture
             if self.config.weather_enabled && self.state.weather.rain > 0.1 {
                 let rain_moisture = self.state.weather.rain * self.config.moisture_rain_gain;
-                for x in 0..grid_size {
-                    for y in 0..grid_size {
-                        self.state.soil_moisture[x][y] =
-                            (self.state.soil_moisture[x][y] + rain_moisture).min(1.0);
-                    }
+                for moisture in self.state.soil_moisture.iter_mut() {
+                    *moisture = (*moisture + rain_moisture).min(1.0);
                 }
             }
 
             // Moisture decay (evaporation)
-            for x in 0..grid_size {
-                for y in 0..grid_size {
-                    self.state.soil_moisture[x][y] *= self.config.moisture_decay_rate;
-                    // Keep minimum moisture based on humidity
-                    if self.config.weather_enabled {
-                        let min_moisture = self.state.weather.humidity * 0.3;
-                        self.state.soil_moisture[x][y] =
-                            self.state.soil_moisture[x][y].max(min_moisture);
-                    }
+            for moisture in self.state.soil_moisture.iter_mut() {
+                *moisture *= self.config.moisture_decay_rate;
+                // Keep minimum moisture based on humidity
+                if self.config.weather_enabled {
+                    let min_moisture = self.state.weather.humidity * 0.3;
+                    *moisture = moisture.max(min_moisture);
                 }
             }
         }
+        self.profile.record(StepPhase::Moisture, phase_started);
 
         // Performance: Growth limits - remove excess hyphae if over limit
+        let phase_started = Instant::now();
         if self.config.max_hyphae > 0 && self.state.hyphae.len() > self.config.max_hyphae {
             // Remove oldest/weakest hyphae first
             let excess = self.state.hyphae.len() - self.config.max_hyphae;
@@ -973,7 +1190,7 @@ impl Simulation {
             indices_to_remove.reverse();
             for &idx in &indices_to_remove {
                 if idx < self.state.hyphae.len() {
-                    self.state.hyphae.swap_remove(idx);
+                    self.state.remove_hypha(idx);
                 }
             }
         }
@@ -983,26 +1200,34 @@ impl Simulation {
             self.hypha_flow_cache.clear();
             self.hypha_flow_cache.resize(self.state.hyphae.len(), 0.0);
             for conn in &self.state.connections {
-                if conn.hypha1 < self.hypha_flow_cache.len() {
-                    self.hypha_flow_cache[conn.hypha1] += conn.flow_accumulator;
-                }
-                if conn.hypha2 < self.hypha_flow_cache.len() {
-                    self.hypha_flow_cache[conn.hypha2] += conn.flow_accumulator;
+                for id in [conn.hypha1, conn.hypha2] {
+                    if let Some(slot) = self.state.hypha_slot(id) {
+                        self.hypha_flow_cache[slot] += conn.flow_accumulator;
+                    }
                 }
             }
         }
 
+        self.profile.record(StepPhase::Growth, phase_started);
+
         // Network Intelligence: Decay memory
         // Performance: Memory decay is already fast (O(n²) but simple operations)
         // Spatial culling and LOD provide better performance gains
+        let phase_started = Instant::now();
         if self.config.memory_enabled {
             let decay_rate = self.config.memory_decay_rate;
-            for x in 0..self.config.grid_size {
-                for y in 0..self.config.grid_size {
-                    self.state.nutrient_memory[x][y] *= decay_rate;
-                }
+            for memory in self.state.nutrient_memory.iter_mut() {
+                *memory *= decay_rate;
             }
+            self.profile.add(
+                StepCounter::MemoryCells,
+                self.state.nutrient_memory.len() as u64,
+            );
         }
+        self.profile.record(StepPhase::MemoryDecay, phase_started);
+
+        // Growth resumes (its timer accumulates)
+        let phase_started = Instant::now();
 
         // Network Intelligence: Decay signals on hyphae
         if self.config.signal_propagation_enabled {
@@ -1034,14 +1259,16 @@ impl Simulation {
             let buckets = &mut self.state.spatial_grid;
 
             // Clear all buckets
-            for row in buckets.iter_mut() {
-                for bucket in row.iter_mut() {
-                    bucket.clear();
-                }
+            for bucket in buckets.iter_mut() {
+                bucket.clear();
             }
 
-            // Build spatial hash grid and snapshot positions
+            // Build spatial hash grid and snapshot positions (one per slot, parent resolved to its slot)
             for (i, h) in self.state.hyphae.iter().enumerate() {
+                let parent_slot = h
+                    .parent
+                    .and_then(|id| self.state.hypha_slots.get(&id).copied());
+                hyphae_positions.push((h.x, h.y, h.alive, h.energy, parent_slot));
                 if !h.alive {
                     continue;
                 }
@@ -1054,395 +1281,437 @@ impl Simulation {
                         buckets[bxu][byu].push(i);
                     }
                 }
-                // Store snapshot for this hypha
-                hyphae_positions.push((h.x, h.y, h.alive, h.energy, h.parent));
-            }
-            // Pad to match indices
-            while hyphae_positions.len() < hyphae_len {
-                hyphae_positions.push((0.0, 0.0, false, 0.0, None));
             }
 
-            for (idx, h) in self.state.hyphae[..hyphae_len].iter_mut().enumerate() {
-                if !h.alive {
-                    continue;
-                }
+            // Tip growth runs in three phases so the per-hypha work spreads across cores: a parallel
+            // move phase, a serial uptake phase (hyphae sharing a cell draw on its nutrients in slot
+            // order) and a parallel senescence/branching phase. Each hypha draws from its own RNG,
+            // seeded from the step seed and its id, so the result doesn't depend on the thread count.
+            let step_seed: u64 = rng.gen();
+            let tip_buckets: &Grid2D<Vec<usize>> = buckets;
 
-                h.prev_x = h.x;
-                h.prev_y = h.y;
-
-                let (mut gx, mut gy) =
-                    nutrient_gradient(&self.state.nutrients, h.x, h.y, self.config.grid_size);
-
-                // Network Intelligence: Blend memory gradient into growth direction
-                if self.config.memory_enabled && self.config.memory_influence > 0.0 {
-                    let (mx, my) = memory_gradient(
-                        &self.state.nutrient_memory,
-                        h.x,
-                        h.y,
-                        self.config.grid_size,
-                    );
-                    let mem_mag = (mx * mx + my * my).sqrt();
-                    if mem_mag > 0.01 {
-                        // Blend memory gradient with nutrient gradient
-                        let influence = self.config.memory_influence;
-                        gx = gx * (1.0 - influence) + mx * influence;
-                        gy = gy * (1.0 - influence) + my * influence;
+            // Move phase: steering, collision and density checks, growth multipliers, obstacles.
+            // Each tip also reports its spatial-grid work (buckets scanned, neighbors checked).
+            let (tip_rngs, tip_work): (Vec<Option<ChaCha8Rng>>, Vec<(u64, u64)>) = self
+                .state
+                .hyphae[..hyphae_len]
+                .par_iter_mut()
+                .enumerate()
+                .map(|(idx, h)| {
+                    if !h.alive {
+                        return (None, (0, 0));
                     }
-                }
-
-                // Network Intelligence: Signal influence on growth direction
-                if self.config.signal_propagation_enabled && h.signal_received > 0.1 {
-                    // Signals can bias growth toward remembered nutrient locations
-                    if let Some((mem_x, mem_y)) = h.last_nutrient_location {
-                        let dx = mem_x - h.x;
-                        let dy = mem_y - h.y;
-                        let dist = (dx * dx + dy * dy).sqrt();
-                        if dist > 1.0 {
-                            let signal_strength = h.signal_received.min(1.0) * 0.3;
-                            gx += (dx / dist) * signal_strength;
-                            gy += (dy / dist) * signal_strength;
+                    let mut rng = hypha_rng(step_seed, h.id);
+
+                    h.prev_x = h.x;
+                    h.prev_y = h.y;
+
+                    let (mut gx, mut gy) =
+                        nutrient_gradient(&self.state.nutrients, h.x, h.y, self.config.grid_size);
+
+                    // Network Intelligence: Blend memory gradient into growth direction
+                    if self.config.memory_enabled && self.config.memory_influence > 0.0 {
+                        let (mx, my) = memory_gradient(
+                            &self.state.nutrient_memory,
+                            h.x,
+                            h.y,
+                            self.config.grid_size,
+                        );
+                        let mem_mag = (mx * mx + my * my).sqrt();
+                        if mem_mag > 0.01 {
+                            // Blend memory gradient with nutrient gradient
+                            let influence = self.config.memory_influence;
+                            gx = gx * (1.0 - influence) + mx * influence;
+                            gy = gy * (1.0 - influence) + my * influence;
                         }
                     }
-                }
 
-                // Zone avoidance: detect nearby toxic/competitor zones and steer away
-                if self.config.zones_enabled && in_bounds(h.x, h.y, self.config.grid_size) {
-                    let xi = h.x as usize;
-                    let yi = h.y as usize;
+                    // Network Intelligence: Signal influence on growth direction
+                    if self.config.signal_propagation_enabled && h.signal_received > 0.1 {
+                        // Signals can bias growth toward remembered nutrient locations
+                        if let Some((mem_x, mem_y)) = h.last_nutrient_location {
+                            let dx = mem_x - h.x;
+                            let dy = mem_y - h.y;
+                            let dist = (dx * dx + dy * dy).sqrt();
+                            if dist > 1.0 {
+                                let signal_strength = h.signal_received.min(1.0) * 0.3;
+                                gx += (dx / dist) * signal_strength;
+                                gy += (dy / dist) * signal_strength;
+                            }
+                        }
+                    }
 
-                    // Check surrounding zones for avoidance
-                    let detection_radius = 5.0;
-                    let mut zone_repulsion_x = 0.0;
-                    let mut zone_repulsion_y = 0.0;
+                    // Zone avoidance: detect nearby toxic/competitor zones and steer away
+                    if self.config.zones_enabled && in_bounds(h.x, h.y, self.config.grid_size) {
+                        let xi = h.x as usize;
+                        let yi = h.y as usize;
 
-                    for dx in -2..=2 {
-                        for dy in -2..=2 {
-                            let check_x = (xi as isize + dx)
-                                .max(0)
-                                .min(self.config.grid_size as isize - 1)
-                                as usize;
-                            let check_y = (yi as isize + dy)
-                                .max(0)
-                                .min(self.config.grid_size as isize - 1)
-                                as usize;
-                            let check_zone = &self.state.zones[check_x][check_y];
-
-                            match check_zone.zone_type {
-                                ZoneType::Toxic | ZoneType::Competitor => {
-                                    let dist = ((dx * dx + dy * dy) as f32).sqrt();
-                                    if dist < detection_radius && dist > 0.1 {
-                                        // Repulsion from zone (steer away)
-                                        let repulsion_strength =
-                                            check_zone.intensity / (dist * dist + 0.1);
-                                        zone_repulsion_x -=
-                                            (dx as f32 / dist) * repulsion_strength * 0.3;
-                                        zone_repulsion_y -=
-                                            (dy as f32 / dist) * repulsion_strength * 0.3;
+                        // Check surrounding zones for avoidance
+                        let detection_radius = 5.0;
+                        let mut zone_repulsion_x = 0.0;
+                        let mut zone_repulsion_y = 0.0;
+
+                        for dx in -2..=2 {
+                            for dy in -2..=2 {
+                                let check_x = (xi as isize + dx)
+                                    .max(0)
+                                    .min(self.config.grid_size as isize - 1)
+                                    as usize;
+                                let check_y = (yi as isize + dy)
+                                    .max(0)
+                                    .min(self.config.grid_size as isize - 1)
+                                    as usize;
+                                let check_zone = &self.state.zones[check_x][check_y];
+
+                                match check_zone.zone_type {
+                                    ZoneType::Toxic | ZoneType::Competitor => {
+                                        let dist = ((dx * dx + dy * dy) as f32).sqrt();
+                                        if dist < detection_radius && dist > 0.1 {
+                                            // Repulsion from zone (steer away)
+                                            let repulsion_strength =
+                                                check_zone.intensity / (dist * dist + 0.1);
+                                            zone_repulsion_x -=
+                                                (dx as f32 / dist) * repulsion_strength * 0.3;
+                                            zone_repulsion_y -=
+                                                (dy as f32 / dist) * repulsion_strength * 0.3;
+                                        }
                                     }
+                                    _ => {}
                                 }
-                                _ => {}
                             }
                         }
-                    }
 
-                    // Apply zone repulsion to gradient (hyphae avoid dangerous zones)
-                    if zone_repulsion_x != 0.0 || zone_repulsion_y != 0.0 {
-                        gx += zone_repulsion_x;
-                        gy += zone_repulsion_y;
+                        // Apply zone repulsion to gradient (hyphae avoid dangerous zones)
+                        if zone_repulsion_x != 0.0 || zone_repulsion_y != 0.0 {
+                            gx += zone_repulsion_x;
+                            gy += zone_repulsion_y;
+                        }
                     }
-                }
 
-                let grad_mag = (gx * gx + gy * gy).sqrt();
-                // Only apply gradient steering if gradient is significant (avoid noise from small gradients)
-                const MIN_GRADIENT_MAG: f32 = 0.08; // Threshold to ignore numerical noise (increased to avoid bias)
-                if grad_mag > MIN_GRADIENT_MAG {
-                    // Add tropism global bias only when gradient is weak (subtle drift)
-                    if grad_mag < 0.15 {
-                        let tx = self.config.tropism_angle.cos() * self.config.tropism_strength;
-                        let ty = self.config.tropism_angle.sin() * self.config.tropism_strength;
-                        gx += tx;
-                        gy += ty;
-                        let new_grad_mag = (gx * gx + gy * gy).sqrt();
-                        if new_grad_mag > MIN_GRADIENT_MAG {
+                    let grad_mag = (gx * gx + gy * gy).sqrt();
+                    // Only apply gradient steering if gradient is significant (avoid noise from small gradients)
+                    const MIN_GRADIENT_MAG: f32 = 0.08; // Threshold to ignore numerical noise (increased to avoid bias)
+                    if grad_mag > MIN_GRADIENT_MAG {
+                        // Add tropism global bias only when gradient is weak (subtle drift)
+                        if grad_mag < 0.15 {
+                            let tx = self.config.tropism_angle.cos() * self.config.tropism_strength;
+                            let ty = self.config.tropism_angle.sin() * self.config.tropism_strength;
+                            gx += tx;
+                            gy += ty;
+                            let new_grad_mag = (gx * gx + gy * gy).sqrt();
+                            if new_grad_mag > MIN_GRADIENT_MAG {
+                                let grad_angle = gy.atan2(gx);
+                                h.angle +=
+                                    (grad_angle - h.angle) * self.config.gradient_steering_strength;
+                            }
+                        } else {
                             let grad_angle = gy.atan2(gx);
                             h.angle +=
                                 (grad_angle - h.angle) * self.config.gradient_steering_strength;
                         }
-                    } else {
-                        let grad_angle = gy.atan2(gx);
-                        h.angle += (grad_angle - h.angle) * self.config.gradient_steering_strength;
-                    }
-                }
-                // Apply random wander - this should be symmetric, but increase it slightly when gradient is weak
-                let wander_boost = if grad_mag < MIN_GRADIENT_MAG {
-                    1.5
-                } else {
-                    1.0
-                };
-                h.angle += rng
-                    .gen_range(-self.config.angle_wander_range..self.config.angle_wander_range)
-                    * wander_boost;
-
-                // Combined neighbor density and collision check in single iteration
-                let mut neighbor_count = 0.0f32;
-                let mut too_close = false;
-                let bx = (h.x / cell_size).floor() as isize;
-                let by = (h.y / cell_size).floor() as isize;
-                let density_check_dist_sq = self.config.hyphae_avoidance_distance_sq() * 4.0;
-                let collision_check_dist_sq = self.config.hyphae_avoidance_distance_sq();
-
-                let new_x = h.x + h.angle.cos() * self.config.step_size;
-                let new_y = h.y + h.angle.sin() * self.config.step_size;
-
-                for gx in (bx - 1)..=(bx + 1) {
-                    if too_close {
-                        break;
-                    }
-                    if gx < 0 {
-                        continue;
-                    }
-                    let gux = gx as usize;
-                    if gux >= nx {
-                        continue;
                     }
-                    for gy in (by - 1)..=(by + 1) {
+                    // Apply random wander - this should be symmetric, but increase it slightly when gradient is weak
+                    let wander_boost = if grad_mag < MIN_GRADIENT_MAG {
+                        1.5
+                    } else {
+                        1.0
+                    };
+                    h.angle += rng
+                        .gen_range(-self.config.angle_wander_range..self.config.angle_wander_range)
+                        * wander_boost;
+
+                    // Combined neighbor density and collision check in single iteration
+                    let mut neighbor_count = 0.0f32;
+                    let mut grid_queries = 0u64;
+                    let mut neighbor_checks = 0u64;
+                    let mut too_close = false;
+                    let bx = (h.x / cell_size).floor() as isize;
+                    let by = (h.y / cell_size).floor() as isize;
+                    let density_check_dist_sq = self.config.hyphae_avoidance_distance_sq() * 4.0;
+                    let collision_check_dist_sq = self.config.hyphae_avoidance_distance_sq();
+
+                    let new_x = h.x + h.angle.cos() * self.config.step_size;
+                    let new_y = h.y + h.angle.sin() * self.config.step_size;
+
+                    for gx in (bx - 1)..=(bx + 1) {
                         if too_close {
                             break;
                         }
-                        if gy < 0 {
+                        if gx < 0 {
                             continue;
                         }
-                        let guy = gy as usize;
-                        if guy >= ny {
+                        let gux = gx as usize;
+                        if gux >= nx {
                             continue;
                         }
-                        for &other_idx in &buckets[gux][guy] {
-                            if other_idx == idx || other_idx >= hyphae_positions.len() {
-                                continue;
+                        for gy in (by - 1)..=(by + 1) {
+                            if too_close {
+                                break;
                             }
-                            let (other_x, other_y, other_alive, _, _) = hyphae_positions[other_idx];
-                            if !other_alive {
+                            if gy < 0 {
                                 continue;
                             }
-                            // Density check
-                            let dx = h.x - other_x;
-                            let dy = h.y - other_y;
-                            let dist2 = dx * dx + dy * dy;
-                            if dist2 < density_check_dist_sq {
-                                neighbor_count += 1.0;
+                            let guy = gy as usize;
+                            if guy >= ny {
+                                continue;
                             }
-                            // Collision check
-                            if !too_close {
-                                let dx_new = new_x - other_x;
-                                let dy_new = new_y - other_y;
-                                let dist2_new = dx_new * dx_new + dy_new * dy_new;
-                                if dist2_new < collision_check_dist_sq && dist2_new > 0.001 {
-                                    too_close = true;
-                                    break;
+                            grid_queries += 1;
+                            for &other_idx in &tip_buckets[gux][guy] {
+                                if other_idx == idx || other_idx >= hyphae_positions.len() {
+                                    continue;
+                                }
+                                neighbor_checks += 1;
+                                let (other_x, other_y, other_alive, _, _) =
+                                    hyphae_positions[other_idx];
+                                if !other_alive {
+                                    continue;
+                                }
+                                // Density check
+                                let dx = h.x - other_x;
+                                let dy = h.y - other_y;
+                                let dist2 = dx * dx + dy * dy;
+                                if dist2 < density_check_dist_sq {
+                                    neighbor_count += 1.0;
+                                }
+                                // Collision check
+                                if !too_close {
+                                    let dx_new = new_x - other_x;
+                                    let dy_new = new_y - other_y;
+                                    let dist2_new = dx_new * dx_new + dy_new * dy_new;
+                                    if dist2_new < collision_check_dist_sq && dist2_new > 0.001 {
+                                        too_close = true;
+                                        break;
+                                    }
                                 }
                             }
                         }
                     }
-                }
-                let density_slow = 1.0 / (1.0 + 0.05 * neighbor_count);
-
-                // Mycelial Density + Self-Inhibition: Check density map for growth inhibition
-                let density_inhibition = if self.config.density_inhibition_enabled {
-                    let density_map_size = self.state.density_map_size;
-                    let density_x = ((h.x * self.config.density_map_resolution as f32) as usize)
-                        .min(density_map_size - 1);
-                    let density_y = ((h.y * self.config.density_map_resolution as f32) as usize)
-                        .min(density_map_size - 1);
-                    let local_density = self.state.density_map[density_x][density_y];
-
-                    if local_density > self.config.density_inhibition_threshold {
-                        // Calculate inhibition: stronger as density exceeds threshold
-                        let excess_density =
-                            local_density - self.config.density_inhibition_threshold;
-                        let inhibition_factor =
-                            (excess_density / self.config.density_inhibition_threshold).min(1.0)
+                    let density_slow = 1.0 / (1.0 + 0.05 * neighbor_count);
+
+                    // Mycelial Density + Self-Inhibition: Check density map for growth inhibition
+                    let density_inhibition = if self.config.density_inhibition_enabled {
+                        let density_map_size = self.state.density_map_size;
+                        let density_x = ((h.x * self.config.density_map_resolution as f32)
+                            as usize)
+                            .min(density_map_size - 1);
+                        let density_y = ((h.y * self.config.density_map_resolution as f32)
+                            as usize)
+                            .min(density_map_size - 1);
+                        let local_density = self.state.density_map[density_x][density_y];
+
+                        if local_density > self.config.density_inhibition_threshold {
+                            // Calculate inhibition: stronger as density exceeds threshold
+                            let excess_density =
+                                local_density - self.config.density_inhibition_threshold;
+                            let inhibition_factor = (excess_density
+                                / self.config.density_inhibition_threshold)
+                                .min(1.0)
                                 * self.config.density_inhibition_strength;
-                        1.0 - inhibition_factor // Reduce growth by inhibition factor
+                            1.0 - inhibition_factor // Reduce growth by inhibition factor
+                        } else {
+                            1.0 // No inhibition below threshold
+                        }
                     } else {
-                        1.0 // No inhibition below threshold
-                    }
-                } else {
-                    1.0
-                };
+                        1.0
+                    };
 
-                // Network Intelligence: Strength affects growth rate
-                let strength_multiplier = if self.config.adaptive_growth_enabled {
-                    h.strength
-                } else {
-                    1.0
-                };
+                    // Network Intelligence: Strength affects growth rate
+                    let strength_multiplier = if self.config.adaptive_growth_enabled {
+                        h.strength
+                    } else {
+                        1.0
+                    };
 
-                // Weather: Apply weather effects to growth rate
-                let weather_growth_multiplier =
-                    if self.config.weather_enabled && self.config.weather_affects_growth {
-                        self.state.weather.growth_multiplier()
+                    // Weather: Apply weather effects to growth rate
+                    let weather_growth_multiplier =
+                        if self.config.weather_enabled && self.config.weather_affects_growth {
+                            self.state.weather.growth_multiplier()
+                        } else {
+                            1.0
+                        };
+
+                    // Soil moisture: affects growth rate
+                    let moisture_growth_multiplier = if self.config.soil_moisture_enabled
+                        && in_bounds(h.x, h.y, self.config.grid_size)
+                    {
+                        let xi = h.x as usize;
+                        let yi = h.y as usize;
+                        let moisture = self.state.soil_moisture[xi][yi];
+                        // Optimal moisture: 0.5-0.8, too dry or too wet reduces growth
+                        if moisture < 0.3 {
+                            // Too dry: reduced growth
+                            0.4 + (moisture / 0.3) * 0.4
+                        } else if moisture <= 0.8 {
+                            // Optimal: full growth
+                            1.0
+                        } else {
+                            // Too wet: slightly reduced growth
+                            1.0 - (moisture - 0.8) / 0.2 * 0.3
+                        }
                     } else {
                         1.0
                     };
 
-                // Soil moisture: affects growth rate
-                let moisture_growth_multiplier = if self.config.soil_moisture_enabled
-                    && in_bounds(h.x, h.y, self.config.grid_size)
-                {
-                    let xi = h.x as usize;
-                    let yi = h.y as usize;
-                    let moisture = self.state.soil_moisture[xi][yi];
-                    // Optimal moisture: 0.5-0.8, too dry or too wet reduces growth
-                    if moisture < 0.3 {
-                        // Too dry: reduced growth
-                        0.4 + (moisture / 0.3) * 0.4
-                    } else if moisture <= 0.8 {
-                        // Optimal: full growth
+                    // Light exposure: affects growth rate (fungi avoid bright light)
+                    let light_growth_multiplier = if self.config.light_exposure_enabled
+                        && in_bounds(h.x, h.y, self.config.grid_size)
+                    {
+                        let xi = h.x as usize;
+                        let yi = h.y as usize;
+                        let light = self.state.light_exposure[xi][yi];
+                        // Bright light reduces growth
+                        1.0 - (light * self.config.light_growth_penalty)
+                    } else {
                         1.0
+                    };
+
+                    // Carbon/Nitrogen ratio: growth efficiency based on C:N ratio
+                    let cn_ratio_multiplier = if h.nitrogen > 0.001 {
+                        let cn_ratio = h.carbon / h.nitrogen;
+                        let ratio_diff = (cn_ratio - self.config.cn_ratio_required).abs();
+                        let tolerance =
+                            self.config.cn_ratio_required * self.config.cn_ratio_tolerance;
+                        if ratio_diff <= tolerance {
+                            1.0 // Optimal ratio - full growth
+                        } else {
+                            // Growth slows when ratio is off
+                            let excess = (ratio_diff - tolerance).max(0.0);
+                            (1.0 - (excess / (self.config.cn_ratio_required * 2.0))).max(0.5)
+                            // At least 50% growth
+                        }
                     } else {
-                        // Too wet: slightly reduced growth
-                        1.0 - (moisture - 0.8) / 0.2 * 0.3
+                        0.7 // Reduced growth if no nitrogen
+                    };
+
+                    if too_close {
+                        h.angle += rng.gen_range(-0.5..0.5);
                     }
-                } else {
-                    1.0
-                };
 
-                // Light exposure: affects growth rate (fungi avoid bright light)
-                let light_growth_multiplier = if self.config.light_exposure_enabled
-                    && in_bounds(h.x, h.y, self.config.grid_size)
-                {
+                    // Apply all growth multipliers
+                    let final_step_size = self.config.step_size
+                        * density_slow
+                        * density_inhibition
+                        * strength_multiplier
+                        * weather_growth_multiplier
+                        * moisture_growth_multiplier
+                        * light_growth_multiplier
+                        * cn_ratio_multiplier;
+                    h.x += h.angle.cos() * final_step_size;
+                    h.y += h.angle.sin() * final_step_size;
+
                     let xi = h.x as usize;
                     let yi = h.y as usize;
-                    let light = self.state.light_exposure[xi][yi];
-                    // Bright light reduces growth
-                    1.0 - (light * self.config.light_growth_penalty)
-                } else {
-                    1.0
-                };
-
-                // Carbon/Nitrogen ratio: growth efficiency based on C:N ratio
-                let cn_ratio_multiplier = if h.nitrogen > 0.001 {
-                    let cn_ratio = h.carbon / h.nitrogen;
-                    let ratio_diff = (cn_ratio - self.config.cn_ratio_required).abs();
-                    let tolerance = self.config.cn_ratio_required * self.config.cn_ratio_tolerance;
-                    if ratio_diff <= tolerance {
-                        1.0 // Optimal ratio - full growth
-                    } else {
-                        // Growth slows when ratio is off
-                        let excess = (ratio_diff - tolerance).max(0.0);
-                        (1.0 - (excess / (self.config.cn_ratio_required * 2.0))).max(0.5)
-                        // At least 50% growth
+                    if in_bounds(h.x, h.y, self.config.grid_size) && self.state.obstacles[xi][yi] {
+                        h.x = h.prev_x;
+                        h.y = h.prev_y;
+                        let mut found_clear = false;
+                        let mut best_angle = h.angle;
+                        let mut attempts = 0;
+                        while !found_clear && attempts < 8 {
+                            let test_angle =
+                                h.angle + (attempts as f32) * std::f32::consts::PI / 4.0;
+                            let test_x = h.x + test_angle.cos() * self.config.step_size;
+                            let test_y = h.y + test_angle.sin() * self.config.step_size;
+                            let test_xi = test_x as usize;
+                            let test_yi = test_y as usize;
+                            if in_bounds(test_x, test_y, self.config.grid_size)
+                                && !self.state.obstacles[test_xi][test_yi]
+                            {
+                                best_angle = test_angle;
+                                found_clear = true;
+                            }
+                            attempts += 1;
+                        }
+                        if found_clear {
+                            h.angle = best_angle + rng.gen_range(-0.2..0.2);
+                        } else {
+                            h.angle += std::f32::consts::PI + rng.gen_range(-0.5..0.5);
+                        }
+                        h.angle %= std::f32::consts::TAU;
+                        if h.angle < 0.0 {
+                            h.angle += std::f32::consts::TAU;
+                        }
+                        h.x += h.angle.cos() * self.config.step_size;
+                        h.y += h.angle.sin() * self.config.step_size;
                     }
-                } else {
-                    0.7 // Reduced growth if no nitrogen
-                };
 
-                if too_close {
-                    h.angle += rng.gen_range(-0.5..0.5);
-                }
+                    if h.x < 1.0
+                        || h.x >= self.config.grid_size as f32 - 1.0
+                        || h.y < 1.0
+                        || h.y >= self.config.grid_size as f32 - 1.0
+                    {
+                        h.x = h.prev_x;
+                        h.y = h.prev_y;
+                        let min_b = 1.0;
+                        let max_b = self.config.grid_size as f32 - 2.0;
+                        if h.x <= min_b {
+                            h.x = min_b;
+                            h.angle = std::f32::consts::PI - h.angle;
+                        } else if h.x >= max_b {
+                            h.x = max_b;
+                            h.angle = std::f32::consts::PI - h.angle;
+                        }
+                        if h.y <= min_b {
+                            h.y = min_b;
+                            h.angle = -h.angle;
+                        } else if h.y >= max_b {
+                            h.y = max_b;
+                            h.angle = -h.angle;
+                        }
+                        h.angle += rng.gen_range(-0.15..0.15);
+                        h.x += h.angle.cos() * self.config.step_size;
+                        h.y += h.angle.sin() * self.config.step_size;
+                        h.x = h.x.clamp(min_b, max_b);
+                        h.y = h.y.clamp(min_b, max_b);
+                    }
 
-                // Apply all growth multipliers
-                let final_step_size = self.config.step_size
-                    * density_slow
-                    * density_inhibition
-                    * strength_multiplier
-                    * weather_growth_multiplier
-                    * moisture_growth_multiplier
-                    * light_growth_multiplier
-                    * cn_ratio_multiplier;
-                h.x += h.angle.cos() * final_step_size;
-                h.y += h.angle.sin() * final_step_size;
+                    let xi = h.x as usize;
+                    let yi = h.y as usize;
 
-                let xi = h.x as usize;
-                let yi = h.y as usize;
-                if in_bounds(h.x, h.y, self.config.grid_size) && self.state.obstacles[xi][yi] {
-                    h.x = h.prev_x;
-                    h.y = h.prev_y;
-                    let mut found_clear = false;
-                    let mut best_angle = h.angle;
-                    let mut attempts = 0;
-                    while !found_clear && attempts < 8 {
-                        let test_angle = h.angle + (attempts as f32) * std::f32::consts::PI / 4.0;
-                        let test_x = h.x + test_angle.cos() * self.config.step_size;
-                        let test_y = h.y + test_angle.sin() * self.config.step_size;
-                        let test_xi = test_x as usize;
-                        let test_yi = test_y as usize;
-                        if in_bounds(test_x, test_y, self.config.grid_size)
-                            && !self.state.obstacles[test_xi][test_yi]
-                        {
-                            best_angle = test_angle;
-                            found_clear = true;
+                    // Zone effects: toxic zones damage hyphae, competitors consume nutrients
+                    if self.config.zones_enabled && in_bounds(h.x, h.y, self.config.grid_size) {
+                        let zone = &self.state.zones[xi][yi];
+                        match zone.zone_type {
+                            ZoneType::Toxic => {
+                                // Toxic zones damage hyphae (reduce energy)
+                                let damage = self.config.toxic_zone_damage_rate * zone.intensity;
+                                h.energy = (h.energy - damage).max(0.0);
+                                // Increase senescence in toxic zones
+                                h.senescence_factor = (h.senescence_factor + damage * 2.0).min(1.0);
+                            }
+                            ZoneType::Competitor => {
+                                // Competitor zones consume nutrients (reduce available nutrients)
+                                // This is handled in nutrient consumption below
+                            }
+                            ZoneType::Deadwood => {
+                                // Deadwood patches are nutrient-rich, no negative effects
+                            }
+                            ZoneType::None => {}
                         }
-                        attempts += 1;
-                    }
-                    if found_clear {
-                        h.angle = best_angle + rng.gen_range(-0.2..0.2);
-                    } else {
-                        h.angle += std::f32::consts::PI + rng.gen_range(-0.5..0.5);
                     }
-                    h.angle %= std::f32::consts::TAU;
-                    if h.angle < 0.0 {
-                        h.angle += std::f32::consts::TAU;
-                    }
-                    h.x += h.angle.cos() * self.config.step_size;
-                    h.y += h.angle.sin() * self.config.step_size;
-                }
+                    (Some(rng), (grid_queries, neighbor_checks))
+                })
+                .unzip();
+            let (grid_queries, neighbor_checks) = tip_work
+                .iter()
+                .fold((0, 0), |(q, c), &(tq, tc)| (q + tq, c + tc));
+            self.profile.add(
+                StepCounter::TipsGrown,
+                tip_rngs.iter().filter(|rng| rng.is_some()).count() as u64,
+            );
+            self.profile
+                .add(StepCounter::GrowthGridQueries, grid_queries);
+            self.profile
+                .add(StepCounter::GrowthNeighborChecks, neighbor_checks);
 
-                if h.x < 1.0
-                    || h.x >= self.config.grid_size as f32 - 1.0
-                    || h.y < 1.0
-                    || h.y >= self.config.grid_size as f32 - 1.0
-                {
-                    h.x = h.prev_x;
-                    h.y = h.prev_y;
-                    let min_b = 1.0;
-                    let max_b = self.config.grid_size as f32 - 2.0;
-                    if h.x <= min_b {
-                        h.x = min_b;
-                        h.angle = std::f32::consts::PI - h.angle;
-                    } else if h.x >= max_b {
-                        h.x = max_b;
-                        h.angle = std::f32::consts::PI - h.angle;
-                    }
-                    if h.y <= min_b {
-                        h.y = min_b;
-                        h.angle = -h.angle;
-                    } else if h.y >= max_b {
-                        h.y = max_b;
-                        h.angle = -h.angle;
-                    }
-                    h.angle += rng.gen_range(-0.15..0.15);
-                    h.x += h.angle.cos() * self.config.step_size;
-                    h.y += h.angle.sin() * self.config.step_size;
-                    h.x = h.x.clamp(min_b, max_b);
-                    h.y = h.y.clamp(min_b, max_b);
+            // Uptake phase: nutrient consumption, energy decay and starvation
+            for h in self.state.hyphae[..hyphae_len].iter_mut() {
+                if !h.alive {
+                    continue;
                 }
-
                 let xi = h.x as usize;
                 let yi = h.y as usize;
 
-                // Zone effects: toxic zones damage hyphae, competitors consume nutrients
-                if self.config.zones_enabled && in_bounds(h.x, h.y, self.config.grid_size) {
-                    let zone = &self.state.zones[xi][yi];
-                    match zone.zone_type {
-                        ZoneType::Toxic => {
-                            // Toxic zones damage hyphae (reduce energy)
-                            let damage = self.config.toxic_zone_damage_rate * zone.intensity;
-                            h.energy = (h.energy - damage).max(0.0);
-                            // Increase senescence in toxic zones
-                            h.senescence_factor = (h.senescence_factor + damage * 2.0).min(1.0);
-                        }
-                        ZoneType::Competitor => {
-                            // Competitor zones consume nutrients (reduce available nutrients)
-                            // This is handled in nutrient consumption below
-                        }
-                        ZoneType::Deadwood => {
-                            // Deadwood patches are nutrient-rich, no negative effects
-                        }
-                        ZoneType::None => {}
-                    }
-                }
-
                 // Consume both sugar (primary) and nitrogen (secondary)
                 let mut sugar = self.state.nutrients.sugar[xi][yi];
                 let mut nitrogen = self.state.nutrients.nitrogen[xi][yi];
@@ -1563,273 +1832,320 @@ impl Simulation {
                     h.alive = false;
                     continue;
                 }
+            }
 
-                // Hyphal Senescence & Death: Biological aging and death system
-                // Only apply to hyphae that are old enough to have established connections
-                if self.config.senescence_enabled
-                    && h.alive
-                    && h.age >= self.config.senescence_min_age
-                {
-                    // Compute nutrient flow for this hypha (from connections)
-                    let mut nutrient_flow = 0.0;
-                    let mut has_connections = false;
-                    for conn in &self.state.connections {
-                        if conn.hypha1 == idx || conn.hypha2 == idx {
-                            nutrient_flow += conn.flow_accumulator;
+            // (hypha id, connection index) for both ends of every connection, sorted so each
+            // hypha's connections form one run in connection order, for the senescence checks
+            let mut connection_ends: Vec<(u64, usize)> = Vec::new();
+            if self.config.senescence_enabled
+                && self.state.hyphae[..hyphae_len]
+                    .iter()
+                    .any(|h| h.alive && h.age >= self.config.senescence_min_age)
+            {
+                for (conn_idx, conn) in self.state.connections.iter().enumerate() {
+                    connection_ends.push((conn.hypha1, conn_idx));
+                    if conn.hypha2 != conn.hypha1 {
+                        connection_ends.push((conn.hypha2, conn_idx));
+                    }
+                }
+                connection_ends.sort_unstable();
+            }
+
+            // Senescence/branching phase: collected per hypha, then applied in slot order
+            let outcomes: Vec<TipOutcome> = self.state.hyphae[..hyphae_len]
+                .par_iter_mut()
+                .zip(tip_rngs)
+                .enumerate()
+                .map(|(idx, (h, rng))| {
+                    let mut outcome = TipOutcome::default();
+                    let Some(mut rng) = rng.filter(|_| h.alive) else {
+                        return outcome;
+                    };
+
+                    // Hyphal Senescence & Death: Biological aging and death system
+                    // Only apply to hyphae that are old enough to have established connections
+                    if self.config.senescence_enabled
+                        && h.alive
+                        && h.age >= self.config.senescence_min_age
+                    {
+                        // Compute nutrient flow for this hypha (from connections)
+                        let mut nutrient_flow = 0.0;
+                        let mut has_connections = false;
+                        let start = connection_ends.partition_point(|&(id, _)| id < h.id);
+                        let end = connection_ends.partition_point(|&(id, _)| id <= h.id);
+                        let connections = &connection_ends[start..end];
+                        for &(_, conn_idx) in connections {
+                            nutrient_flow += self.state.connections[conn_idx].flow_accumulator;
                             has_connections = true;
                         }
-                    }
 
-                    // Compute distance from main network (distance to nearest connected hypha or network center)
-                    let mut min_distance_to_network = f32::MAX;
-                    let network_center_x = self.config.grid_size as f32 / 2.0;
-                    let network_center_y = self.config.grid_size as f32 / 2.0;
-                    let dist_to_center = ((h.x - network_center_x).powi(2)
-                        + (h.y - network_center_y).powi(2))
-                    .sqrt();
-                    min_distance_to_network = min_distance_to_network.min(dist_to_center);
-
-                    // Check connections to find nearest connected hypha
-                    for conn in &self.state.connections {
-                        let other_idx = if conn.hypha1 == idx {
-                            conn.hypha2
-                        } else if conn.hypha2 == idx {
-                            conn.hypha1
-                        } else {
-                            continue;
-                        };
-                        if other_idx < hyphae_positions.len() {
-                            let (other_x, other_y, other_alive, _, _) = hyphae_positions[other_idx];
-                            if other_alive {
-                                let dist =
-                                    ((h.x - other_x).powi(2) + (h.y - other_y).powi(2)).sqrt();
-                                min_distance_to_network = min_distance_to_network.min(dist);
+                        // Compute distance from main network (distance to nearest connected hypha or network center)
+                        let mut min_distance_to_network = f32::MAX;
+                        let network_center_x = self.config.grid_size as f32 / 2.0;
+                        let network_center_y = self.config.grid_size as f32 / 2.0;
+                        let dist_to_center = ((h.x - network_center_x).powi(2)
+                            + (h.y - network_center_y).powi(2))
+                        .sqrt();
+                        min_distance_to_network = min_distance_to_network.min(dist_to_center);
+
+                        // Check connections to find nearest connected hypha
+                        for &(_, conn_idx) in connections {
+                            let conn = &self.state.connections[conn_idx];
+                            let other_id = if conn.hypha1 == h.id {
+                                conn.hypha2
+                            } else if conn.hypha2 == h.id {
+                                conn.hypha1
+                            } else {
+                                continue;
+                            };
+                            if let Some(&other_idx) = self.state.hypha_slots.get(&other_id) {
+                                let (other_x, other_y, other_alive, _, _) =
+                                    hyphae_positions[other_idx];
+                                if other_alive {
+                                    let dist =
+                                        ((h.x - other_x).powi(2) + (h.y - other_y).powi(2)).sqrt();
+                                    min_distance_to_network = min_distance_to_network.min(dist);
+                                }
                             }
                         }
-                    }
 
-                    // Check parent connection
-                    if let Some(parent_idx) = h.parent {
-                        if parent_idx < hyphae_positions.len() {
-                            let (parent_x, parent_y, parent_alive, _, _) =
-                                hyphae_positions[parent_idx];
-                            if parent_alive {
-                                let dist =
-                                    ((h.x - parent_x).powi(2) + (h.y - parent_y).powi(2)).sqrt();
-                                min_distance_to_network = min_distance_to_network.min(dist);
+                        // Check parent connection
+                        if let Some(parent_idx) = hyphae_positions[idx].4 {
+                            if parent_idx < hyphae_positions.len() {
+                                let (parent_x, parent_y, parent_alive, _, _) =
+                                    hyphae_positions[parent_idx];
+                                if parent_alive {
+                                    let dist = ((h.x - parent_x).powi(2)
+                                        + (h.y - parent_y).powi(2))
+                                    .sqrt();
+                                    min_distance_to_network = min_distance_to_network.min(dist);
+                                }
                             }
                         }
-                    }
 
-                    // Calculate senescence factors
-                    let mut death_probability = self.config.senescence_base_probability;
+                        // Calculate senescence factors
+                        let mut death_probability = self.config.senescence_base_probability;
 
-                    // Factor 1: Low nutrient flow increases death probability
-                    // Only apply if hypha has connections (otherwise it's too early to judge)
-                    if has_connections
-                        && nutrient_flow < self.config.senescence_nutrient_flow_threshold
-                    {
-                        let flow_factor =
-                            1.0 - (nutrient_flow / self.config.senescence_nutrient_flow_threshold);
-                        death_probability += flow_factor * 0.0002; // Reduced from 0.001 to 0.0002 (0.02% max)
-                    }
-
-                    // Factor 2: Distance from main network increases death probability
-                    if min_distance_to_network > self.config.senescence_distance_threshold {
-                        let distance_factor = ((min_distance_to_network
-                            - self.config.senescence_distance_threshold)
-                            / self.config.senescence_unsupported_collapse_distance)
-                            .min(1.0);
-                        death_probability += distance_factor * 0.0001; // Reduced from 0.0005 to 0.0001 (0.01% max)
-
-                        // Collapse unsupported branches (beyond threshold distance)
-                        if min_distance_to_network
-                            > self.config.senescence_unsupported_collapse_distance
+                        // Factor 1: Low nutrient flow increases death probability
+                        // Only apply if hypha has connections (otherwise it's too early to judge)
+                        if has_connections
+                            && nutrient_flow < self.config.senescence_nutrient_flow_threshold
                         {
-                            death_probability += 0.002; // Reduced from 0.01 to 0.002 (0.2% chance)
+                            let flow_factor = 1.0
+                                - (nutrient_flow / self.config.senescence_nutrient_flow_threshold);
+                            death_probability += flow_factor * 0.0002; // Reduced from 0.001 to 0.0002 (0.02% max)
                         }
-                    }
 
-                    // Factor 3: Weather extremes (too hot or too cold)
-                    if self.config.weather_enabled {
-                        let temp = self.state.weather.temperature;
-                        let optimal_min = 0.8;
-                        let optimal_max = 1.2;
-                        if temp < (optimal_min - self.config.senescence_weather_extreme_threshold)
-                            || temp
-                                > (optimal_max + self.config.senescence_weather_extreme_threshold)
-                        {
-                            let extreme_factor = if temp < optimal_min {
-                                (optimal_min
-                                    - self.config.senescence_weather_extreme_threshold
-                                    - temp)
-                                    / self.config.senescence_weather_extreme_threshold
-                            } else {
-                                (temp
-                                    - optimal_max
-                                    - self.config.senescence_weather_extreme_threshold)
-                                    / self.config.senescence_weather_extreme_threshold
+                        // Factor 2: Distance from main network increases death probability
+                        if min_distance_to_network > self.config.senescence_distance_threshold {
+                            let distance_factor = ((min_distance_to_network
+                                - self.config.senescence_distance_threshold)
+                                / self.config.senescence_unsupported_collapse_distance)
+                                .min(1.0);
+                            death_probability += distance_factor * 0.0001; // Reduced from 0.0005 to 0.0001 (0.01% max)
+
+                            // Collapse unsupported branches (beyond threshold distance)
+                            if min_distance_to_network
+                                > self.config.senescence_unsupported_collapse_distance
+                            {
+                                death_probability += 0.002; // Reduced from 0.01 to 0.002 (0.2% chance)
+                            }
+                        }
+
+                        // Factor 3: Weather extremes (too hot or too cold)
+                        if self.config.weather_enabled {
+                            let temp = self.state.weather.temperature;
+                            let optimal_min = 0.8;
+                            let optimal_max = 1.2;
+                            if temp
+                                < (optimal_min - self.config.senescence_weather_extreme_threshold)
+                                || temp
+                                    > (optimal_max
+                                        + self.config.senescence_weather_extreme_threshold)
+                            {
+                                let extreme_factor = if temp < optimal_min {
+                                    (optimal_min
+                                        - self.config.senescence_weather_extreme_threshold
+                                        - temp)
+                                        / self.config.senescence_weather_extreme_threshold
+                                } else {
+                                    (temp
+                                        - optimal_max
+                                        - self.config.senescence_weather_extreme_threshold)
+                                        / self.config.senescence_weather_extreme_threshold
+                                }
+                                .min(1.0);
+                                death_probability += extreme_factor * 0.0002; // Reduced from 0.0008 to 0.0002 (0.02% max)
                             }
-                            .min(1.0);
-                            death_probability += extreme_factor * 0.0002; // Reduced from 0.0008 to 0.0002 (0.02% max)
                         }
-                    }
 
-                    // Update senescence factor (accumulates over time, but slower)
-                    let senescence_increase = death_probability * 5.0; // Reduced from 10.0 to 5.0
-                    h.senescence_factor = (h.senescence_factor + senescence_increase).min(1.0);
+                        // Update senescence factor (accumulates over time, but slower)
+                        let senescence_increase = death_probability * 5.0; // Reduced from 10.0 to 5.0
+                        h.senescence_factor = (h.senescence_factor + senescence_increase).min(1.0);
 
-                    // Apply death probability
-                    if rng.gen::<f32>() < death_probability {
-                        h.alive = false;
-                        continue;
+                        // Apply death probability
+                        if rng.gen::<f32>() < death_probability {
+                            h.alive = false;
+                            return outcome;
+                        }
                     }
-                }
 
-                if let Some(parent_idx) = h.parent {
-                    if parent_idx < hyphae_positions.len() {
-                        let (parent_x, parent_y, parent_alive, parent_energy, _) =
-                            hyphae_positions[parent_idx];
-                        if parent_alive {
-                            let dx = h.x - parent_x;
-                            let dy = h.y - parent_y;
-                            let dist = (dx * dx + dy * dy).sqrt();
-                            let max_dist = 6.0f32;
-                            if dist < max_dist {
-                                let transfer_rate = 0.002 * (1.0 - dist / max_dist).max(0.0);
-                                let wanted = (h.energy - parent_energy) * 0.5;
-                                let transfer = (wanted * transfer_rate).clamp(-0.01, 0.01);
-                                if transfer.abs() > 0.0 {
-                                    energy_transfers.push((idx, parent_idx, transfer));
+                    if let Some(parent_idx) = hyphae_positions[idx].4 {
+                        if parent_idx < hyphae_positions.len() {
+                            let (parent_x, parent_y, parent_alive, parent_energy, _) =
+                                hyphae_positions[parent_idx];
+                            if parent_alive {
+                                let dx = h.x - parent_x;
+                                let dy = h.y - parent_y;
+                                let dist = (dx * dx + dy * dy).sqrt();
+                                let max_dist = 6.0f32;
+                                if dist < max_dist {
+                                    let transfer_rate = 0.002 * (1.0 - dist / max_dist).max(0.0);
+                                    let wanted = (h.energy - parent_energy) * 0.5;
+                                    let transfer = (wanted * transfer_rate).clamp(-0.01, 0.01);
+                                    if transfer.abs() > 0.0 {
+                                        outcome.transfer = Some((idx, parent_idx, transfer));
+                                    }
                                 }
                             }
                         }
                     }
-                }
 
-                // Performance: Growth limits - stop branching after threshold
-                let can_branch = if self.config.max_hyphae_branching_threshold > 0 {
-                    hyphae_len < self.config.max_hyphae_branching_threshold
-                } else {
-                    true
-                };
+                    // Performance: Growth limits - stop branching after threshold
+                    let can_branch = if self.config.max_hyphae_branching_threshold > 0 {
+                        hyphae_len < self.config.max_hyphae_branching_threshold
+                    } else {
+                        true
+                    };
 
-                if can_branch {
-                    let age_branch_boost = (1.0 + h.age * 0.05).min(2.0);
-                    // Weather: Apply weather effects to branching probability
-                    let weather_branch_mult =
-                        if self.config.weather_enabled && self.config.weather_affects_growth {
-                            self.state.weather.growth_multiplier()
+                    if can_branch {
+                        let age_branch_boost = (1.0 + h.age * 0.05).min(2.0);
+                        // Weather: Apply weather effects to branching probability
+                        let weather_branch_mult =
+                            if self.config.weather_enabled && self.config.weather_affects_growth {
+                                self.state.weather.growth_multiplier()
+                            } else {
+                                1.0
+                            };
+
+                        // Soil moisture: affects branching probability
+                        let moisture_branch_mult = if self.config.soil_moisture_enabled
+                            && in_bounds(h.x, h.y, self.config.grid_size)
+                        {
+                            let xi = h.x as usize;
+                            let yi = h.y as usize;
+                            let moisture = self.state.soil_moisture[xi][yi];
+                            // Higher moisture = more branching
+                            0.5 + moisture * 0.5
                         } else {
                             1.0
                         };
 
-                    // Soil moisture: affects branching probability
-                    let moisture_branch_mult = if self.config.soil_moisture_enabled
-                        && in_bounds(h.x, h.y, self.config.grid_size)
-                    {
-                        let xi = h.x as usize;
-                        let yi = h.y as usize;
-                        let moisture = self.state.soil_moisture[xi][yi];
-                        // Higher moisture = more branching
-                        0.5 + moisture * 0.5
-                    } else {
-                        1.0
-                    };
+                        let branch_prob = self.config.branch_prob
+                            * age_branch_boost
+                            * weather_branch_mult
+                            * moisture_branch_mult;
+
+                        // Ensure minimum branching probability even in bad weather
+                        // This prevents complete stagnation while still allowing weather effects
+                        let min_branch_prob = self.config.branch_prob * 0.3; // At least 30% of base
+                        let branch_prob = branch_prob.max(min_branch_prob);
+
+                        if rng.gen::<f32>() < branch_prob {
+                            // Give new branch a small initial offset to prevent immediate fusion
+                            // Offset in the direction of the branch angle
+                            let branch_angle = h.angle + rng.gen_range(-1.2..1.2);
+                            let offset_distance = 1.5; // Offset by 1.5 units (more than fusion_distance of 1.0)
+                            let offset_x = h.x + branch_angle.cos() * offset_distance;
+                            let offset_y = h.y + branch_angle.sin() * offset_distance;
+
+                            // Create segment immediately to connect parent to new branch (prevents blank space)
+                            #[cfg(feature = "ui")]
+                            #[cfg(not(test))]
+                            {
+                                let from = macroquad::prelude::vec2(
+                                    h.x * self.config.cell_size,
+                                    h.y * self.config.cell_size,
+                                );
+                                let to = macroquad::prelude::vec2(
+                                    offset_x * self.config.cell_size,
+                                    offset_y * self.config.cell_size,
+                                );
+                                outcome.branch_segment = Some(Segment { from, to, age: 0.0 });
+                            }
+                            #[cfg(any(test, not(feature = "ui")))]
+                            {
+                                use crate::types::Vec2;
+                                let from = Vec2::new(
+                                    h.x * self.config.cell_size,
+                                    h.y * self.config.cell_size,
+                                );
+                                let to = Vec2::new(
+                                    offset_x * self.config.cell_size,
+                                    offset_y * self.config.cell_size,
+                                );
+                                outcome.branch_segment = Some(Segment { from, to, age: 0.0 });
+                            }
 
-                    let branch_prob = self.config.branch_prob
-                        * age_branch_boost
-                        * weather_branch_mult
-                        * moisture_branch_mult;
-
-                    // Ensure minimum branching probability even in bad weather
-                    // This prevents complete stagnation while still allowing weather effects
-                    let min_branch_prob = self.config.branch_prob * 0.3; // At least 30% of base
-                    let branch_prob = branch_prob.max(min_branch_prob);
-
-                    if rng.gen::<f32>() < branch_prob {
-                        let idxp = hyphae_len;
-                        // Give new branch a small initial offset to prevent immediate fusion
-                        // Offset in the direction of the branch angle
-                        let branch_angle = h.angle + rng.gen_range(-1.2..1.2);
-                        let offset_distance = 1.5; // Offset by 1.5 units (more than fusion_distance of 1.0)
-                        let offset_x = h.x + branch_angle.cos() * offset_distance;
-                        let offset_y = h.y + branch_angle.sin() * offset_distance;
-
-                        // Create segment immediately to connect parent to new branch (prevents blank space)
-                        #[cfg(feature = "ui")]
-                        #[cfg(not(test))]
-                        {
-                            let from = macroquad::prelude::vec2(
-                                h.x * self.config.cell_size,
-                                h.y * self.config.cell_size,
-                            );
-                            let to = macroquad::prelude::vec2(
-                                offset_x * self.config.cell_size,
-                                offset_y * self.config.cell_size,
-                            );
-                            self.state.segments.push(Segment { from, to, age: 0.0 });
-                        }
-                        #[cfg(any(test, not(feature = "ui")))]
-                        {
-                            use crate::types::Vec2;
-                            let from =
-                                Vec2::new(h.x * self.config.cell_size, h.y * self.config.cell_size);
-                            let to = Vec2::new(
-                                offset_x * self.config.cell_size,
-                                offset_y * self.config.cell_size,
-                            );
-                            self.state.segments.push(Segment { from, to, age: 0.0 });
+                            outcome.branch = Some(Hypha {
+                                id: 0,
+                                x: offset_x,
+                                y: offset_y,
+                                prev_x: h.x, // Previous position is parent position
+                                prev_y: h.y,
+                                angle: branch_angle,
+                                alive: true,
+                                energy: h.energy * 0.5,
+                                parent: Some(h.id),
+                                age: 0.0,
+                                strength: h.strength * 0.8, // Branches start slightly weaker
+                                signal_received: 0.0,
+                                last_nutrient_location: h.last_nutrient_location,
+                                senescence_factor: h.senescence_factor * 0.5, // Inherit some senescence
+                                carbon: h.carbon * 0.5, // Share nutrients with branch
+                                nitrogen: h.nitrogen * 0.5,
+                            });
+                            h.energy *= 0.5;
+                            h.carbon *= 0.5; // Share nutrients
+                            h.nitrogen *= 0.5;
                         }
+                    }
 
-                        new_hyphae.push(Hypha {
-                            x: offset_x,
-                            y: offset_y,
-                            prev_x: h.x, // Previous position is parent position
-                            prev_y: h.y,
-                            angle: branch_angle,
-                            alive: true,
-                            energy: h.energy * 0.5,
-                            parent: Some(idxp),
-                            age: 0.0,
-                            strength: h.strength * 0.8, // Branches start slightly weaker
-                            signal_received: 0.0,
-                            last_nutrient_location: h.last_nutrient_location,
-                            senescence_factor: h.senescence_factor * 0.5, // Inherit some senescence
-                            carbon: h.carbon * 0.5, // Share nutrients with branch
-                            nitrogen: h.nitrogen * 0.5,
-                        });
-                        h.energy *= 0.5;
-                        h.carbon *= 0.5; // Share nutrients
-                        h.nitrogen *= 0.5;
+                    // Create segment for visualization (trails)
+                    // Use types::Vec2 for headless/test mode, macroquad::Vec2 for UI mode
+                    #[cfg(feature = "ui")]
+                    #[cfg(not(test))]
+                    {
+                        let from = macroquad::prelude::vec2(
+                            h.prev_x * self.config.cell_size,
+                            h.prev_y * self.config.cell_size,
+                        );
+                        let to = macroquad::prelude::vec2(
+                            h.x * self.config.cell_size,
+                            h.y * self.config.cell_size,
+                        );
+                        outcome.trail = Some(Segment { from, to, age: 0.0 });
                     }
-                }
+                    #[cfg(any(test, not(feature = "ui")))]
+                    {
+                        use crate::types::Vec2;
+                        let from = Vec2::new(
+                            h.prev_x * self.config.cell_size,
+                            h.prev_y * self.config.cell_size,
+                        );
+                        let to =
+                            Vec2::new(h.x * self.config.cell_size, h.y * self.config.cell_size);
+                        outcome.trail = Some(Segment { from, to, age: 0.0 });
+                    }
+                    outcome
+                })
+                .collect();
 
-                // Create segment for visualization (trails)
-                // Use types::Vec2 for headless/test mode, macroquad::Vec2 for UI mode
-                #[cfg(feature = "ui")]
-                #[cfg(not(test))]
-                {
-                    let from = macroquad::prelude::vec2(
-                        h.prev_x * self.config.cell_size,
-                        h.prev_y * self.config.cell_size,
-                    );
-                    let to = macroquad::prelude::vec2(
-                        h.x * self.config.cell_size,
-                        h.y * self.config.cell_size,
-                    );
-                    self.state.segments.push(Segment { from, to, age: 0.0 });
-                }
-                #[cfg(any(test, not(feature = "ui")))]
-                {
-                    use crate::types::Vec2;
-                    let from = Vec2::new(
-                        h.prev_x * self.config.cell_size,
-                        h.prev_y * self.config.cell_size,
-                    );
-                    let to = Vec2::new(h.x * self.config.cell_size, h.y * self.config.cell_size);
-                    self.state.segments.push(Segment { from, to, age: 0.0 });
-                }
+            for outcome in outcomes {
+                energy_transfers.extend(outcome.transfer);
+                new_hyphae.extend(outcome.branch);
+                self.state.segments.extend(outcome.branch_segment);
+                self.state.segments.extend(outcome.trail);
             }
 
             for (from, to, amount) in energy_transfers {
@@ -1862,14 +2178,23 @@ impl Simulation {
                 }
             }
 
-            self.state.hyphae.extend(new_hyphae);
+            self.profile
+                .add(StepCounter::Branches, new_hyphae.len() as u64);
+            for hypha in new_hyphae {
+                self.state.add_hypha(hypha);
+            }
+            self.profile.record(StepPhase::Growth, phase_started);
+            let buckets = &mut self.state.spatial_grid;
 
             // Fusion: When hyphae are very close, merge them instead of just connecting
             // This is true biological fusion (anastomosis with merging)
+            let phase_started = Instant::now();
             if self.config.fusion_enabled {
                 let fusion_dist_sq = self.config.fusion_distance * self.config.fusion_distance;
                 let mut hyphae_to_remove: Vec<usize> = Vec::new();
                 let mut fusion_energy_transfers: Vec<(usize, f32)> = Vec::new();
+                let mut grid_queries = 0u64;
+                let mut pairs_checked = 0u64;
 
                 // Use spatial grid for efficient fusion checking
                 for i in 0..self.state.hyphae.len() {
@@ -1887,6 +2212,7 @@ impl Simulation {
                         for gy in (by - 1).max(0)..=(by + 1).min(ny as isize - 1) {
                             let gux = gx as usize;
                             let guy = gy as usize;
+                            grid_queries += 1;
                             for &j in &buckets[gux][guy] {
                                 if j <= i
                                     || hyphae_to_remove.contains(&j)
@@ -1894,6 +2220,7 @@ impl Simulation {
                                 {
                                     continue;
                                 }
+                                pairs_checked += 1;
 
                                 let h2_x = self.state.hyphae[j].x;
                                 let h2_y = self.state.hyphae[j].y;
@@ -1935,19 +2262,15 @@ impl Simulation {
                                     hyphae_to_remove.push(j);
 
                                     // Remove any connections involving j
+                                    let j_id = self.state.hyphae[j].id;
                                     let mut connections_to_remove: Vec<usize> = Vec::new();
                                     for (conn_idx, conn) in
                                         self.state.connections.iter().enumerate()
                                     {
-                                        if conn.hypha1 == j || conn.hypha2 == j {
+                                        if conn.hypha1 == j_id || conn.hypha2 == j_id {
                                             connections_to_remove.push(conn_idx);
                                             // Remove from connection set
-                                            let key = if conn.hypha1 < conn.hypha2 {
-                                                (conn.hypha1, conn.hypha2)
-                                            } else {
-                                                (conn.hypha2, conn.hypha1)
-                                            };
-                                            self.state.connection_set.remove(&key);
+                                            self.state.connection_set.remove(&conn.key());
                                         }
                                     }
 
@@ -1960,16 +2283,6 @@ impl Simulation {
                                         }
                                     }
 
-                                    // Update connection indices: replace j with i in remaining connections
-                                    for conn in &mut self.state.connections {
-                                        if conn.hypha1 == j {
-                                            conn.hypha1 = i;
-                                        }
-                                        if conn.hypha2 == j {
-                                            conn.hypha2 = i;
-                                        }
-                                    }
-
                                     break; // Only fuse with one hypha at a time
                                 }
                             }
@@ -1980,23 +2293,32 @@ impl Simulation {
                 // Remove fused hyphae in reverse order
                 hyphae_to_remove.sort();
                 hyphae_to_remove.dedup();
+                self.profile
+                    .add(StepCounter::FusionGridQueries, grid_queries);
+                self.profile.add(StepCounter::FusionPairs, pairs_checked);
+                self.profile
+                    .add(StepCounter::Fusions, hyphae_to_remove.len() as u64);
                 hyphae_to_remove.reverse();
                 for &idx in &hyphae_to_remove {
                     if idx < self.state.hyphae.len() {
-                        self.state.hyphae.swap_remove(idx);
+                        self.state.remove_hypha(idx);
                     }
                 }
             }
+            self.profile.record(StepPhase::Fusion, phase_started);
+            let buckets = &mut self.state.spatial_grid;
 
             // connections - use spatial hash grid to avoid O(n²) check
+            let phase_started = Instant::now();
             let anastomosis_dist_sq = self.config.anastomosis_distance_sq();
             let mut new_connections: Vec<(usize, usize, f32)> = Vec::new();
+            let mut grid_queries = 0u64;
+            let mut pairs_checked = 0u64;
+            let mut connections_created = 0u64;
 
             // Rebuild spatial grid after fusion
-            for row in buckets.iter_mut() {
-                for bucket in row.iter_mut() {
-                    bucket.clear();
-                }
+            for bucket in buckets.iter_mut() {
+                bucket.clear();
             }
 
             for (i, h) in self.state.hyphae.iter().enumerate() {
@@ -2032,10 +2354,12 @@ impl Simulation {
                     for gy in by.max(0)..=(by + 1).min(ny as isize - 1) {
                         let gux = gx as usize;
                         let guy = gy as usize;
+                        grid_queries += 1;
                         for &j in &buckets[gux][guy] {
                             if j <= i {
                                 continue; // Only check pairs once (j > i)
                             }
+                            pairs_checked += 1;
                             let h2_x = self.state.hyphae[j].x;
                             let h2_y = self.state.hyphae[j].y;
                             let h2_energy = self.state.hyphae[j].energy;
@@ -2048,16 +2372,16 @@ impl Simulation {
                             let dist2 = dx * dx + dy * dy;
                             if dist2 < anastomosis_dist_sq {
                                 // Use HashSet for O(1) lookup instead of O(n) linear search
-                                let key = (i, j);
-                                if !self.state.connection_set.contains(&key) {
-                                    self.state.connection_set.insert(key);
-                                    self.state.connections.push(Connection {
-                                        hypha1: i,
-                                        hypha2: j,
-                                        strength: self.config.min_connection_strength,
-                                        signal: 0.0,
-                                        flow_accumulator: 0.0,
-                                    });
+                                let connection = Connection {
+                                    hypha1: self.state.hyphae[i].id,
+                                    hypha2: self.state.hyphae[j].id,
+                                    strength: self.config.min_connection_strength,
+                                    signal: 0.0,
+                                    flow_accumulator: 0.0,
+                                };
+                                if self.state.connection_set.insert(connection.key()) {
+                                    self.state.connections.push(connection);
+                                    connections_created += 1;
                                     let energy_diff = h1_energy - h2_energy;
                                     if energy_diff.abs() > 0.1 {
                                         let transfer = energy_diff * 0.1;
@@ -2101,18 +2425,8 @@ impl Simulation {
             // Clean up dead connections
             let mut dead_connections = Vec::new();
             for (idx, c) in self.state.connections.iter().enumerate() {
-                let h1_alive = self
-                    .state
-                    .hyphae
-                    .get(c.hypha1)
-                    .map(|h| h.alive)
-                    .unwrap_or(false);
-                let h2_alive = self
-                    .state
-                    .hyphae
-                    .get(c.hypha2)
-                    .map(|h| h.alive)
-                    .unwrap_or(false);
+                let h1_alive = self.state.hypha(c.hypha1).map(|h| h.alive).unwrap_or(false);
+                let h2_alive = self.state.hypha(c.hypha2).map(|h| h.alive).unwrap_or(false);
                 if !h1_alive || !h2_alive {
                     dead_connections.push(idx);
                 }
@@ -2121,32 +2435,45 @@ impl Simulation {
             // Remove dead connections in reverse order to maintain indices
             for &idx in dead_connections.iter().rev() {
                 let c = &self.state.connections[idx];
-                let key = if c.hypha1 < c.hypha2 {
-                    (c.hypha1, c.hypha2)
-                } else {
-                    (c.hypha2, c.hypha1)
-                };
-                self.state.connection_set.remove(&key);
+                self.state.connection_set.remove(&c.key());
                 self.state.connections.swap_remove(idx);
             }
+            self.profile
+                .add(StepCounter::ConnectionGridQueries, grid_queries);
+            self.profile
+                .add(StepCounter::ConnectionPairs, pairs_checked);
+            self.profile
+                .add(StepCounter::ConnectionsCreated, connections_created);
+            self.profile.add(
+                StepCounter::ConnectionsRemoved,
+                dead_connections.len() as u64,
+            );
+            self.profile.record(StepPhase::Anastomosis, phase_started);
         }
 
         // Resource allocation along connections (diffusive flow)
         // Also handle signal propagation and adaptive growth
+        let phase_started = Instant::now();
         let mut connection_updates: Vec<(usize, f32, f32, f32)> = Vec::new(); // (idx, new_strength, new_signal, flow_acc)
 
         for (conn_idx, c) in self.state.connections.iter().enumerate() {
-            let (i, j) = if c.hypha1 <= c.hypha2 {
-                (c.hypha1, c.hypha2)
-            } else {
-                (c.hypha2, c.hypha1)
+            let (id1, id2) = c.key();
+            let (Some(&i), Some(&j)) = (
+                self.state.hypha_slots.get(&id1),
+                self.state.hypha_slots.get(&id2),
+            ) else {
+                continue;
             };
-            if j >= self.state.hyphae.len() {
+            if i == j {
                 continue;
             }
-            let (left, right) = self.state.hyphae.split_at_mut(j);
-            let h1 = &mut left[i];
-            let h2 = &mut right[0];
+            // h1 is always the hypha with the smaller id
+            let (left, right) = self.state.hyphae.split_at_mut(i.max(j));
+            let (h1, h2) = if i < j {
+                (&mut left[i], &mut right[0])
+            } else {
+                (&mut right[0], &mut left[j])
+            };
             if !h1.alive || !h2.alive {
                 continue;
             }
@@ -2217,6 +2544,10 @@ impl Simulation {
         }
 
         // Apply connection updates
+        self.profile.add(
+            StepCounter::ConnectionsFlowed,
+            connection_updates.len() as u64,
+        );
         for (idx, strength, signal, flow_acc) in connection_updates {
             if let Some(c) = self.state.connections.get_mut(idx) {
                 c.strength = strength;
@@ -2235,14 +2566,13 @@ impl Simulation {
             }
 
             // Remove weak connections in reverse order
+            self.profile.add(
+                StepCounter::ConnectionsPruned,
+                weak_connections.len() as u64,
+            );
             for &idx in weak_connections.iter().rev() {
                 let c = &self.state.connections[idx];
-                let key = if c.hypha1 < c.hypha2 {
-                    (c.hypha1, c.hypha2)
-                } else {
-                    (c.hypha2, c.hypha1)
-                };
-                self.state.connection_set.remove(&key);
+                self.state.connection_set.remove(&c.key());
                 self.state.connections.swap_remove(idx);
             }
 
@@ -2260,8 +2590,10 @@ impl Simulation {
                 }
             }
         }
+        self.profile.record(StepPhase::Flow, phase_started);
 
         // Update flow field (water flow direction and strength)
+        let phase_started = Instant::now();
         if self.config.flow_enabled {
             // Base flow direction with random variation
             let mut flow_dir = self.config.flow_direction;
@@ -2282,16 +2614,17 @@ impl Simulation {
             let flow_vy = flow_strength * flow_dir.sin();
 
             // Update flow field (could be spatially varying, but uniform for now)
-            for x in 0..self.config.grid_size {
-                for y in 0..self.config.grid_size {
-                    // Simple uniform flow field (could be made spatially varying later)
-                    self.state.flow_velocity_x[x][y] = flow_vx;
-                    self.state.flow_velocity_y[x][y] = flow_vy;
-                }
-            }
+            self.state.flow_velocity_x.fill(flow_vx);
+            self.state.flow_velocity_y.fill(flow_vy);
+            self.profile.add(
+                StepCounter::FlowFieldCells,
+                self.state.flow_velocity_x.len() as u64,
+            );
         }
+        self.profile.record(StepPhase::FlowField, phase_started);
 
         // Update zones: grow zones over time, competitors consume nutrients
+        let phase_started = Instant::now();
         if self.config.zones_enabled {
             for x in 0..self.config.grid_size {
                 for y in 0..self.config.grid_size {
@@ -2316,24 +2649,28 @@ impl Simulation {
                     }
                 }
             }
+            self.profile
+                .add(StepCounter::ZoneCells, self.state.zones.len() as u64);
         }
+        self.profile.record(StepPhase::Zones, phase_started);
 
         // Mycelial Density + Self-Inhibition: Update density map
+        let phase_started = Instant::now();
         if self.config.density_inhibition_enabled {
             // Decay density map over time
             let decay_rate = self.config.density_decay_rate;
-            for x in 0..self.state.density_map_size {
-                for y in 0..self.state.density_map_size {
-                    self.state.density_map[x][y] *= decay_rate;
-                }
+            for density in self.state.density_map.iter_mut() {
+                *density *= decay_rate;
             }
 
             // Add density based on current hyphae positions
             let density_increment = 1.0; // Amount of density added per hypha
+            let mut stamps = 0u64;
             for h in &self.state.hyphae {
                 if !h.alive {
                     continue;
                 }
+                stamps += 1;
                 let density_x = ((h.x * self.config.density_map_resolution as f32) as usize)
                     .min(self.state.density_map_size - 1);
                 let density_y = ((h.y * self.config.density_map_resolution as f32) as usize)
@@ -2354,12 +2691,19 @@ impl Simulation {
                     }
                 }
             }
+            self.profile.add(
+                StepCounter::DensityCells,
+                self.state.density_map.len() as u64,
+            );
+            self.profile.add(StepCounter::DensityStamps, stamps);
         }
+        self.profile.record(StepPhase::DensityMap, phase_started);
 
-        // diffuse nutrients (LOD: bounding box + frame skipping)
-        // Only skip diffusion when FPS is very low to prevent visual issues
-        let do_diffuse = if get_fps() < 25.0 {
-            (self.state.frame_index % 2) == 0 // Skip every other frame only when FPS < 25
+        // diffuse nutrients (LOD: bounding box + optional frame skipping)
+        // Frame skipping is an opt-in quality setting, never driven by render FPS
+        let phase_started = Instant::now();
+        let do_diffuse = if self.state.quality.skip_diffusion_every_other_step {
+            self.state.frame_index.is_multiple_of(2) // Skip every other frame
         } else {
             true
         };
@@ -2472,6 +2816,11 @@ impl Simulation {
                 }
             }
 
+            self.profile.add(
+                StepCounter::DiffusedCells,
+                ((x1 + 1).saturating_sub(x0) * (y1 + 1).saturating_sub(y0)) as u64,
+            );
+
             // Copy only the diffused active region back to main buffer
             // This preserves the rest of the grid while updating only where needed
             for x in x0..=x1 {
@@ -2486,6 +2835,10 @@ impl Simulation {
             let regen_rate = self.config.nutrient_regen_rate;
             let floor = self.config.nutrient_regen_floor;
             let grid_limit = self.config.grid_size - 1;
+            self.profile.add(
+                StepCounter::RegenSamples,
+                self.config.nutrient_regen_samples as u64,
+            );
             for _ in 0..self.config.nutrient_regen_samples {
                 let x = rng.gen_range(1..grid_limit);
                 let y = rng.gen_range(1..grid_limit);
@@ -2499,13 +2852,17 @@ impl Simulation {
                 }
             }
         }
+        self.profile.record(StepPhase::Diffusion, phase_started);
 
         // spores
+        let phase_started = Instant::now();
         let mut new_hyphae_from_spores = vec![];
+        let mut spores_moved = 0u64;
         for spore in &mut self.state.spores {
             if !spore.alive {
                 continue;
             }
+            spores_moved += 1;
             spore.x += spore.vx;
             spore.y += spore.vy;
             spore.age += 0.01;
@@ -2534,6 +2891,7 @@ impl Simulation {
 
             if total_nutrient > germination_threshold {
                 new_hyphae_from_spores.push(Hypha {
+                    id: 0,
                     x: spore.x,
                     y: spore.y,
                     prev_x: spore.x,
@@ -2551,13 +2909,16 @@ impl Simulation {
                     nitrogen: 0.0,
                 });
                 spore.alive = false;
+                self.events.spores_germinated.push((spore.x, spore.y));
                 // Particle burst at germination (visualization only - not used in tests)
+                // Uses macroquad's own RNG so visuals never perturb the seeded simulation stream
                 #[cfg(all(not(test), feature = "ui"))]
-                {
+                if !is_headless_mode() {
                     use macroquad::prelude::*;
                     for k in 0..8 {
-                        let a = (k as f32 / 8.0) * std::f32::consts::TAU + rng.gen_range(-0.2..0.2);
-                        let r = rng.gen_range(2.0..5.0);
+                        let a = (k as f32 / 8.0) * std::f32::consts::TAU
+                            + macroquad::rand::gen_range(-0.2, 0.2);
+                        let r = macroquad::rand::gen_range(2.0, 5.0);
                         let px = spore.x * self.config.cell_size + a.cos() * r;
                         let py = spore.y * self.config.cell_size + a.sin() * r;
                         draw_circle(px, py, 1.5, Color::new(1.0, 0.8, 0.3, 0.6));
@@ -2565,7 +2926,10 @@ impl Simulation {
                 }
             }
         }
-        self.state.hyphae.extend(new_hyphae_from_spores);
+        self.profile.add(StepCounter::SporesMoved, spores_moved);
+        for hypha in new_hyphae_from_spores {
+            self.state.add_hypha(hypha);
+        }
         self.state
             .spores
             .retain(|s| s.alive && s.age < self.config.spore_max_age);
@@ -2579,9 +2943,8 @@ impl Simulation {
                 total_energy += h.energy;
             }
         }
-        let fps = get_fps();
         self.state.fruit_cooldown_timer =
-            (self.state.fruit_cooldown_timer - 1.0 / fps.max(1.0)).max(0.0);
+            (self.state.fruit_cooldown_timer - self.state.dt).max(0.0);
         if self.state.fruit_cooldown_timer <= 0.0
             && hyphae_count >= self.config.fruiting_min_hyphae
             && total_energy >= self.config.fruiting_threshold_total_energy
@@ -2798,6 +3161,7 @@ impl Simulation {
             if needs_final_release {
                 fruit_spore_events.push((fx, fy));
             }
+            self.events.fruit_died.push((fx, fy));
             if energy > 0.0 {
                 let nutrient_return = energy * self.config.fruiting_nutrient_return_fraction;
                 if nutrient_return > 0.0 {
@@ -2860,6 +3224,7 @@ impl Simulation {
                     alive: true,
                     age: 0.0,
                 });
+                self.events.spores_released.push((sx, sy));
 
                 // Occasionally germinate immediately into a new hypha for faster colonization
                 if rng.gen_bool(0.3) {
@@ -2871,23 +3236,82 @@ impl Simulation {
                 }
             }
         }
+        self.profile.record(StepPhase::Spores, phase_started);
+
+        self.record_history();
+        self.profile.total = step_started.elapsed();
+        self.profile_stats.push(&self.profile);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use external_rand::rngs::StdRng;
-    use external_rand::SeedableRng;
 
     /// Helper function to create a simulation for testing
-    fn create_test_simulation() -> (Simulation, StdRng) {
-        let mut rng = StdRng::seed_from_u64(42);
+    fn create_test_simulation() -> (Simulation, SimRng) {
+        let mut rng = create_rng(42);
         let config = SimulationConfig::default();
         let sim = Simulation::with_config(&mut rng, config);
         (sim, rng)
     }
 
+    /// Helper function to flatten the simulation state into raw bits for exact comparison
+    fn state_fingerprint(state: &SimulationState) -> Vec<u64> {
+        let mut bits: Vec<u64> = Vec::new();
+        for h in &state.hyphae {
+            for v in [
+                h.x,
+                h.y,
+                h.angle,
+                h.energy,
+                h.age,
+                h.strength,
+                h.signal_received,
+                h.senescence_factor,
+                h.carbon,
+                h.nitrogen,
+            ] {
+                bits.push(v.to_bits() as u64);
+            }
+            bits.push(h.alive as u64);
+            bits.push(h.id);
+            bits.push(h.parent.map(|p| p + 1).unwrap_or(0));
+        }
+        for c in &state.connections {
+            bits.push(c.hypha1);
+            bits.push(c.hypha2);
+            for v in [c.strength, c.signal, c.flow_accumulator] {
+                bits.push(v.to_bits() as u64);
+            }
+        }
+        for s in &state.spores {
+            for v in [s.x, s.y, s.vx, s.vy, s.age] {
+                bits.push(v.to_bits() as u64);
+            }
+        }
+        for f in &state.fruit_bodies {
+            for v in [f.x, f.y, f.age, f.energy, f.lifespan] {
+                bits.push(v.to_bits() as u64);
+            }
+        }
+        for grid in [
+            &state.nutrients.sugar,
+            &state.nutrients.nitrogen,
+            &state.nutrient_memory,
+            &state.soil_moisture,
+            &state.light_exposure,
+        ] {
+            bits.extend(grid.iter().map(|v| v.to_bits() as u64));
+        }
+        let w = &state.weather;
+        for v in [w.temperature, w.humidity, w.rain, w.time] {
+            bits.push(v.to_bits() as u64);
+        }
+        bits.push(state.frame_index);
+        bits
+    }
+
     /// Test that simulation can be created and initialized
     #[test]
     fn test_simulation_creation() {
@@ -2896,7 +3320,7 @@ mod tests {
         // Check that simulation state is initialized
         assert_eq!(sim.state.hyphae.len(), sim.config.initial_hyphae_count);
         assert!(sim.state.frame_index == 0);
-        assert!(sim.state.nutrient_memory.len() == sim.config.grid_size);
+        assert!(sim.state.nutrient_memory.width() == sim.config.grid_size);
     }
 
     /// Test that simulation can run for multiple steps without panicking
@@ -2913,6 +3337,166 @@ mod tests {
         assert!(sim.state.frame_index > 0);
     }
 
+    /// Test that the same seed and config give bit-identical state after N steps
+    #[test]
+    fn test_seeded_runs_are_reproducible() {
+        let run = |seed: u64| {
+            let config = SimulationConfig {
+                seed: Some(seed),
+                ..SimulationConfig::default()
+            };
+            let mut rng = create_rng(seed);
+            let mut sim = Simulation::with_config(&mut rng, config);
+            for _ in 0..150 {
+                sim.step(&mut rng);
+            }
+            state_fingerprint(&sim.state)
+        };
+
+        let first = run(7);
+        let second = run(7);
+        assert!(
+            first == second,
+            "Same seed and config should reproduce the same state"
+        );
+        assert!(
+            first != run(8),
+            "Different seeds should produce different runs"
+        );
+    }
+
+    /// Test that the parallel growth phases give the same state on any number of threads
+    #[test]
+    fn test_parallel_growth_matches_serial() {
+        let run = |threads: usize| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            pool.install(|| {
+                let config = SimulationConfig {
+                    seed: Some(5),
+                    ..SimulationConfig::default()
+                };
+                let mut rng = create_rng(5);
+                let mut sim = Simulation::with_config(&mut rng, config);
+                for _ in 0..200 {
+                    sim.step(&mut rng);
+                }
+                state_fingerprint(&sim.state)
+            })
+        };
+
+        let serial = run(1);
+        assert_eq!(
+            serial,
+            run(4),
+            "Growth on 4 threads should match the single-threaded run"
+        );
+    }
+
+    /// Test that a snapshot restores the exact state and continues the run identically
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let config = SimulationConfig {
+            seed: Some(11),
+            ..SimulationConfig::default()
+        };
+        let mut rng = create_rng(11);
+        let mut sim = Simulation::with_config(&mut rng, config);
+        for _ in 0..60 {
+            sim.step(&mut rng);
+        }
+
+        let mut json = Vec::new();
+        sim.write_snapshot(&rng, &mut json).unwrap();
+        let (mut restored, mut restored_rng) =
+            Simulation::from_snapshot_json(std::str::from_utf8(&json).unwrap()).unwrap();
+        assert!(
+            state_fingerprint(&sim.state) == state_fingerprint(&restored.state),
+            "Restored snapshot should match the saved state"
+        );
+
+        // The original run keeps its own generator, the restored one continues with the saved copy
+        for _ in 0..60 {
+            sim.step(&mut rng);
+            restored.step(&mut restored_rng);
+        }
+        assert!(
+            state_fingerprint(&sim.state) == state_fingerprint(&restored.state),
+            "Restored simulation should continue identically"
+        );
+    }
+
+    /// Test that snapshots with mismatched grids or dangling hypha references are rejected
+    #[test]
+    fn test_snapshot_rejects_inconsistent_state() {
+        let mut rng = create_rng(4);
+        let mut sim = Simulation::with_config(&mut rng, SimulationConfig::default());
+        for _ in 0..20 {
+            sim.step(&mut rng);
+        }
+        let mut json = Vec::new();
+        sim.write_snapshot(&rng, &mut json).unwrap();
+        let snapshot: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        let load = |edit: &dyn Fn(&mut serde_json::Value)| {
+            let mut snapshot = snapshot.clone();
+            edit(&mut snapshot["state"]);
+            Simulation::from_snapshot_json(&snapshot.to_string()).map(|_| ())
+        };
+        assert!(load(&|_| {}).is_ok());
+
+        let rejected = [
+            load(&|s| {
+                s["nutrients"]["nitrogen"].as_array_mut().unwrap().pop();
+            }),
+            load(&|s| {
+                s["zones"][0].as_array_mut().unwrap().pop();
+            }),
+            load(&|s| s["density_map_size"] = 7.into()),
+            load(&|s| s["hyphae"][0]["parent"] = 999_999.into()),
+            load(&|s| s["hyphae"][0]["x"] = 1.0e6.into()),
+            load(&|s| {
+                s["connections"] = serde_json::json!([{
+                    "hypha1": 0, "hypha2": 999_999, "strength": 0.5, "signal": 0.0, "flow_accumulator": 0.0
+                }]);
+            }),
+        ];
+        for result in rejected {
+            assert!(result.is_err());
+        }
+    }
+
+    /// Test that the simulation advances by the configured fixed time step
+    #[test]
+    fn test_fixed_time_step() {
+        let mut rng = create_rng(42);
+        let config = SimulationConfig {
+            time_step: 0.05,
+            ..SimulationConfig::default()
+        };
+        let mut sim = Simulation::with_config(&mut rng, config);
+        assert_eq!(sim.state.dt, 0.05);
+        assert!(
+            !sim.state.quality.skip_diffusion_every_other_step,
+            "Diffusion skipping should be opt-in"
+        );
+
+        let mut expected_time = 0.0f32;
+        for _ in 0..50 {
+            sim.step(&mut rng);
+            expected_time += 0.05;
+        }
+        assert_eq!(sim.state.weather.time, expected_time);
+
+        let lod_config = SimulationConfig {
+            lod_skip_diffusion: true,
+            ..SimulationConfig::default()
+        };
+        let lod_sim = Simulation::with_config(&mut rng, lod_config);
+        assert!(lod_sim.state.quality.skip_diffusion_every_other_step);
+    }
+
     /// Test that hyphae have valid energy levels after running
     #[test]
     fn test_hyphae_energy_valid() {
@@ -2976,13 +3560,13 @@ mod tests {
         // Check that all connections reference valid hyphae
         for conn in &sim.state.connections {
             assert!(
-                conn.hypha1 < sim.state.hyphae.len(),
-                "Connection references invalid hypha1 index {}",
+                sim.state.hypha_slot(conn.hypha1).is_some(),
+                "Connection references unknown hypha1 id {}",
                 conn.hypha1
             );
             assert!(
-                conn.hypha2 < sim.state.hyphae.len(),
-                "Connection references invalid hypha2 index {}",
+                sim.state.hypha_slot(conn.hypha2).is_some(),
+                "Connection references unknown hypha2 id {}",
                 conn.hypha2
             );
             assert!(
@@ -3074,13 +3658,52 @@ mod tests {
 
         // Check that hyphae count doesn't exceed max_hyphae
         assert!(
-            sim.state.hyphae.len() <= sim.config.max_hyphae as usize,
+            sim.state.hyphae.len() <= sim.config.max_hyphae,
             "Hyphae count {} exceeds max_hyphae {}",
             sim.state.hyphae.len(),
             sim.config.max_hyphae
         );
     }
 
+    /// Test that hypha ids stay unique and resolvable through culling and fusion
+    #[test]
+    fn test_hypha_ids_stable() {
+        let (mut sim, mut rng) = create_test_simulation();
+        // Low enough that culling is sure to remove hyphae as the colony grows
+        sim.config.max_hyphae = 6;
+        sim.config.max_hyphae_branching_threshold = 50;
+        sim.config.fusion_enabled = true;
+
+        let mut seen = HashSet::new();
+        for _ in 0..200 {
+            sim.step(&mut rng);
+            assert_eq!(sim.state.hypha_slots.len(), sim.state.hyphae.len());
+            for (slot, h) in sim.state.hyphae.iter().enumerate() {
+                assert_eq!(sim.state.hypha_slot(h.id), Some(slot));
+                assert!(h.id < sim.state.next_hypha_id);
+                // Parents are always older, and resolve to themselves while they are still around
+                if let Some(parent) = h.parent {
+                    assert!(parent < h.id);
+                    if let Some(parent_slot) = sim.state.hypha_slot(parent) {
+                        assert_eq!(sim.state.hyphae[parent_slot].id, parent);
+                    }
+                }
+                seen.insert(h.id);
+            }
+            for conn in &sim.state.connections {
+                for id in [conn.hypha1, conn.hypha2] {
+                    let slot = sim
+                        .state
+                        .hypha_slot(id)
+                        .expect("connection to a removed hypha");
+                    assert_eq!(sim.state.hyphae[slot].id, id);
+                }
+            }
+        }
+        // Hyphae were removed along the way, and their ids were not handed out again
+        assert!(seen.len() > sim.state.hyphae.len());
+    }
+
     /// Test that weather affects the simulation
     #[test]
     fn test_weather_effects() {
@@ -3136,7 +3759,7 @@ mod tests {
         assert_eq!(connections_count, sim.state.connections.len());
         assert_eq!(fruit_count, sim.state.fruit_bodies.len());
         assert!(
-            avg_energy >= 0.0 && avg_energy <= 1.0,
+            (0.0..=1.0).contains(&avg_energy),
             "Average energy {} is out of range [0.0, 1.0]",
             avg_energy
         );
@@ -3206,7 +3829,10 @@ mod tests {
         }
 
         // Check that hyphae count is valid (may increase due to branching or decrease due to pruning)
-        assert!(sim.state.hyphae.len() > 0, "Should have at least one hypha");
+        assert!(
+            !sim.state.hyphae.is_empty(),
+            "Should have at least one hypha"
+        );
     }
 
     /// Test that fusion works when enabled
@@ -3222,7 +3848,10 @@ mod tests {
 
         // Fusion should work (may reduce hyphae count when they merge)
         // Just check that simulation doesn't crash and has valid state
-        assert!(sim.state.hyphae.len() > 0, "Should have at least one hypha");
+        assert!(
+            !sim.state.hyphae.is_empty(),
+            "Should have at least one hypha"
+        );
     }
 
     /// Test validation after 100 iterations
@@ -3254,8 +3883,8 @@ mod tests {
 
         // Validate connections
         for conn in &sim.state.connections {
-            assert!(conn.hypha1 < sim.state.hyphae.len());
-            assert!(conn.hypha2 < sim.state.hyphae.len());
+            assert!(sim.state.hypha_slot(conn.hypha1).is_some());
+            assert!(sim.state.hypha_slot(conn.hypha2).is_some());
             assert!(conn.strength >= 0.0 && conn.strength <= 1.0);
         }
 
@@ -3267,4 +3896,31 @@ mod tests {
             }
         }
     }
+
+    /// Test that environment edits only touch cells inside the requested disc
+    #[test]
+    fn test_environment_edits() {
+        let (mut sim, _) = create_test_simulation();
+
+        let cells = sim.paint_zone(50, 50, 3.0, ZoneType::Competitor, 0.6);
+        assert_eq!(cells, 29);
+        assert_eq!(sim.state.zones[50][53].zone_type, ZoneType::Competitor);
+        assert_eq!(sim.state.zones[50][50].intensity, 0.6);
+        assert_ne!(sim.state.zones[53][53].zone_type, ZoneType::Competitor);
+
+        sim.paint_zone(50, 50, 3.0, ZoneType::None, 1.0);
+        assert_eq!(sim.state.zones[50][50].zone_type, ZoneType::None);
+        assert_eq!(sim.state.zones[50][50].intensity, 0.0);
+
+        // Clipped at the grid edge
+        assert_eq!(sim.set_obstacles(0, 0, 1.0, true), 3);
+        assert!(sim.state.obstacles[1][0]);
+        sim.set_obstacles(0, 0, 1.0, false);
+        assert!(!sim.state.obstacles[0][0]);
+
+        sim.set_moisture_region(10, 10, 0.0, 0.9);
+        assert_eq!(sim.state.soil_moisture[10][10], 0.9);
+        sim.set_light_region(10, 10, 2.0, 2.0);
+        assert_eq!(sim.state.light_exposure[11][10], 1.0);
+    }
 }
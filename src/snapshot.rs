@@ -0,0 +1,187 @@
+// Simulation snapshots - save and restore the complete simulation state
+// Used to resume long runs, share colonies, and branch experiments from a known state
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::config::SimulationConfig;
+use crate::grid::Grid2D;
+use crate::simulation::{spatial_grid_size, SimRng, Simulation, SimulationState};
+
+/// Snapshot format version, bumped whenever the serialized state layout changes
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+// Borrowed view used for saving (avoids cloning every grid)
+#[derive(Serialize)]
+struct SnapshotRef<'a> {
+    version: u32,
+    config: &'a SimulationConfig,
+    state: &'a SimulationState,
+    rng: &'a SimRng,
+}
+
+#[derive(Deserialize)]
+struct Snapshot {
+    version: u32,
+    config: SimulationConfig,
+    state: SimulationState,
+    rng: SimRng,
+}
+
+impl Simulation {
+    /// Write the config, full simulation state and `rng`, the generator stepping this
+    /// simulation, as JSON.
+    pub fn write_snapshot<W: Write>(
+        &self,
+        rng: &SimRng,
+        writer: W,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let snapshot = SnapshotRef {
+            version: SNAPSHOT_VERSION,
+            config: &self.config,
+            state: &self.state,
+            rng,
+        };
+        serde_json::to_writer(writer, &snapshot)?;
+        Ok(())
+    }
+
+    /// Save a snapshot of the config, full simulation state and generator to a JSON file.
+    pub fn save_snapshot<P: AsRef<Path>>(
+        &self,
+        rng: &SimRng,
+        path: P,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut writer = BufWriter::new(std::fs::File::create(path)?);
+        self.write_snapshot(rng, &mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Restore a simulation and its generator from snapshot JSON. Stepping it with that
+    /// generator continues the run exactly as if it had never been saved.
+    /// Control flags (pause, overlays, speed) start from their defaults.
+    pub fn from_snapshot_json(json: &str) -> Result<(Self, SimRng), Box<dyn std::error::Error>> {
+        let snapshot: Snapshot = serde_json::from_str(json)?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(format!(
+                "Unsupported snapshot version {} (expected {})",
+                snapshot.version, SNAPSHOT_VERSION
+            )
+            .into());
+        }
+
+        snapshot.config.validate()?;
+
+        let mut state = snapshot.state;
+        check_state(&state, &snapshot.config)?;
+
+        state.rebuild_spatial_grid();
+        state.rebuild_hypha_slots();
+        Ok((Self::from_state(state, snapshot.config), snapshot.rng))
+    }
+
+    /// Load a simulation from a snapshot file written by `save_snapshot`.
+    pub fn load_snapshot<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<(Self, SimRng), Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Err(format!("Snapshot file not found: {}", path.display()).into());
+        }
+        let contents = std::fs::read_to_string(path)?;
+        Self::from_snapshot_json(&contents)
+    }
+}
+
+// Reject snapshots whose grids don't match the config or whose hypha references dangle:
+// either would panic on indexing in the next step
+fn check_state(state: &SimulationState, config: &SimulationConfig) -> Result<(), String> {
+    let grid_size = config.grid_size;
+    let grids = [
+        ("sugar", size(&state.nutrients.sugar)),
+        ("nitrogen", size(&state.nutrients.nitrogen)),
+        ("nutrients_back.sugar", size(&state.nutrients_back.sugar)),
+        (
+            "nutrients_back.nitrogen",
+            size(&state.nutrients_back.nitrogen),
+        ),
+        ("nutrient_memory", size(&state.nutrient_memory)),
+        ("obstacles", size(&state.obstacles)),
+        ("flow_velocity_x", size(&state.flow_velocity_x)),
+        ("flow_velocity_y", size(&state.flow_velocity_y)),
+        ("zones", size(&state.zones)),
+        ("soil_moisture", size(&state.soil_moisture)),
+        ("light_exposure", size(&state.light_exposure)),
+    ];
+    for (name, (width, height)) in grids {
+        if width != grid_size || height != grid_size {
+            return Err(format!(
+                "Snapshot grid {} is {}x{}, but grid_size {} from its config needs {}x{}",
+                name, width, height, grid_size, grid_size, grid_size
+            ));
+        }
+    }
+
+    let density_size = grid_size * config.density_map_resolution;
+    let (width, height) = size(&state.density_map);
+    if state.density_map_size != density_size || width != density_size || height != density_size {
+        return Err(format!(
+            "Snapshot density_map is {}x{} with density_map_size {}, but the config needs {}",
+            width, height, state.density_map_size, density_size
+        ));
+    }
+
+    let (nx, ny) = spatial_grid_size(config);
+    if (state.spatial_grid_nx, state.spatial_grid_ny) != (nx, ny) {
+        return Err(format!(
+            "Snapshot spatial grid is {}x{}, but the config needs {}x{}",
+            state.spatial_grid_nx, state.spatial_grid_ny, nx, ny
+        ));
+    }
+
+    let mut ids = HashSet::with_capacity(state.hyphae.len());
+    for h in &state.hyphae {
+        if !ids.insert(h.id) {
+            return Err(format!("Snapshot has more than one hypha with id {}", h.id));
+        }
+        if h.id >= state.next_hypha_id {
+            return Err(format!(
+                "Snapshot hypha id {} is not below next_hypha_id {}",
+                h.id, state.next_hypha_id
+            ));
+        }
+        let size = grid_size as f32;
+        if !(h.x >= 0.0 && h.x < size && h.y >= 0.0 && h.y < size) {
+            return Err(format!(
+                "Snapshot hypha {} at ({}, {}) is outside the {}x{} grid",
+                h.id, h.x, h.y, grid_size, grid_size
+            ));
+        }
+    }
+    for h in &state.hyphae {
+        if let Some(parent) = h.parent.filter(|parent| !ids.contains(parent)) {
+            return Err(format!(
+                "Snapshot hypha {} has parent {}, which is not a hypha",
+                h.id, parent
+            ));
+        }
+    }
+    for c in &state.connections {
+        for id in [c.hypha1, c.hypha2] {
+            if !ids.contains(&id) {
+                return Err(format!(
+                    "Snapshot connection {}-{} refers to hypha {}, which does not exist",
+                    c.hypha1, c.hypha2, id
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn size<T>(grid: &Grid2D<T>) -> (usize, usize) {
+    (grid.width(), grid.height())
+}
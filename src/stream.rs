@@ -0,0 +1,695 @@
+// Live state streaming - per-tick deltas pushed to WebSocket clients
+// Instead of polling GET /state (which clones every grid), clients receive only
+// what changed each tick: hyphae, connections, spore/fruit events and grid cells.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+use crate::api::{stats_to_response, HyphaData, StatsData};
+use crate::grid::Grid2D;
+use crate::simulation::Simulation;
+
+// Buffered events per client before it is considered lagging (and resynced)
+const STREAM_CAPACITY: usize = 256;
+
+// Grid cells are only reported once they drift this far from the last reported value,
+// otherwise slow global decay (memory, moisture) would resend every cell on every tick
+pub const GRID_CHANGE_EPSILON: f32 = 0.01;
+
+/// Channels a client can subscribe to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamChannel {
+    Hyphae,
+    Connections,
+    Spores,
+    FruitBodies,
+    Grids,
+    Stats,
+}
+
+impl StreamChannel {
+    pub const ALL: [StreamChannel; 6] = [
+        StreamChannel::Hyphae,
+        StreamChannel::Connections,
+        StreamChannel::Spores,
+        StreamChannel::FruitBodies,
+        StreamChannel::Grids,
+        StreamChannel::Stats,
+    ];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim() {
+            "hyphae" => Some(StreamChannel::Hyphae),
+            "connections" => Some(StreamChannel::Connections),
+            "spores" => Some(StreamChannel::Spores),
+            "fruit_bodies" => Some(StreamChannel::FruitBodies),
+            "grids" => Some(StreamChannel::Grids),
+            "stats" => Some(StreamChannel::Stats),
+            _ => None,
+        }
+    }
+}
+
+/// Grid layers tracked for cell-level changes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GridLayer {
+    Sugar,
+    Nitrogen,
+    NutrientMemory,
+    SoilMoisture,
+    LightExposure,
+    Obstacles,
+}
+
+impl GridLayer {
+    pub const ALL: [GridLayer; 6] = [
+        GridLayer::Sugar,
+        GridLayer::Nitrogen,
+        GridLayer::NutrientMemory,
+        GridLayer::SoilMoisture,
+        GridLayer::LightExposure,
+        GridLayer::Obstacles,
+    ];
+
+    fn value(self, sim: &Simulation, x: usize, y: usize) -> f32 {
+        let state = &sim.state;
+        match self {
+            GridLayer::Sugar => state.nutrients.sugar[x][y],
+            GridLayer::Nitrogen => state.nutrients.nitrogen[x][y],
+            GridLayer::NutrientMemory => state.nutrient_memory[x][y],
+            GridLayer::SoilMoisture => state.soil_moisture[x][y],
+            GridLayer::LightExposure => state.light_exposure[x][y],
+            GridLayer::Obstacles => {
+                if state.obstacles[x][y] {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct IndexedHypha {
+    pub index: usize,
+    #[serde(flatten)]
+    pub hypha: HyphaData,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct HyphaMove {
+    pub index: usize,
+    pub id: u64,
+    pub x: f32,
+    pub y: f32,
+    pub energy: f32,
+}
+
+/// A slot in `state.hyphae` and the id of the hypha in it
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct HyphaSlot {
+    pub index: usize,
+    pub id: u64,
+}
+
+/// Hypha changes, keyed by slot index in `state.hyphae`. Every entry also carries the hypha id,
+/// so clients can follow a hypha across frames even when its slot is reused.
+/// Apply in order: `added`, `moved`, `died`, then truncate the list to `len`.
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct HyphaeDelta {
+    pub added: Vec<IndexedHypha>,
+    pub moved: Vec<HyphaMove>,
+    pub died: Vec<HyphaSlot>,
+    pub len: usize,
+}
+
+/// Connection changes as hypha id pairs, smaller id first
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct ConnectionsDelta {
+    pub added: Vec<(u64, u64)>,
+    pub removed: Vec<(u64, u64)>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug)]
+pub struct PointEvent {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl From<&(f32, f32)> for PointEvent {
+    fn from(&(x, y): &(f32, f32)) -> Self {
+        Self { x, y }
+    }
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct SporesDelta {
+    pub released: Vec<PointEvent>,
+    pub germinated: Vec<PointEvent>,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct FruitBodiesDelta {
+    pub spawned: Vec<PointEvent>,
+    pub died: Vec<PointEvent>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct GridCellChange {
+    pub layer: GridLayer,
+    pub x: usize,
+    pub y: usize,
+    pub value: f32,
+}
+
+/// Everything that changed between `from_frame` and `frame_index`.
+/// Channels a client is not subscribed to are left out.
+#[derive(Serialize, Clone, Debug)]
+pub struct StateDelta {
+    pub from_frame: u64,
+    pub frame_index: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hyphae: Option<HyphaeDelta>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connections: Option<ConnectionsDelta>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spores: Option<SporesDelta>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fruit_bodies: Option<FruitBodiesDelta>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grids: Option<Vec<GridCellChange>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<StatsData>,
+}
+
+impl StateDelta {
+    /// Keep only the channels in `channels`
+    pub fn filtered(&self, channels: &HashSet<StreamChannel>) -> StateDelta {
+        let pick = |channel| channels.contains(&channel);
+        StateDelta {
+            from_frame: self.from_frame,
+            frame_index: self.frame_index,
+            hyphae: self.hyphae.clone().filter(|_| pick(StreamChannel::Hyphae)),
+            connections: self
+                .connections
+                .clone()
+                .filter(|_| pick(StreamChannel::Connections)),
+            spores: self.spores.clone().filter(|_| pick(StreamChannel::Spores)),
+            fruit_bodies: self
+                .fruit_bodies
+                .clone()
+                .filter(|_| pick(StreamChannel::FruitBodies)),
+            grids: self.grids.clone().filter(|_| pick(StreamChannel::Grids)),
+            stats: self.stats.clone().filter(|_| pick(StreamChannel::Stats)),
+        }
+    }
+
+    /// Fold a later delta into this one (used to throttle clients without losing events).
+    /// Events are concatenated; positions and grid values keep only their latest value.
+    pub fn merge(&mut self, later: StateDelta) {
+        self.frame_index = later.frame_index;
+
+        if let Some(next) = later.hyphae {
+            let current = self.hyphae.get_or_insert_with(HyphaeDelta::default);
+            let replaced: HashSet<usize> = next.added.iter().map(|h| h.index).collect();
+            let moved_now: HashSet<usize> = next.moved.iter().map(|m| m.index).collect();
+            current.died.retain(|d| !replaced.contains(&d.index));
+            current
+                .moved
+                .retain(|m| !replaced.contains(&m.index) && !moved_now.contains(&m.index));
+            for m in next.moved {
+                // A slot added earlier in the batch just carries its newest position
+                if let Some(added) = current.added.iter_mut().rev().find(|h| h.index == m.index) {
+                    added.hypha.x = m.x;
+                    added.hypha.y = m.y;
+                    added.hypha.energy = m.energy;
+                } else {
+                    current.moved.push(m);
+                }
+            }
+            current.added.extend(next.added);
+            current.died.extend(next.died);
+            current.len = next.len;
+        }
+
+        if let Some(next) = later.connections {
+            let current = self
+                .connections
+                .get_or_insert_with(ConnectionsDelta::default);
+            for key in next.added {
+                if let Some(pos) = current.removed.iter().position(|k| *k == key) {
+                    current.removed.swap_remove(pos);
+                } else {
+                    current.added.push(key);
+                }
+            }
+            for key in next.removed {
+                if let Some(pos) = current.added.iter().position(|k| *k == key) {
+                    current.added.swap_remove(pos);
+                } else {
+                    current.removed.push(key);
+                }
+            }
+        }
+
+        if let Some(next) = later.spores {
+            let current = self.spores.get_or_insert_with(SporesDelta::default);
+            current.released.extend(next.released);
+            current.germinated.extend(next.germinated);
+        }
+
+        if let Some(next) = later.fruit_bodies {
+            let current = self
+                .fruit_bodies
+                .get_or_insert_with(FruitBodiesDelta::default);
+            current.spawned.extend(next.spawned);
+            current.died.extend(next.died);
+        }
+
+        if let Some(next) = later.grids {
+            let current = self.grids.get_or_insert_with(Vec::new);
+            let mut latest: BTreeMap<(GridLayer, usize, usize), f32> = current
+                .iter()
+                .map(|c| ((c.layer, c.x, c.y), c.value))
+                .collect();
+            for c in next {
+                latest.insert((c.layer, c.x, c.y), c.value);
+            }
+            *current = latest
+                .into_iter()
+                .map(|((layer, x, y), value)| GridCellChange { layer, x, y, value })
+                .collect();
+        }
+
+        if later.stats.is_some() {
+            self.stats = later.stats;
+        }
+    }
+}
+
+// Per-slot summary of a hypha from the previous tick
+#[derive(Clone, Copy)]
+struct HyphaSummary {
+    id: u64,
+    x: f32,
+    y: f32,
+    alive: bool,
+}
+
+/// Remembers the last published state and computes deltas against it
+pub struct DeltaTracker {
+    frame_index: u64,
+    hyphae: Vec<HyphaSummary>,
+    connections: HashSet<(u64, u64)>,
+    // Last reported value per layer
+    grids: Vec<Grid2D<f32>>,
+}
+
+fn hypha_data(sim: &Simulation, index: usize) -> HyphaData {
+    let h = &sim.state.hyphae[index];
+    HyphaData {
+        id: h.id,
+        x: h.x,
+        y: h.y,
+        prev_x: h.prev_x,
+        prev_y: h.prev_y,
+        angle: h.angle,
+        alive: h.alive,
+        energy: h.energy,
+        parent: h.parent,
+        age: h.age,
+        strength: h.strength,
+        senescence_factor: h.senescence_factor,
+        carbon: h.carbon,
+        nitrogen: h.nitrogen,
+    }
+}
+
+fn connection_keys(sim: &Simulation) -> HashSet<(u64, u64)> {
+    sim.state.connections.iter().map(|c| c.key()).collect()
+}
+
+impl DeltaTracker {
+    /// Start tracking from the current simulation state
+    pub fn new(sim: &Simulation) -> Self {
+        let grid_size = sim.config.grid_size;
+        let grids = GridLayer::ALL
+            .iter()
+            .map(|layer| Grid2D::from_fn(grid_size, grid_size, |x, y| layer.value(sim, x, y)))
+            .collect();
+        Self {
+            frame_index: sim.state.frame_index,
+            hyphae: Self::summarize(sim),
+            connections: connection_keys(sim),
+            grids,
+        }
+    }
+
+    fn summarize(sim: &Simulation) -> Vec<HyphaSummary> {
+        sim.state
+            .hyphae
+            .iter()
+            .map(|h| HyphaSummary {
+                id: h.id,
+                x: h.x,
+                y: h.y,
+                alive: h.alive,
+            })
+            .collect()
+    }
+
+    /// Compute everything that changed since the last call and advance the baseline
+    pub fn diff(&mut self, sim: &Simulation) -> StateDelta {
+        let mut hyphae = HyphaeDelta {
+            len: sim.state.hyphae.len(),
+            ..HyphaeDelta::default()
+        };
+        for (index, h) in sim.state.hyphae.iter().enumerate() {
+            match self.hyphae.get(index) {
+                // A slot is new (or reused by a different hypha after removal)
+                None => hyphae.added.push(IndexedHypha {
+                    index,
+                    hypha: hypha_data(sim, index),
+                }),
+                Some(prev) if h.id != prev.id || (h.alive && !prev.alive) => {
+                    hyphae.added.push(IndexedHypha {
+                        index,
+                        hypha: hypha_data(sim, index),
+                    })
+                }
+                Some(prev) => {
+                    // Tips can move and die within the same step, so report both
+                    if (prev.alive || h.alive) && (h.x != prev.x || h.y != prev.y) {
+                        hyphae.moved.push(HyphaMove {
+                            index,
+                            id: h.id,
+                            x: h.x,
+                            y: h.y,
+                            energy: h.energy,
+                        });
+                    }
+                    if prev.alive && !h.alive {
+                        hyphae.died.push(HyphaSlot { index, id: h.id });
+                    }
+                }
+            }
+        }
+        self.hyphae = Self::summarize(sim);
+
+        let connections_now = connection_keys(sim);
+        let mut connections = ConnectionsDelta {
+            added: connections_now
+                .difference(&self.connections)
+                .copied()
+                .collect(),
+            removed: self
+                .connections
+                .difference(&connections_now)
+                .copied()
+                .collect(),
+        };
+        connections.added.sort_unstable();
+        connections.removed.sort_unstable();
+        self.connections = connections_now;
+
+        let mut grids = Vec::new();
+        for (layer, reported) in GridLayer::ALL.iter().zip(self.grids.iter_mut()) {
+            let grid_size = reported.width().min(sim.config.grid_size);
+            for x in 0..grid_size {
+                for y in 0..grid_size {
+                    let value = layer.value(sim, x, y);
+                    let last = &mut reported[x][y];
+                    if (value - *last).abs() >= GRID_CHANGE_EPSILON {
+                        *last = value;
+                        grids.push(GridCellChange {
+                            layer: *layer,
+                            x,
+                            y,
+                            value,
+                        });
+                    }
+                }
+            }
+        }
+
+        let events = &sim.events;
+        let delta = StateDelta {
+            from_frame: self.frame_index,
+            frame_index: sim.state.frame_index,
+            hyphae: Some(hyphae),
+            connections: Some(connections),
+            spores: Some(SporesDelta {
+                released: events
+                    .spores_released
+                    .iter()
+                    .map(PointEvent::from)
+                    .collect(),
+                germinated: events
+                    .spores_germinated
+                    .iter()
+                    .map(PointEvent::from)
+                    .collect(),
+            }),
+            fruit_bodies: Some(FruitBodiesDelta {
+                spawned: events.fruit_spawned.iter().map(PointEvent::from).collect(),
+                died: events.fruit_died.iter().map(PointEvent::from).collect(),
+            }),
+            grids: Some(grids),
+            stats: Some(stats_to_response(sim)),
+        };
+        self.frame_index = sim.state.frame_index;
+        delta
+    }
+}
+
+/// Messages fanned out to every connected stream client
+#[derive(Clone, Debug)]
+pub enum StreamEvent {
+    Delta(Arc<StateDelta>),
+    // The simulation was replaced (reset, snapshot load): clients need the full state again
+    Resync,
+}
+
+/// Shared publisher for live deltas.
+/// Deltas are only computed while at least one client is connected.
+pub struct StreamHub {
+    sender: broadcast::Sender<StreamEvent>,
+    tracker: Mutex<Option<DeltaTracker>>,
+}
+
+impl Default for StreamHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StreamHub {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(STREAM_CAPACITY);
+        Self {
+            sender,
+            tracker: Mutex::new(None),
+        }
+    }
+
+    /// Subscribe a client whose baseline is the current state of `sim`.
+    /// Call while holding the simulation lock the baseline is taken under, so the next
+    /// `publish` sends the client every change made after it.
+    pub fn subscribe(&self, sim: &Simulation) -> broadcast::Receiver<StreamEvent> {
+        if let Ok(mut tracker) = self.tracker.lock() {
+            if tracker.is_none() {
+                *tracker = Some(DeltaTracker::new(sim));
+            }
+        }
+        self.sender.subscribe()
+    }
+
+    /// Publish what changed in `sim` since the last call. Call after every step.
+    pub fn publish(&self, sim: &Simulation) {
+        let Ok(mut tracker) = self.tracker.lock() else {
+            return;
+        };
+        if self.sender.receiver_count() == 0 {
+            *tracker = None;
+            return;
+        }
+        // Subscribing starts the tracker, so every client already has the baseline it diffs from
+        if let Some(tracker) = tracker.as_mut() {
+            let delta = tracker.diff(sim);
+            // Only fails when every client disconnected in the meantime
+            let _ = self.sender.send(StreamEvent::Delta(Arc::new(delta)));
+        }
+    }
+
+    /// Tell clients the simulation was replaced and restart tracking from `sim`
+    pub fn resync(&self, sim: &Simulation) {
+        if let Ok(mut tracker) = self.tracker.lock() {
+            *tracker = if self.sender.receiver_count() > 0 {
+                Some(DeltaTracker::new(sim))
+            } else {
+                None
+            };
+        }
+        let _ = self.sender.send(StreamEvent::Resync);
+    }
+}
+
+/// Subscription settings sent by a client (as JSON text frames or `/ws` query parameters)
+#[derive(Deserialize, Debug, Default)]
+pub struct StreamRequest {
+    /// Channels to receive (all channels when never set)
+    pub channels: Option<Vec<StreamChannel>>,
+    /// Minimum milliseconds between updates; deltas in between are merged (0 = every tick)
+    pub interval_ms: Option<u64>,
+}
+
+impl StreamRequest {
+    /// Update a client's settings with the fields present in this request
+    pub fn apply(self, channels: &mut HashSet<StreamChannel>, interval_ms: &mut u64) {
+        if let Some(list) = self.channels {
+            *channels = list.into_iter().collect();
+        }
+        if let Some(ms) = self.interval_ms {
+            *interval_ms = ms;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::simulation_to_response;
+    use crate::config::SimulationConfig;
+    use crate::simulation::create_rng;
+
+    /// Test that applying streamed deltas rebuilds the same hyphae as the simulation
+    #[test]
+    fn test_deltas_replay_hyphae() {
+        let mut rng = create_rng(3);
+        let mut sim = Simulation::with_config(&mut rng, SimulationConfig::default());
+        let mut tracker = DeltaTracker::new(&sim);
+        let mut positions: Vec<Option<(f32, f32, bool)>> = sim
+            .state
+            .hyphae
+            .iter()
+            .map(|h| Some((h.x, h.y, h.alive)))
+            .collect();
+
+        // Throttled client: merge several ticks before applying
+        for _ in 0..20 {
+            let mut batch: Option<StateDelta> = None;
+            for _ in 0..5 {
+                sim.step(&mut rng);
+                let delta = tracker.diff(&sim);
+                match batch.as_mut() {
+                    Some(batch) => batch.merge(delta),
+                    None => batch = Some(delta),
+                }
+            }
+            let hyphae = batch.unwrap().hyphae.unwrap();
+            for added in hyphae.added {
+                if positions.len() <= added.index {
+                    positions.resize(added.index + 1, None);
+                }
+                positions[added.index] = Some((added.hypha.x, added.hypha.y, added.hypha.alive));
+            }
+            for m in hyphae.moved {
+                let slot = positions[m.index].as_mut().unwrap();
+                slot.0 = m.x;
+                slot.1 = m.y;
+            }
+            for d in hyphae.died {
+                positions[d.index].as_mut().unwrap().2 = false;
+            }
+            positions.truncate(hyphae.len);
+        }
+
+        let expected: Vec<Option<(f32, f32, bool)>> = sim
+            .state
+            .hyphae
+            .iter()
+            .map(|h| Some((h.x, h.y, h.alive)))
+            .collect();
+        assert!(
+            positions == expected,
+            "Replaying merged deltas should reproduce hypha positions"
+        );
+    }
+
+    /// Test that a subscriber joining mid-run gets every change after its baseline
+    #[test]
+    fn test_first_subscriber_gets_every_change() {
+        let mut rng = create_rng(6);
+        let mut sim = Simulation::with_config(&mut rng, SimulationConfig::default());
+        for _ in 0..30 {
+            sim.step(&mut rng);
+        }
+        let hub = StreamHub::new();
+        let mut events = hub.subscribe(&sim);
+        let baseline = simulation_to_response(&sim);
+        let mut hyphae: Vec<(u64, f32, f32, bool)> = baseline
+            .hyphae
+            .iter()
+            .map(|h| (h.id, h.x, h.y, h.alive))
+            .collect();
+        let mut connections: HashSet<(u64, u64)> = baseline
+            .connections
+            .iter()
+            .map(|c| (c.hypha1, c.hypha2))
+            .collect();
+
+        for _ in 0..2 {
+            sim.step(&mut rng);
+            hub.publish(&sim);
+        }
+        let mut frames = Vec::new();
+        while let Ok(StreamEvent::Delta(delta)) = events.try_recv() {
+            frames.push(delta.frame_index);
+            let changed = delta.connections.clone().unwrap();
+            connections.extend(changed.added);
+            for key in &changed.removed {
+                connections.remove(key);
+            }
+            let delta = delta.hyphae.clone().unwrap();
+            for added in delta.added {
+                if hyphae.len() <= added.index {
+                    hyphae.resize(added.index + 1, (0, 0.0, 0.0, false));
+                }
+                let h = &added.hypha;
+                hyphae[added.index] = (h.id, h.x, h.y, h.alive);
+            }
+            // Moves and deaths name the hypha that was in the slot all along
+            for m in delta.moved {
+                assert_eq!(hyphae[m.index].0, m.id);
+                hyphae[m.index].1 = m.x;
+                hyphae[m.index].2 = m.y;
+            }
+            for d in delta.died {
+                assert_eq!(hyphae[d.index].0, d.id);
+                hyphae[d.index].3 = false;
+            }
+            hyphae.truncate(delta.len);
+        }
+        let baseline_frame = baseline.stats.frame_index;
+        assert_eq!(frames, [baseline_frame + 1, baseline_frame + 2]);
+
+        let state = simulation_to_response(&sim);
+        let expected: Vec<(u64, f32, f32, bool)> = state
+            .hyphae
+            .iter()
+            .map(|h| (h.id, h.x, h.y, h.alive))
+            .collect();
+        assert_eq!(hyphae, expected);
+        let expected: HashSet<(u64, u64)> = state
+            .connections
+            .iter()
+            .map(|c| (c.hypha1, c.hypha2))
+            .collect();
+        assert_eq!(connections, expected);
+    }
+}
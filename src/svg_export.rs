@@ -0,0 +1,279 @@
+// Vector export - write the colony as SVG for print-quality figures
+// Segments, connections, hyphae and fruit bodies are vector shapes styled like the window;
+// heatmaps (and the memory overlay) are optional raster layers embedded at one pixel per grid cell
+
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::render::{RenderOptions, Shape, BACKGROUND};
+use crate::simulation::Simulation;
+
+impl Simulation {
+    /// Write the colony as SVG. `options` picks the layers like for PNG renders; `scale` only sets
+    /// the nominal width and height, since the drawing itself is resolution independent.
+    pub fn write_svg<W: Write>(
+        &self,
+        mut w: W,
+        options: &RenderOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let side = self.config.grid_size as f32 * self.config.cell_size;
+        let size = side * options.scale;
+        writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            w,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="{0}" height="{0}" viewBox="0 0 {1} {1}">"#,
+            size, side
+        )?;
+        writeln!(w, "  <title>mycorust frame {}</title>", self.frame_index)?;
+        writeln!(
+            w,
+            r#"  <rect width="{0}" height="{0}" fill="{1}"/>"#,
+            side,
+            hex_color(&BACKGROUND)
+        )?;
+
+        // Same order as the window and the PNG renderer
+        self.write_raster_layer(&mut w, "heatmaps", &self.heatmap_shapes(options))?;
+        write_group(
+            &mut w,
+            "terrain",
+            r#" shape-rendering="crispEdges""#,
+            &self.terrain_shapes(),
+        )?;
+        if options.segments {
+            write_group(
+                &mut w,
+                "segments",
+                r#" stroke-linecap="round""#,
+                &self.segment_shapes(),
+            )?;
+        }
+        if options.connections {
+            write_group(
+                &mut w,
+                "connections",
+                r#" stroke-linecap="round""#,
+                &self.connection_shapes(),
+            )?;
+        }
+        if options.memory {
+            self.write_raster_layer(&mut w, "memory", &self.memory_shapes())?;
+        }
+        if options.hyphae {
+            write_group(&mut w, "hyphae", "", &self.hypha_shapes())?;
+        }
+        if options.fruit_bodies {
+            write_group(
+                &mut w,
+                "fruit_bodies",
+                r#" stroke-linecap="round""#,
+                &self.fruit_body_shapes(),
+            )?;
+        }
+        writeln!(w, "</svg>")?;
+        Ok(())
+    }
+
+    /// Save the colony as an SVG file.
+    pub fn save_svg<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: &RenderOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut writer = BufWriter::new(std::fs::File::create(path)?);
+        self.write_svg(&mut writer, options)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    // Grid-cell shapes as an embedded PNG stretched over the grid (nothing when empty)
+    fn write_raster_layer<W: Write>(
+        &self,
+        w: &mut W,
+        id: &str,
+        shapes: &[Shape],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(image) = self.render_cell_layer(shapes) else {
+            return Ok(());
+        };
+        let mut png = Vec::new();
+        image.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)?;
+        let side = self.config.grid_size as f32 * self.config.cell_size;
+        writeln!(
+            w,
+            r#"  <image id="{0}" width="{1}" height="{1}" preserveAspectRatio="none" style="image-rendering:pixelated" xlink:href="data:image/png;base64,{2}"/>"#,
+            id,
+            side,
+            base64(&png)
+        )?;
+        Ok(())
+    }
+}
+
+fn write_group<W: Write>(
+    w: &mut W,
+    id: &str,
+    attributes: &str,
+    shapes: &[Shape],
+) -> Result<(), Box<dyn std::error::Error>> {
+    if shapes.is_empty() {
+        return Ok(());
+    }
+    writeln!(w, r#"  <g id="{}"{}>"#, id, attributes)?;
+    for shape in shapes {
+        write_shape(w, shape)?;
+    }
+    writeln!(w, "  </g>")?;
+    Ok(())
+}
+
+fn write_shape<W: Write>(w: &mut W, shape: &Shape) -> std::io::Result<()> {
+    match *shape {
+        Shape::Rect {
+            x,
+            y,
+            w: width,
+            h,
+            color,
+        } => writeln!(
+            w,
+            r#"    <rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="{}"{}/>"#,
+            x,
+            y,
+            width,
+            h,
+            hex_color(&color),
+            opacity("fill-opacity", color[3])
+        ),
+        Shape::Line {
+            from,
+            to,
+            width,
+            color,
+        } => writeln!(
+            w,
+            r#"    <line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke="{}" stroke-width="{:.2}"{}/>"#,
+            from.0,
+            from.1,
+            to.0,
+            to.1,
+            hex_color(&color),
+            width,
+            opacity("stroke-opacity", color[3])
+        ),
+        Shape::Circle {
+            center,
+            radius,
+            color,
+        } => writeln!(
+            w,
+            r#"    <circle cx="{:.2}" cy="{:.2}" r="{:.2}" fill="{}"{}/>"#,
+            center.0,
+            center.1,
+            radius,
+            hex_color(&color),
+            opacity("fill-opacity", color[3])
+        ),
+    }
+}
+
+fn hex_color(color: &[f32]) -> String {
+    let byte = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        byte(color[0]),
+        byte(color[1]),
+        byte(color[2])
+    )
+}
+
+// Opacity attribute, left out when fully opaque
+fn opacity(name: &str, alpha: f32) -> String {
+    if alpha >= 1.0 {
+        String::new()
+    } else {
+        format!(r#" {}="{:.3}""#, name, alpha.max(0.0))
+    }
+}
+
+// Standard base64 with padding, for data URIs
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[((n >> (18 - 6 * i)) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimulationConfig;
+    use crate::simulation::create_rng;
+
+    // A 40x40 colony after a few steps, so there are segments to draw
+    fn grown_simulation() -> Simulation {
+        let config = SimulationConfig {
+            grid_size: 40,
+            ..SimulationConfig::default()
+        };
+        let mut rng = create_rng(5);
+        let mut sim = Simulation::with_config(&mut rng, config);
+        for _ in 0..40 {
+            sim.step(&mut rng);
+        }
+        sim
+    }
+
+    /// Test that base64 pads partial groups
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b"Man"), "TWFu");
+        assert_eq!(base64(b"Ma"), "TWE=");
+        assert_eq!(base64(b"M"), "TQ==");
+    }
+
+    /// Test that vector layers become one SVG line per drawn segment, without raster images
+    #[test]
+    fn test_svg_vector_layers() {
+        let sim = grown_simulation();
+        let mut vector_only = Vec::new();
+        let options = RenderOptions::with_layers("segments,connections,fruit_bodies", 2.0).unwrap();
+        sim.write_svg(&mut vector_only, &options).unwrap();
+        let svg = String::from_utf8(vector_only).unwrap();
+        assert!(svg.contains(r#"viewBox="0 0 "#));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert!(!svg.contains("<image"));
+        assert_eq!(
+            svg.matches("<line").count(),
+            sim.segment_shapes().len()
+                + sim.connection_shapes().len()
+                + sim
+                    .fruit_body_shapes()
+                    .iter()
+                    .filter(|s| matches!(s, Shape::Line { .. }))
+                    .count()
+        );
+    }
+
+    /// Test that heatmaps are embedded as one raster layer
+    #[test]
+    fn test_svg_heatmap_raster() {
+        let sim = grown_simulation();
+        let mut with_heatmaps = Vec::new();
+        sim.write_svg(&mut with_heatmaps, &RenderOptions::default())
+            .unwrap();
+        let svg = String::from_utf8(with_heatmaps).unwrap();
+        assert_eq!(svg.matches("data:image/png;base64,").count(), 1);
+    }
+}
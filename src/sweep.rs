@@ -0,0 +1,407 @@
+// Parameter sweeps - run every combination of config values (times N replicates)
+// in parallel and collect one summary row per run for sensitivity analyses
+
+use rayon::prelude::*;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::batch::{step_and_summarize, write_batch_outputs, BatchSummary};
+use crate::config::SimulationConfig;
+use crate::history::csv_field;
+use crate::simulation::{create_rng, Simulation};
+
+// Range values are rounded to this many decimals so 0.1 steps don't print as 0.30000000000000004
+const RANGE_DECIMALS: i32 = 9;
+
+/// Values for one swept field: an explicit list, or a numeric range
+/// (`from`..=`to` by `step`, or `count` evenly spaced values)
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ParameterValues {
+    List(Vec<serde_json::Value>),
+    Range {
+        from: f64,
+        to: f64,
+        step: Option<f64>,
+        count: Option<usize>,
+    },
+}
+
+impl ParameterValues {
+    fn expand(&self, field: &str) -> Result<Vec<serde_json::Value>, String> {
+        let (from, to, step, count) = match self {
+            ParameterValues::List(values) if values.is_empty() => {
+                return Err(format!("{}: value list is empty", field))
+            }
+            ParameterValues::List(values) => return Ok(values.clone()),
+            ParameterValues::Range {
+                from,
+                to,
+                step,
+                count,
+            } => (*from, *to, *step, *count),
+        };
+        if !from.is_finite() || !to.is_finite() || to < from {
+            return Err(format!("{}: range needs finite from <= to", field));
+        }
+        let values: Vec<f64> = match (step, count) {
+            (Some(step), None) if step > 0.0 => {
+                // Small tolerance so `to` is included despite rounding
+                let n = ((to - from) / step + 1e-9).floor() as usize;
+                (0..=n).map(|i| from + i as f64 * step).collect()
+            }
+            (None, Some(count)) if count >= 2 => (0..count)
+                .map(|i| from + (to - from) * i as f64 / (count - 1) as f64)
+                .collect(),
+            (None, Some(1)) => vec![from],
+            _ => {
+                return Err(format!(
+                    "{}: range needs either a positive `step` or a `count` of at least 1",
+                    field
+                ))
+            }
+        };
+        let scale = 10f64.powi(RANGE_DECIMALS);
+        Ok(values
+            .into_iter()
+            .map(|v| {
+                let v = (v * scale).round() / scale;
+                // Whole numbers stay integers so they also fit integer fields
+                if v.fract() == 0.0 && v.abs() < i64::MAX as f64 {
+                    serde_json::Value::from(v as i64)
+                } else {
+                    serde_json::Value::from(v)
+                }
+            })
+            .collect())
+    }
+}
+
+fn default_replicates() -> u32 {
+    1
+}
+
+/// A sweep specification file (YAML or JSON)
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SweepSpec {
+    /// Config fields applied to every run, on top of the base config
+    #[serde(default)]
+    pub base: serde_json::Map<String, serde_json::Value>,
+    /// Swept config fields; every combination of their values is one sweep point
+    pub parameters: BTreeMap<String, ParameterValues>,
+    /// Runs per point, each with its own seed
+    #[serde(default = "default_replicates")]
+    pub replicates: u32,
+    /// Steps per run
+    pub steps: u64,
+    /// Seed of replicate 0; replicate r uses `seed + r` at every point
+    pub seed: Option<u64>,
+    /// Also write each run's snapshot, history, network and summary under `runs/<run_id>/`
+    #[serde(default)]
+    pub save_runs: bool,
+}
+
+/// One run of a sweep: a point in parameter space and a replicate seed
+#[derive(Clone, Debug)]
+pub struct SweepRun {
+    pub run_id: String,
+    pub point: usize,
+    pub replicate: u32,
+    pub seed: u64,
+    pub params: Vec<(String, serde_json::Value)>,
+    pub config: SimulationConfig,
+}
+
+/// A finished sweep run
+#[derive(Clone, Debug)]
+pub struct SweepResult {
+    pub run: SweepRun,
+    pub summary: BatchSummary,
+}
+
+impl SweepSpec {
+    /// Read a sweep specification from a YAML or JSON file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read sweep spec {}: {}", path.display(), e))?;
+        // YAML is a superset of JSON, so one parser covers both
+        let spec: SweepSpec = serde_yaml::from_str(&contents)
+            .map_err(|e| format!("Invalid sweep spec {}: {}", path.display(), e))?;
+        Ok(spec)
+    }
+
+    /// Expand into the full list of runs (points x replicates), validating every config first.
+    /// `base_seed` is used when the spec has no `seed`.
+    pub fn expand(
+        &self,
+        base: &SimulationConfig,
+        base_seed: u64,
+    ) -> Result<Vec<SweepRun>, Box<dyn std::error::Error>> {
+        if self.replicates == 0 {
+            return Err("replicates must be at least 1".into());
+        }
+        let base = base
+            .with_patch(&serde_json::Value::Object(self.base.clone()))
+            .map_err(|e| format!("Invalid sweep base: {}", e))?
+            .0;
+
+        let mut axes = Vec::new();
+        for (field, values) in &self.parameters {
+            axes.push((field.clone(), values.expand(field)?));
+        }
+
+        // Cartesian product of all axes (the last field varies fastest)
+        let mut points: Vec<Vec<(String, serde_json::Value)>> = vec![Vec::new()];
+        for (field, values) in &axes {
+            points = points
+                .into_iter()
+                .flat_map(|point| {
+                    values.iter().map(move |value| {
+                        let mut point = point.clone();
+                        point.push((field.clone(), value.clone()));
+                        point
+                    })
+                })
+                .collect();
+        }
+
+        let first_seed = self.seed.unwrap_or(base_seed);
+        let mut runs = Vec::with_capacity(points.len() * self.replicates as usize);
+        for (point, params) in points.into_iter().enumerate() {
+            let patch: serde_json::Map<_, _> = params.iter().cloned().collect();
+            let mut config = base
+                .with_patch(&serde_json::Value::Object(patch))
+                .map_err(|e| format!("Invalid sweep point {}: {}", point, e))?
+                .0;
+            config
+                .validate()
+                .map_err(|e| format!("Sweep point {} ({}): {}", point, describe(&params), e))?;
+            for replicate in 0..self.replicates {
+                let seed = first_seed.wrapping_add(replicate as u64);
+                config.seed = Some(seed);
+                runs.push(SweepRun {
+                    run_id: format!("p{:03}_r{:02}", point, replicate),
+                    point,
+                    replicate,
+                    seed,
+                    params: params.clone(),
+                    config: config.clone(),
+                });
+            }
+        }
+        Ok(runs)
+    }
+}
+
+fn describe(params: &[(String, serde_json::Value)]) -> String {
+    params
+        .iter()
+        .map(|(field, value)| format!("{}={}", field, value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Run every sweep run in parallel on the rayon thread pool (results keep the run order).
+/// With `save_runs`, each run's outputs go to `out_dir/runs/<run_id>/`.
+pub fn run_sweep(
+    runs: &[SweepRun],
+    steps: u64,
+    save_runs: bool,
+    out_dir: &Path,
+    progress: bool,
+) -> Result<Vec<SweepResult>, String> {
+    let finished = AtomicUsize::new(0);
+    runs.par_iter()
+        .map(|run| {
+            let mut rng = create_rng(run.seed);
+            let mut sim = Simulation::try_with_config(&mut rng, run.config.clone())
+                .map_err(|e| format!("Run {}: {}", run.run_id, e))?;
+            let summary = step_and_summarize(&mut sim, &mut rng, steps, false);
+            if save_runs {
+                let dir = out_dir.join("runs").join(&run.run_id);
+                std::fs::create_dir_all(&dir)
+                    .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+                write_batch_outputs(&sim, &rng, &summary, &dir)
+                    .map_err(|e| format!("Run {}: {}", run.run_id, e))?;
+            }
+            if progress {
+                let done = finished.fetch_add(1, Ordering::Relaxed) + 1;
+                eprintln!(
+                    "  [{}/{}] {} ({}) finished in {:.1}s",
+                    done,
+                    runs.len(),
+                    run.run_id,
+                    describe(&run.params),
+                    summary.elapsed_secs
+                );
+            }
+            Ok(SweepResult {
+                run: run.clone(),
+                summary,
+            })
+        })
+        .collect()
+}
+
+/// Write a tidy table: one row per run with its parameters, seed and final metrics.
+pub fn write_summary_csv<W: Write>(
+    mut writer: W,
+    results: &[SweepResult],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let param_names: Vec<&str> = results
+        .first()
+        .map(|r| r.run.params.iter().map(|(name, _)| name.as_str()).collect())
+        .unwrap_or_default();
+
+    write!(writer, "run_id,point,replicate,seed")?;
+    for name in &param_names {
+        write!(writer, ",{}", csv_field(name))?;
+    }
+    writeln!(
+        writer,
+        ",frame_index,elapsed_secs,hyphae_count,spores_count,connections_count,fruit_count,\
+         avg_energy,total_energy,component_count,largest_component,loop_count,mean_degree,\
+         mean_shortest_path,global_efficiency,betweenness_max,total_length"
+    )?;
+
+    for result in results {
+        let run = &result.run;
+        let s = &result.summary;
+        let a = &s.analysis;
+        write!(
+            writer,
+            "{},{},{},{}",
+            csv_field(&run.run_id),
+            run.point,
+            run.replicate,
+            run.seed
+        )?;
+        for (_, value) in &run.params {
+            let text = match value {
+                serde_json::Value::String(text) => Cow::Borrowed(text.as_str()),
+                value => Cow::Owned(value.to_string()),
+            };
+            write!(writer, ",{}", csv_field(&text))?;
+        }
+        writeln!(
+            writer,
+            ",{},{:.3},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            s.frame_index,
+            s.elapsed_secs,
+            s.stats.hyphae_count,
+            s.stats.spores_count,
+            s.stats.connections_count,
+            s.stats.fruit_count,
+            s.stats.avg_energy,
+            s.stats.total_energy,
+            a.component_count,
+            a.largest_component,
+            a.loop_count,
+            a.mean_degree,
+            a.mean_shortest_path,
+            a.global_efficiency,
+            a.betweenness.max,
+            a.total_length
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn small_spec() -> SweepSpec {
+        serde_yaml::from_str(
+            "
+base:
+  grid_size: 30
+parameters:
+  branch_prob: [0.004, 0.02]
+  memory_influence: { from: 0.0, to: 0.3, step: 0.1 }
+replicates: 2
+steps: 20
+seed: 7
+",
+        )
+        .unwrap()
+    }
+
+    /// Test that a spec expands into every combination of values and replicate seeds
+    #[test]
+    fn test_sweep_expansion() {
+        let runs = small_spec()
+            .expand(&SimulationConfig::default(), 0)
+            .unwrap();
+        assert_eq!(runs.len(), 2 * 4 * 2);
+        assert_eq!(runs[0].config.grid_size, 30);
+        assert_eq!(runs[0].params[1].1, serde_json::json!(0));
+        assert_eq!(runs[2].params[1].1, serde_json::json!(0.1));
+        assert_eq!(runs[7].params[1].1, serde_json::json!(0.3));
+        // Replicates share seeds across points
+        assert_eq!((runs[0].seed, runs[1].seed, runs[2].seed), (7, 8, 7));
+    }
+
+    /// Test that a spec producing an invalid config is rejected before anything runs
+    #[test]
+    fn test_sweep_rejects_invalid_values() {
+        let bad: SweepSpec =
+            serde_yaml::from_str("parameters: {step_size: [-1.0]}\nsteps: 1").unwrap();
+        assert!(bad.expand(&SimulationConfig::default(), 0).is_err());
+    }
+
+    /// Test that the summary CSV has one row per run, parameters first
+    #[test]
+    fn test_sweep_summary_csv() {
+        let spec = small_spec();
+        let runs = spec.expand(&SimulationConfig::default(), 0).unwrap();
+        let results = run_sweep(&runs[..4], spec.steps, false, Path::new("."), false).unwrap();
+        let mut csv = Vec::new();
+        write_summary_csv(&mut csv, &results).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with("run_id,point,replicate,seed,branch_prob,memory_influence,"));
+        assert!(lines[1].starts_with("p000_r00,0,0,7,0.004,0,20,"));
+    }
+
+    /// Test that values with commas or quotes are quoted so later columns stay in place
+    #[test]
+    fn test_sweep_csv_quoting() {
+        let spec = small_spec();
+        let runs = spec.expand(&SimulationConfig::default(), 0).unwrap();
+        let mut results = run_sweep(&runs[..1], spec.steps, false, Path::new("."), false).unwrap();
+        results[0].run.params = vec![
+            ("sugar_map".into(), serde_json::json!("maps/a,b.png")),
+            ("label".into(), serde_json::json!("say \"hi\"")),
+            ("sizes".into(), serde_json::json!([1, 2])),
+        ];
+        let mut csv = Vec::new();
+        write_summary_csv(&mut csv, &results).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("p000_r00,0,0,7,\"maps/a,b.png\",\"say \"\"hi\"\"\",\"[1,2]\",20,"));
+    }
+
+    /// Test that the same seed and config reproduce the same run, even in parallel
+    #[test]
+    fn test_sweep_reproducible() {
+        let spec = small_spec();
+        let runs = spec.expand(&SimulationConfig::default(), 0).unwrap();
+        let results = run_sweep(&runs[..4], spec.steps, false, Path::new("."), false).unwrap();
+        let again = run_sweep(&runs[..1], spec.steps, false, Path::new("."), false).unwrap();
+        assert_eq!(
+            again[0].summary.stats.total_energy,
+            results[0].summary.stats.total_energy
+        );
+    }
+}
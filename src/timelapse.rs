@@ -0,0 +1,224 @@
+// Time-lapse recording - render a frame every N steps into a numbered PNG sequence
+// (frame_000000.png, frame_000001.png, ...) and optionally an animated GIF built as frames arrive
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use crate::render::RenderOptions;
+use crate::simulation::Simulation;
+
+pub const GIF_FILE: &str = "timelapse.gif";
+
+// Quantization speed for GIF frames (1 = best quality, 30 = fastest)
+const GIF_SPEED: i32 = 10;
+
+/// An active recording. Frames use the layers currently shown in the window and the
+/// `timelapse_*` config fields as they are at each capture (`timelapse_gif` only counts at the start).
+pub struct Timelapse {
+    dir: PathBuf,
+    frames: usize,
+    // Unbuffered so every finished frame is on disk, even if the process is killed
+    gif: Option<GifEncoder<File>>,
+}
+
+impl Timelapse {
+    /// Create the output directory, plus the GIF file when `gif` is set.
+    pub fn start<P: AsRef<Path>>(dir: P, gif: bool) -> Result<Self, Box<dyn std::error::Error>> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let gif = if gif {
+            let path = dir.join(GIF_FILE);
+            let file = File::create(&path)
+                .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+            let mut encoder = GifEncoder::new_with_speed(file, GIF_SPEED);
+            encoder.set_repeat(Repeat::Infinite)?;
+            Some(encoder)
+        } else {
+            None
+        };
+        Ok(Self {
+            dir,
+            frames: 0,
+            gif,
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames
+    }
+
+    pub fn gif_path(&self) -> Option<PathBuf> {
+        self.gif.as_ref().map(|_| self.dir.join(GIF_FILE))
+    }
+
+    pub fn frame_path(&self, number: usize) -> PathBuf {
+        self.dir.join(format!("frame_{:06}.png", number))
+    }
+
+    /// Encode a rendered frame as the next PNG of the sequence (and the next GIF frame).
+    pub fn write(&mut self, frame: TimelapseFrame) -> Result<(), Box<dyn std::error::Error>> {
+        let path = self.frame_path(self.frames);
+        frame
+            .image
+            .save_with_format(&path, image::ImageFormat::Png)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        if let Some(encoder) = &mut self.gif {
+            let delay = Delay::from_numer_denom_ms(frame.delay_ms, 1);
+            encoder.encode_frame(Frame::from_parts(frame.image, 0, 0, delay))?;
+        }
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Report a failed `write`, after which the recording should be dropped
+    pub fn report_stopped(&self, error: &dyn std::fmt::Display) {
+        eprintln!("Time-lapse stopped after {} frames: {}", self.frames, error);
+    }
+}
+
+/// A rendered frame, waiting to be encoded by `Timelapse::write`. Rendering needs the
+/// simulation; encoding doesn't, so it can run after a lock on the simulation is released.
+pub struct TimelapseFrame {
+    image: RgbaImage,
+    delay_ms: u32,
+}
+
+impl TimelapseFrame {
+    fn render(sim: &Simulation) -> Self {
+        // Frames of big grids shrink to the render size limit rather than ending the recording
+        let options =
+            RenderOptions::from_view(sim, sim.fit_render_scale(sim.config.timelapse_scale));
+        Self {
+            image: sim.render_image(&options),
+            delay_ms: sim.config.timelapse_gif_delay_ms,
+        }
+    }
+}
+
+impl Simulation {
+    /// Start recording into `dir`, beginning with the current frame. Stops any running recording.
+    pub fn start_timelapse<P: AsRef<Path>>(
+        &mut self,
+        dir: P,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.stop_timelapse();
+        let mut timelapse = Timelapse::start(dir, self.config.timelapse_gif)?;
+        timelapse.write(TimelapseFrame::render(self))?;
+        self.timelapse = Some(timelapse);
+        Ok(())
+    }
+
+    /// Stop recording. Dropping the returned recording finishes the GIF.
+    pub fn stop_timelapse(&mut self) -> Option<Timelapse> {
+        self.timelapse.take()
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.timelapse.is_some()
+    }
+
+    /// The frame due after the latest step, when recording and the interval is reached.
+    pub fn timelapse_frame(&self) -> Option<TimelapseFrame> {
+        let interval = u64::from(self.config.timelapse_interval.max(1));
+        if self.timelapse.is_none() || !self.state.frame_index.is_multiple_of(interval) {
+            return None;
+        }
+        Some(TimelapseFrame::render(self))
+    }
+
+    /// Capture a frame when the interval is reached. `step` doesn't record, so call this
+    /// after each step; a failed capture is reported and ends the recording.
+    pub fn record_timelapse(&mut self) {
+        let Some(frame) = self.timelapse_frame() else {
+            return;
+        };
+        let Some(mut timelapse) = self.timelapse.take() else {
+            return;
+        };
+        match timelapse.write(frame) {
+            Ok(()) => self.timelapse = Some(timelapse),
+            Err(e) => timelapse.report_stopped(&e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimulationConfig;
+    use crate::simulation::{create_rng, SimRng};
+
+    fn recording_simulation(name: &str, gif: bool) -> (Simulation, SimRng, PathBuf) {
+        let config = SimulationConfig {
+            grid_size: 20,
+            timelapse_interval: 5,
+            timelapse_scale: 0.5,
+            timelapse_gif: gif,
+            ..SimulationConfig::default()
+        };
+        let mut rng = create_rng(4);
+        let mut sim = Simulation::with_config(&mut rng, config);
+        let dir = std::env::temp_dir().join(format!(
+            "mycorust_timelapse_{}_{}",
+            name,
+            std::process::id()
+        ));
+        sim.start_timelapse(&dir).unwrap();
+        (sim, rng, dir)
+    }
+
+    /// Test that a frame is written at the start and then every `timelapse_interval` steps
+    #[test]
+    fn test_timelapse_frames() {
+        let (mut sim, mut rng, dir) = recording_simulation("frames", false);
+        assert!(sim.is_recording());
+        for _ in 0..12 {
+            sim.step(&mut rng);
+            sim.record_timelapse();
+        }
+        // The starting frame plus frames 5 and 10
+        let timelapse = sim.stop_timelapse().unwrap();
+        assert!(!sim.is_recording());
+        assert_eq!(timelapse.frame_count(), 3);
+        assert!(timelapse.frame_path(2).exists());
+        assert!(!timelapse.frame_path(3).exists());
+        assert!(timelapse.gif_path().is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Test that stepping alone doesn't record; the caller captures frames
+    #[test]
+    fn test_timelapse_not_recorded_by_step() {
+        let (mut sim, mut rng, dir) = recording_simulation("step", false);
+        for _ in 0..10 {
+            sim.step(&mut rng);
+        }
+        assert_eq!(sim.stop_timelapse().unwrap().frame_count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Test that the GIF gets every frame and is finished when the recording is dropped
+    #[test]
+    fn test_timelapse_gif() {
+        let (mut sim, mut rng, dir) = recording_simulation("gif", true);
+        for _ in 0..5 {
+            sim.step(&mut rng);
+            sim.record_timelapse();
+        }
+        let timelapse = sim.stop_timelapse().unwrap();
+        let gif_path = timelapse.gif_path().unwrap();
+        drop(timelapse);
+
+        let gif = std::fs::read(gif_path).unwrap();
+        assert!(gif.starts_with(b"GIF89a"));
+        assert_eq!(gif.last(), Some(&0x3B)); // Trailer written when the recording is dropped
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
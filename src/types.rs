@@ -1,5 +1,6 @@
 #[cfg(all(not(test), feature = "ui"))]
 use macroquad::prelude::Vec2;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 #[cfg(any(test, not(feature = "ui")))]
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -15,21 +16,47 @@ impl Vec2 {
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct Connection {
-    pub hypha1: usize,
-    pub hypha2: usize,
+    pub hypha1: u64, // Hypha ids (see `SimulationState::hypha_slot`)
+    pub hypha2: u64,
     // Network intelligence: track connection strength and signals
     pub strength: f32,         // Connection strength (0.0-1.0), increases with flow
     pub signal: f32,           // Current signal strength propagating through
     pub flow_accumulator: f32, // Accumulated nutrient flow for reinforcement learning
 }
 
+impl Connection {
+    /// Hypha id pair with the smaller id first, as stored in `connection_set`
+    pub fn key(&self) -> (u64, u64) {
+        (self.hypha1.min(self.hypha2), self.hypha1.max(self.hypha2))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct Segment {
+    #[serde(with = "vec2_serde")]
     pub from: Vec2,
+    #[serde(with = "vec2_serde")]
     pub to: Vec2,
     pub age: f32,
 }
 
+// Vec2 is macroquad's type in UI builds, so serialize it as a plain (x, y) pair
+mod vec2_serde {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(v: &Vec2, serializer: S) -> Result<S::Ok, S::Error> {
+        (v.x, v.y).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec2, D::Error> {
+        let (x, y) = <(f32, f32)>::deserialize(deserializer)?;
+        Ok(Vec2::new(x, y))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct FruitBody {
     pub x: f32,
     pub y: f32,
@@ -41,7 +68,7 @@ pub struct FruitBody {
 }
 
 // Zone types for contaminants/competitors
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ZoneType {
     None,           // No zone
     Toxic,          // Toxic zone - harms hyphae
@@ -49,7 +76,7 @@ pub enum ZoneType {
     Deadwood,       // Deadwood patch - nutrient-rich but may have mild effects
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Zone {
     pub zone_type: ZoneType,
     pub intensity: f32, // Intensity/strength of the zone (0.0-1.0)
@@ -2,8 +2,11 @@ use macroquad::prelude::*;
 
 use crate::config::SimulationConfig;
 use crate::controls::get_controls_text;
+use crate::grid::Grid2D;
 use crate::hypha::Hypha;
 use crate::nutrients::{nutrient_color, NutrientGrid};
+use crate::profile::ProfileReport;
+use crate::simulation::SimulationState;
 use crate::types::{Connection, FruitBody, Segment};
 
 pub fn draw_nutrients(nutrients: &NutrientGrid, config: &SimulationConfig) {
@@ -26,7 +29,7 @@ pub fn draw_nutrients(nutrients: &NutrientGrid, config: &SimulationConfig) {
 }
 
 // Heatmap: Draw moisture overlay
-pub fn draw_heatmap_moisture(moisture: &[Vec<f32>], config: &SimulationConfig) {
+pub fn draw_heatmap_moisture(moisture: &Grid2D<f32>, config: &SimulationConfig) {
     let grid_size = config.grid_size;
     let cell_size = config.cell_size;
     for x in 0..grid_size {
@@ -49,8 +52,8 @@ pub fn draw_heatmap_moisture(moisture: &[Vec<f32>], config: &SimulationConfig) {
 pub fn draw_heatmap_age(hyphae: &[Hypha], config: &SimulationConfig) {
     let grid_size = config.grid_size;
     let cell_size = config.cell_size;
-    let mut age_map = vec![vec![0.0f32; grid_size]; grid_size];
-    let mut count_map = vec![vec![0u32; grid_size]; grid_size];
+    let mut age_map = Grid2D::new(grid_size, grid_size, 0.0f32);
+    let mut count_map = Grid2D::new(grid_size, grid_size, 0u32);
 
     // Accumulate age values per cell
     for h in hyphae.iter().filter(|h| h.alive) {
@@ -98,7 +101,7 @@ pub fn draw_heatmap_flow(
 ) {
     let grid_size = config.grid_size;
     let cell_size = config.cell_size;
-    let mut flow_map = vec![vec![0.0f32; grid_size]; grid_size];
+    let mut flow_map = Grid2D::new(grid_size, grid_size, 0.0f32);
 
     // Accumulate flow values per cell
     for (idx, h) in hyphae.iter().enumerate().filter(|(_, h)| h.alive) {
@@ -144,8 +147,8 @@ pub fn draw_heatmap_flow(
 pub fn draw_heatmap_growth(
     _hyphae: &[Hypha],
     nutrients: &NutrientGrid,
-    moisture: &[Vec<f32>],
-    light_exposure: &[Vec<f32>],
+    moisture: &Grid2D<f32>,
+    light_exposure: &Grid2D<f32>,
     config: &SimulationConfig,
 ) {
     let grid_size = config.grid_size;
@@ -206,7 +209,7 @@ pub fn draw_heatmap_growth(
 }
 
 // Network Intelligence: Draw memory overlay (subtle purple/blue tint)
-pub fn draw_memory_overlay(memory: &[Vec<f32>], memory_visible: bool, config: &SimulationConfig) {
+pub fn draw_memory_overlay(memory: &Grid2D<f32>, memory_visible: bool, config: &SimulationConfig) {
     if !memory_visible {
         return;
     }
@@ -235,12 +238,12 @@ pub fn draw_memory_overlay(memory: &[Vec<f32>], memory_visible: bool, config: &S
     }
 }
 
-pub fn draw_zones(zones: &[Vec<crate::types::Zone>], config: &SimulationConfig) {
+pub fn draw_zones(zones: &Grid2D<crate::types::Zone>, config: &SimulationConfig) {
     use crate::types::ZoneType;
     let cell_size = config.cell_size;
 
-    for x in 0..zones.len() {
-        for y in 0..zones[x].len() {
+    for x in 0..zones.width() {
+        for y in 0..zones.height() {
             let zone = &zones[x][y];
             if zone.zone_type == ZoneType::None {
                 continue;
@@ -262,7 +265,7 @@ pub fn draw_zones(zones: &[Vec<crate::types::Zone>], config: &SimulationConfig)
     }
 }
 
-pub fn draw_obstacles(obstacles: &[Vec<bool>], config: &SimulationConfig) {
+pub fn draw_obstacles(obstacles: &Grid2D<bool>, config: &SimulationConfig) {
     let grid_size = config.grid_size;
     let cell_size = config.cell_size;
     #[allow(clippy::needless_range_loop)]
@@ -483,11 +486,11 @@ pub fn draw_hyphae_enhanced(
 }
 
 pub fn draw_connections(
-    connections: &[Connection],
-    hyphae: &[Hypha],
+    state: &SimulationState,
     connections_visible: bool,
     config: &SimulationConfig,
 ) {
+    let connections = &state.connections;
     let cell_size = config.cell_size;
     if !connections_visible || connections.is_empty() {
         return;
@@ -526,7 +529,7 @@ pub fn draw_connections(
     let pulse = (t * 2.0).sin() * 0.25 + 0.5; // 0.25..0.75
 
     for conn in connections.iter() {
-        if let (Some(h1), Some(h2)) = (hyphae.get(conn.hypha1), hyphae.get(conn.hypha2)) {
+        if let (Some(h1), Some(h2)) = (state.hypha(conn.hypha1), state.hypha(conn.hypha2)) {
             if h1.alive && h2.alive {
                 let x1 = h1.x * cell_size;
                 let y1 = h1.y * cell_size;
@@ -707,6 +710,7 @@ pub fn draw_fruit_bodies(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn draw_stats_and_help(
     hyphae_count: usize,
     spores_count: usize,
@@ -786,6 +790,86 @@ pub fn draw_stats_and_help(
     // This will be controlled by the help_popup_visible flag passed from main
 }
 
+/// Draw the step profile overlay: time per phase over the recent window, its share of the step,
+/// the run mean and the phase's work counters. Phases running well above their run mean are
+/// highlighted, which is where a run that slows down over time is losing its frames.
+pub fn draw_profile_overlay(report: &ProfileReport) {
+    let x0 = 10.0;
+    let y0 = 140.0;
+    let line_height = 16.0;
+    let font_size = 15.0;
+    let width = 720.0;
+    let height = line_height * (report.phases.len() as f32 + 2.0) + 8.0;
+    draw_rectangle(
+        x0 - 4.0,
+        y0 - 4.0,
+        width,
+        height,
+        Color::new(0.0, 0.0, 0.0, 0.6),
+    );
+
+    let mut y = y0 + line_height - 4.0;
+    draw_text(
+        &format!(
+            "Step: {:.2} ms last | {:.2} ms recent ({} steps) | {:.2} ms run mean",
+            report.last_step_ms, report.recent_step_ms, report.window, report.mean_step_ms
+        ),
+        x0,
+        y,
+        font_size,
+        WHITE,
+    );
+    // Column x offsets: phase, recent ms, share, run mean ms, work counters
+    let columns = [x0, x0 + 110.0, x0 + 185.0, x0 + 245.0, x0 + 320.0];
+    y += line_height;
+    let header = [
+        "phase",
+        "recent ms",
+        "share",
+        "run ms",
+        "work per step (recent)",
+    ];
+    for (text, x) in header.iter().zip(columns) {
+        draw_text(text, x, y, font_size, Color::new(0.7, 0.7, 0.7, 1.0));
+    }
+
+    for phase in &report.phases {
+        y += line_height;
+        let slower = phase.recent_ms > 0.1 && phase.recent_ms > phase.mean_ms * 1.5;
+        let color = if slower {
+            ORANGE
+        } else {
+            Color::new(1.0, 1.0, 1.0, 0.9)
+        };
+        let counters: Vec<String> = phase
+            .counters
+            .iter()
+            .map(|(name, counter)| format!("{} {}", name, compact_count(counter.recent)))
+            .collect();
+        let cells = [
+            phase.name.to_string(),
+            format!("{:.3}", phase.recent_ms),
+            format!("{:.1}%", phase.share * 100.0),
+            format!("{:.3}", phase.mean_ms),
+            counters.join("  "),
+        ];
+        for (text, x) in cells.iter().zip(columns) {
+            draw_text(text, x, y, font_size, color);
+        }
+    }
+}
+
+// Short form of a per-step count (950, 12.3k, 4.1M)
+fn compact_count(value: f64) -> String {
+    if value >= 1_000_000.0 {
+        format!("{:.1}M", value / 1_000_000.0)
+    } else if value >= 1_000.0 {
+        format!("{:.1}k", value / 1_000.0)
+    } else {
+        format!("{:.0}", value)
+    }
+}
+
 /// Draw help popup window with controls
 pub fn draw_help_popup(camera_enabled: bool) {
     let screen_width = screen_width();
@@ -3,8 +3,9 @@
 
 use ::rand as external_rand;
 use external_rand::Rng;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Season {
     Spring,
     Summer,
@@ -13,7 +14,7 @@ pub enum Season {
 }
 
 /// Weather conditions that affect mycelium growth
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Weather {
     // Temperature in arbitrary units (0.0 = freezing, 1.0 = optimal, 2.0 = too hot)
     pub temperature: f32,
@@ -29,6 +30,12 @@ pub struct Weather {
     pub seasonal_cycle_enabled: bool,
 }
 
+impl Default for Weather {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Weather {
     pub fn new() -> Self {
         Self {